@@ -1,13 +1,29 @@
 use anyhow::Result;
 use log::info;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
 use serde::{Serialize, Deserialize};
 
-use crate::process::{Process, ProcessState};
-use crate::scheduler::{Scheduler, RoundRobinScheduler, SjfScheduler, FifoScheduler};
-use crate::modules::mem::paging::FrameManager;
-use crate::modules::mem::buddy::BuddyAllocator;
-use crate::modules::ipc::sync::ProducerConsumerBuffer;
+use crate::process::{MemoryPattern, Process, ProcessState};
+use crate::scheduler::{Scheduler, RoundRobinScheduler, SjfScheduler, FifoScheduler, PriorityScheduler};
+use crate::modules::mem::paging::{FrameManager, ReplacementAlgorithm};
+use crate::modules::mem::buddy::BuddyStats;
+use crate::modules::mem::zone::{ZoneKind, ZonedHeap};
+use crate::modules::mem::contiguous::{ContiguousAllocator, FitStrategy};
+use crate::modules::mem::slab::{SlabAllocator, KERNEL_PID};
+use crate::modules::mem::cache::{CacheConfig, CacheHierarchy};
+use crate::modules::mem::trace::{TraceOp, TraceReader};
+use crate::modules::mem::segmentation::{AccessKind, SegmentKind, SegmentTarget, SegmentationManager, SegmentationMode, SEGMENT_REGION_PAGES};
+use crate::modules::mem::tlb::{AccessLatencies, TlbPolicy};
+use crate::modules::mem::address::{AddressingConfig, PageTableKind};
+use crate::modules::mem::swap::SwapConfig;
+use crate::modules::mem::allocation::{
+ FrameAllocationPolicy, ReplacementScope, PffConfig, ThrashingConfig, ThrashingMonitor, ThrashingVerdict
+};
+use crate::modules::ipc::IpcRegistry;
+use crate::modules::ipc::deadlock::{DeadlockReport, Recovery, RequestOutcome, ResourceManager};
+use crate::modules::ipc::mailbox::{self, Address, Message, RecvOutcome, SendOutcome};
+use crate::modules::ipc::sync::{BufferOp, BufferReport, BufferResult, MutexProtocol, ProducerConsumerBuffer};
 
 /// Estado global del kernel
 #[derive(Serialize, Deserialize)]
@@ -21,12 +37,38 @@ pub struct KernelState {
 
  current_process: Option<u32>,
  memory_manager: FrameManager,
- heap_allocator: BuddyAllocator,
+ #[serde(default)]
+ thrashing: ThrashingMonitor,
+ #[serde(default)]
+ demand_paging: DemandPagingConfig,
+ #[serde(default)]
+ fault_waits: HashMap<u32, u64>, // pid -> tiempo en que su página queda cargada
+ #[serde(default)]
+ heap: ZonedHeap,
+ #[serde(default = "default_contiguous_heap")]
+ contiguous_heap: ContiguousAllocator,
+ #[serde(default)]
+ slab: SlabAllocator,
+ #[serde(default)]
+ heap_leaks: Vec<HeapLeak>, // Bloques que seguían asignados al terminar su proceso
+ #[serde(default)]
+ segmentation: SegmentationManager,
+ #[serde(default)]
+ cache: Option<CacheHierarchy>, // Caché de CPU alimentada con las direcciones físicas accedidas
  producer_consumer: ProducerConsumerBuffer,
+ #[serde(default)]
+ ipc: IpcRegistry,
+ #[serde(default)]
+ resources: ResourceManager, // Recursos con varias instancias (detección de interbloqueos)
  current_time: u64,
  finished_processes: Vec<Process>,
 }
 
+/// Heap contiguo del mismo tamaño que el heap buddy por defecto
+fn default_contiguous_heap() -> ContiguousAllocator {
+ ContiguousAllocator::new(4096)
+}
+
 /// Función para satisfacer el `default` de serde para el scheduler.
 fn default_scheduler() -> Box<dyn Scheduler> {
  Box::new(FifoScheduler::new()) // Un valor por defecto temporal
@@ -41,8 +83,18 @@ impl KernelState {
  scheduler_type,
  current_process: None,
  memory_manager: FrameManager::new(num_frames),
- heap_allocator: BuddyAllocator::new(4096, 64), // 4KB total, bloques mínimos de 64 bytes
+ thrashing: ThrashingMonitor::default(),
+ demand_paging: DemandPagingConfig::default(),
+ fault_waits: HashMap::new(),
+ heap: ZonedHeap::default(),
+ contiguous_heap: default_contiguous_heap(),
+ slab: SlabAllocator::default(),
+ heap_leaks: Vec::new(),
+ segmentation: SegmentationManager::default(),
+ cache: None,
  producer_consumer: ProducerConsumerBuffer::new(5),
+ ipc: IpcRegistry::new(),
+ resources: ResourceManager::default(),
  current_time: 0,
  finished_processes: Vec::new(),
  }
@@ -53,15 +105,86 @@ impl KernelState {
  SchedulerType::RoundRobin(quantum) => Box::new(RoundRobinScheduler::new(quantum)),
  SchedulerType::SJF => Box::new(SjfScheduler::new()),
  SchedulerType::FIFO => Box::new(FifoScheduler::new()),
+ SchedulerType::Priority => Box::new(PriorityScheduler::new()),
+ }
+ }
+
+ /// Configurar la TLB y las latencias de acceso a memoria
+ pub fn configure_tlb(&mut self, capacity: usize, policy: TlbPolicy, flush_on_switch: bool, latencies: AccessLatencies) {
+ self.memory_manager.configure_tlb(capacity, policy, flush_on_switch);
+ self.memory_manager.set_latencies(latencies);
+ }
+
+ /// Configurar tamaño de página y tabla de páginas
+ pub fn configure_addressing(&mut self, config: AddressingConfig) {
+ self.memory_manager.configure_addressing(config);
+ }
+
+ /// Configurar asignación de marcos, PFF y detección de thrashing
+ pub fn configure_frame_allocation(&mut self, policy: FrameAllocationPolicy, scope: ReplacementScope, pff: PffConfig, thrashing: ThrashingConfig) {
+ self.memory_manager.configure_allocation(policy, scope, pff);
+ self.thrashing = ThrashingMonitor::new(thrashing);
+ self.rebalance_frames();
+ }
+
+ /// Configurar tamaño y zonas del heap (el heap contiguo usa el mismo tamaño total)
+ pub fn configure_heap(&mut self, size: usize, min_block: usize, dma_size: usize) -> Result<(), String> {
+ self.heap = ZonedHeap::new(size, min_block, dma_size)?;
+ self.contiguous_heap = ContiguousAllocator::new(self.heap.total_size());
+ Ok(())
+ }
+
+ /// Activar la caché de CPU (L1 y L2 opcional)
+ pub fn configure_cache(&mut self, l1: CacheConfig, l2: Option<CacheConfig>) -> Result<(), String> {
+ self.cache = Some(CacheHierarchy::new(l1, l2)?);
+ Ok(())
  }
+
+ /// Pasar por la caché el acceso a una dirección física
+ fn feed_cache(&mut self, physical_address: u64) {
+ if let Some(cache) = self.cache.as_mut() {
+ cache.access(physical_address);
+ }
+ }
+
+ /// Dirección física del inicio de un marco (las trazas por página no traen desplazamiento)
+ fn frame_address(&self, frame: usize) -> u64 {
+ (frame * self.memory_manager.addressing().page_size) as u64
+ }
+
+ /// Configurar el área de swap en el disco simulado
+ pub fn configure_swap(&mut self, config: SwapConfig) {
+ self.memory_manager.configure_swap(config);
+ }
+
+ /// Configurar los accesos a memoria que generan los procesos en cada tick
+ pub fn configure_demand_paging(&mut self, config: DemandPagingConfig) {
+ self.demand_paging = config;
+ }
+
+ /// Recalcular cuotas de marcos entre los procesos activos
+ fn rebalance_frames(&mut self) {
+ let mut active: Vec<(u32, usize)> = self.processes
+ .values()
+ .filter(|p| !self.thrashing.suspended.contains(&p.pid))
+ .map(|p| (p.pid, p.memory_req))
+ .collect();
+ active.sort();
+ self.memory_manager.rebalance(&active);
  }
 
  /// Crear nuevo proceso
  pub fn create_process(&mut self, cpu_burst: u64, memory_req: usize) -> u32 {
+ self.create_process_with_pattern(cpu_burst, memory_req, None)
+ }
+
+ /// Crear proceso que referencia memoria al ejecutar
+ pub fn create_process_with_pattern(&mut self, cpu_burst: u64, memory_req: usize, pattern: Option<MemoryPattern>) -> u32 {
  let pid = self.next_pid;
  self.next_pid += 1;
 
- let mut process = Process::with_arrival(pid, cpu_burst, memory_req, self.current_time);
+ let mut process = Process::with_arrival(pid, cpu_burst, memory_req, self.current_time)
+ .with_memory_pattern(pattern);
  process.state = ProcessState::Ready;
 
  info!("Proceso {} creado (burst={}, mem={})", pid, cpu_burst, memory_req);
@@ -71,10 +194,106 @@ impl KernelState {
 
  self.scheduler.push(process.clone());
  self.processes.insert(pid, process);
+ self.rebalance_frames();
 
  pid
  }
 
+ /// Crear un hijo que comparte el espacio de direcciones del padre (copy-on-write)
+ pub fn fork_process(&mut self, parent_pid: u32) -> Result<u32> {
+ let parent = self.processes.get(&parent_pid)
+ .ok_or_else(|| anyhow::anyhow!("Proceso {} no encontrado", parent_pid))?;
+ let (burst, memory_req, pattern) = (parent.remaining_burst.max(1), parent.memory_req, parent.memory_pattern.clone());
+
+ let child_pid = self.create_process_with_pattern(burst, memory_req, pattern);
+ let shared = self.memory_manager.fork(parent_pid, child_pid);
+ info!("fork: {} -> {} ({} páginas COW)", parent_pid, child_pid, shared);
+ println!("[INFO] {} página(s) compartidas en modo copy-on-write", shared);
+ Ok(child_pid)
+ }
+
+ /// Reemplazar la imagen del proceso: suelta todas sus páginas sin copiarlas
+ pub fn exec_process(&mut self, pid: u32, pattern: Option<MemoryPattern>) -> Result<()> {
+ let proc = self.processes.get_mut(&pid)
+ .ok_or_else(|| anyhow::anyhow!("Proceso {} no encontrado", pid))?;
+ proc.memory_pattern = pattern;
+ proc.retry_page = None;
+ self.memory_manager.exec(pid);
+ Ok(())
+ }
+
+ /// Escribir en páginas de un proceso (provoca copias si están compartidas)
+ pub fn write_memory(&mut self, pid: u32, page: usize) -> Result<()> {
+ let copies_before = self.memory_manager.cow_stats().copies;
+ let frame = self.memory_manager.write_page(pid, page, self.demand_paging.algorithm)
+ .map_err(|e| anyhow::anyhow!(e))?;
+ if self.memory_manager.cow_stats().copies > copies_before {
+ println!("[OK] Escritura en página {} del proceso {} -> copia en marco {} (COW)", page, pid, frame);
+ } else {
+ println!("[OK] Escritura en página {} del proceso {} -> Marco {}", page, pid, frame);
+ }
+ Ok(())
+ }
+
+ /// Mostrar métricas de copy-on-write
+ pub fn cow_status(&self) {
+ let cow = self.memory_manager.cow_stats();
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ COPY-ON-WRITE ║");
+ println!("╠════════════════════════════════════════════════════╣");
+ println!("║ Forks: {} ", cow.forks);
+ println!("║ Páginas compartidas al hacer fork: {} ", cow.shared_pages);
+ println!("║ Fallos COW: {} ", cow.cow_faults);
+ println!("║ Marcos copiados: {} ", cow.copies);
+ println!("║ Resueltos sin copiar (único dueño): {} ", cow.reclaimed);
+ println!("║ Copias evitadas: {} ", cow.copies_avoided());
+ println!("╚════════════════════════════════════════════════════╝");
+ }
+
+ /// Crear un segmento de memoria compartida
+ pub fn shm_create(&mut self, name: &str, pages: usize) -> Result<u32> {
+ self.memory_manager.shm_create(name, pages)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Conectar un proceso a un segmento; devuelve la página base
+ pub fn shm_attach(&mut self, pid: u32, name: &str, base: Option<usize>) -> Result<usize> {
+ if !self.processes.contains_key(&pid) {
+ return Err(anyhow::anyhow!("Proceso {} no encontrado", pid));
+ }
+ self.memory_manager.shm_attach(pid, name, base)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ pub fn shm_detach(&mut self, pid: u32, name: &str) -> Result<()> {
+ self.memory_manager.shm_detach(pid, name)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Marcar un segmento para destrucción; devuelve true si se eliminó en el acto
+ pub fn shm_destroy(&mut self, name: &str) -> Result<bool> {
+ self.memory_manager.shm_destroy(name)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Mostrar los segmentos de memoria compartida
+ pub fn shm_status(&self) {
+ println!("\n╔═════════════════════════════════════════════════════════════════╗");
+ println!("║ MEMORIA COMPARTIDA ║");
+ println!("╠════╦════════════╦═════════╦════════╦════════════╦═══════════════╣");
+ println!("║ ID ║ Nombre ║ Páginas ║ nattch ║ Residentes ║ Estado ║");
+ println!("╠════╬════════════╬═════════╬════════╬════════════╬═══════════════╣");
+ for seg in self.memory_manager.shm().segments() {
+ let state = if seg.marked_for_destroy { "a destruir" } else { "activo" };
+ println!("║ {:2} ║ {:10} ║ {:7} ║ {:6} ║ {:10} ║ {:13} ║",
+ seg.id, seg.name, seg.pages, seg.attach_count(), seg.resident_pages(), state);
+ for (pid, base) in seg.attachments() {
+ println!("║ ║ PID {:3} en páginas {:#x}-{:#x} ", pid, base, base + seg.pages - 1);
+ }
+ }
+ println!("╚════╩════════════╩═════════╩════════╩════════════╩═══════════════╝");
+ }
+
  /// Devuelve una lista de procesos en estado Ready.
  pub fn get_ready_processes(&self) -> Vec<Process> {
  self.processes
@@ -87,11 +306,11 @@ impl KernelState {
 
  /// Listar procesos
  pub fn list_processes(&self) {
- println!("\n╔═══════════════════════════════════════════════════════════╗");
+ println!("\n╔══════════════════════════════════════════════════════════════════╗");
  println!("║ LISTA DE PROCESOS ║");
- println!("╠═════╦═══════════╦═══════════════╦═══════════╦═════════════╣");
- println!("║ PID ║ Estado ║ Burst Restante║ Memoria ║ Llegada ║");
- println!("╠═════╬═══════════╬═══════════════╬═══════════╬═════════════╣");
+ println!("╠═════╦═══════════╦═══════════════╦═══════════╦═════════════╦══════╣");
+ println!("║ PID ║ Estado ║ Burst Restante║ Memoria ║ Llegada ║ Prio ║");
+ println!("╠═════╬═══════════╬═══════════════╬═══════════╬═════════════╬══════╣");
 
  let mut pids: Vec<_> = self.processes.keys().collect();
  pids.sort();
@@ -99,28 +318,29 @@ impl KernelState {
  for pid in pids {
  if let Some(proc) = self.processes.get(pid) {
  let state_str = format!("{:?}", proc.state);
- println!("║ {:3} ║ {:9} ║ {:4} ║ {:5} ║ {:3} ║",
+ println!("║ {:3} ║ {:9} ║ {:4} ║ {:5} ║ {:3} ║ {:4} ║",
  proc.pid,
  state_str,
  proc.remaining_burst,
  proc.memory_req,
- proc.arrival_time
+ proc.arrival_time,
+ proc.priority
  );
  }
  }
 
  if !self.finished_processes.is_empty() {
- println!("╠═════╩═══════════╩═══════════════╩═══════════╩═════════════╣");
+ println!("╠═════╩═══════════╩═══════════════╩═══════════╩═════════════╩══════╣");
  println!("║ PROCESOS TERMINADOS ║");
- println!("╠═════╦═══════════╦═══════════════╦═══════════╦═════════════╣");
+ println!("╠═════╦═══════════╦═══════════════╦═══════════╦═════════════╦══════╣");
  
  for proc in &self.finished_processes {
- println!("║ {:3} ║ Terminated║ 0 ║ {:5} ║ {:3} ║",
- proc.pid, proc.memory_req, proc.arrival_time);
+ println!("║ {:3} ║ Terminated║ 0 ║ {:5} ║ {:3} ║ {:4} ║",
+ proc.pid, proc.memory_req, proc.arrival_time, proc.base_priority);
  }
  }
 
- println!("╚═════╩═══════════╩═══════════════╩═══════════╩═════════════╝");
+ println!("╚═════╩═══════════╩═══════════════╩═══════════╩═════════════╩══════╝");
  println!("Scheduler activo: {} | Cola: {} procesos", 
  self.scheduler.name(), self.scheduler.len());
  }
@@ -130,18 +350,40 @@ impl KernelState {
  if let Some(mut proc) = self.processes.remove(&pid) {
  proc.mark_finished(self.current_time);
  info!("Proceso {} terminado forzosamente", pid);
+ self.scheduler.remove(pid);
+ self.fault_waits.remove(&pid);
+ self.release_process_ipc(pid);
  self.finished_processes.push(proc);
+ self.release_process_memory(pid);
  Ok(())
  } else {
  Err(anyhow::anyhow!("Proceso {} no encontrado", pid))
  }
  }
 
+ /// Soltar lo que un proceso que terminó (kill o fin de ráfaga) tiene en IPC:
+ /// mutexes, esperas en semáforos y buzones, su buzón directo, los recursos que
+ /// tenía asignados y su petición al buffer
+ fn release_process_ipc(&mut self, pid: u32) {
+ self.release_mutexes(pid);
+ self.ipc.remove_waiter(pid);
+ // Su buzón directo desaparece: quien esperaba para dejarle un mensaje se despierta
+ for waiter in self.ipc.remove_mailbox(&mailbox::direct_mailbox(pid)) {
+ self.unblock_process(waiter);
+ }
+ // Sus instancias vuelven al recurso y se atienden las peticiones que ya caben
+ for waiter in self.resources.remove_process(pid) {
+ self.unblock_process(waiter);
+ }
+ self.producer_consumer.cancel(pid);
+ }
+
  /// Suspender proceso (bloquearlo)
  pub fn suspend_process(&mut self, pid: u32) -> Result<()> {
  if let Some(proc) = self.processes.get_mut(&pid) {
  if proc.state == ProcessState::Running || proc.state == ProcessState::Ready {
  proc.state = ProcessState::Blocked;
+ self.scheduler.remove(pid);
  info!("Proceso {} suspendido", pid);
  Ok(())
  } else {
@@ -154,9 +396,26 @@ impl KernelState {
 
  /// Reanudar proceso (desbloquearlo)
  pub fn resume_process(&mut self, pid: u32) -> Result<()> {
+ if let Some(sem) = self.ipc.waiting_on(pid) {
+ return Err(anyhow::anyhow!("Proceso {} espera en el semáforo '{}': solo un signal lo despierta", pid, sem));
+ }
+ if let Some(mutex) = self.ipc.mutex_waited_by(pid) {
+ return Err(anyhow::anyhow!("Proceso {} espera el mutex '{}': solo un unlock lo despierta", pid, mutex.name()));
+ }
+ if let Some(mailbox) = self.ipc.mailbox_waited_by(pid) {
+ return Err(anyhow::anyhow!("Proceso {} está bloqueado en el buzón '{}'", pid, mailbox));
+ }
+ if self.producer_consumer.is_pending(pid) {
+ return Err(anyhow::anyhow!("Proceso {} espera en el buffer productor-consumidor", pid));
+ }
+ if self.resources.is_waiting(pid) {
+ return Err(anyhow::anyhow!("Proceso {} espera instancias de un recurso", pid));
+ }
  if let Some(proc) = self.processes.get_mut(&pid) {
  if proc.state == ProcessState::Blocked {
  proc.state = ProcessState::Ready;
+ self.fault_waits.remove(&pid);
+ self.thrashing.suspended.retain(|p| *p != pid);
  // Volver a agregarlo al scheduler
  self.scheduler.push(proc.clone());
  info!("Proceso {} reanudado", pid);
@@ -179,14 +438,27 @@ impl KernelState {
  self.current_time += 1;
  println!("\n[TIME] Tiempo: {} (paso {})", self.current_time, step + 1);
 
- // Obtener siguiente proceso del scheduler
- if let Some(mut process) = self.scheduler.next() {
+ self.wake_page_fault_waiters();
+
+ // Obtener siguiente proceso del scheduler; si falla de página se bloquea y se prueba con otro
+ let mut cpu_busy = false;
+ while let Some(mut process) = self.scheduler.next() {
  println!("-> CPU: Proceso {} ejecutando...", process.pid);
+ self.memory_manager.context_switch(process.pid);
  
  // Marcar como iniciado si es primera vez
  process.mark_started(self.current_time);
  process.state = ProcessState::Running;
 
+ if let Some(page) = self.issue_memory_accesses(&mut process) {
+ let wake_at = self.current_time + self.demand_paging.fault_service_ticks;
+ println!(" Fallo de página {}: bloqueado hasta t={}", page, wake_at);
+ process.state = ProcessState::Blocked;
+ self.fault_waits.insert(process.pid, wake_at);
+ self.processes.insert(process.pid, process);
+ continue;
+ }
+
  // Ejecutar por 1 unidad de tiempo
  let quantum_used = 1.min(process.remaining_burst);
  process.remaining_burst -= quantum_used;
@@ -199,7 +471,10 @@ impl KernelState {
  println!("[OK] Proceso {} TERMINADO", process.pid);
  // Eliminar de la lista de procesos activos y añadir la versión actualizada a los finalizados
  if self.processes.remove(&process.pid).is_some() {
+ let pid = process.pid;
  self.finished_processes.push(process);
+ self.release_process_ipc(pid);
+ self.release_process_memory(pid);
  }
  } else {
  // Volver a la cola
@@ -207,14 +482,142 @@ impl KernelState {
  self.processes.insert(process.pid, process.clone());
  self.scheduler.push(process.clone());
  }
- } else {
+ cpu_busy = true;
+ break;
+ }
+
+ if !cpu_busy {
+ if self.fault_waits.is_empty() {
  println!(" (CPU inactiva - no hay procesos)");
+ } else {
+ println!(" (CPU inactiva - {} proceso(s) esperando páginas)", self.fault_waits.len());
  }
  }
+ self.memory_housekeeping(cpu_busy);
+ }
 
  println!("\n[TIME] Tiempo actual: {}", self.current_time);
  }
 
+ /// Emitir las referencias a memoria del tick; devuelve la página que falló
+ /// La referencia que falla se repite al reanudar (reinicio de instrucción)
+ /// Con tiempo de servicio 0 los fallos se atienden sin bloquear
+ fn issue_memory_accesses(&mut self, process: &mut Process) -> Option<usize> {
+ for _ in 0..self.demand_paging.accesses_per_tick {
+ let page = match process.retry_page.take() {
+ Some(page) => page,
+ None => process.memory_pattern.as_mut()?.next_page()?,
+ };
+ let faults_before = self.memory_manager.page_faults();
+ match self.memory_manager.access_page(process.pid, page, self.demand_paging.algorithm) {
+ Ok(frame) => self.feed_cache(self.frame_address(frame)),
+ Err(e) => {
+ println!("[ERROR] Acceso a página {} del proceso {}: {}", page, process.pid, e);
+ continue;
+ }
+ }
+ if self.memory_manager.page_faults() > faults_before {
+ process.page_faults += 1;
+ if self.demand_paging.fault_service_ticks > 0 {
+ process.retry_page = Some(page);
+ return Some(page);
+ }
+ }
+ }
+ None
+ }
+
+ /// Devolver a la cola los procesos cuya página ya fue cargada
+ fn wake_page_fault_waiters(&mut self) {
+ let mut ready: Vec<u32> = self.fault_waits
+ .iter()
+ .filter(|(_, wake_at)| **wake_at <= self.current_time)
+ .map(|(pid, _)| *pid)
+ .collect();
+ ready.sort();
+
+ for pid in ready {
+ self.fault_waits.remove(&pid);
+ if let Some(proc) = self.processes.get_mut(&pid) {
+ if proc.state == ProcessState::Blocked {
+ proc.state = ProcessState::Ready;
+ self.scheduler.push(proc.clone());
+ println!("[MEM] Proceso {} listo: página cargada", pid);
+ }
+ }
+ }
+ }
+
+ /// Liberar marcos y cuota de un proceso que terminó
+ fn release_process_memory(&mut self, pid: u32) {
+ self.memory_manager.release_process(pid);
+ self.segmentation.release(pid);
+ self.reclaim_heap(pid);
+ self.thrashing.suspended.retain(|p| *p != pid);
+ self.rebalance_frames();
+ }
+
+ /// Ajustes periódicos de memoria: PFF y detección de thrashing
+ fn memory_housekeeping(&mut self, cpu_busy: bool) {
+ let pff = *self.memory_manager.pff();
+ if pff.enabled && self.current_time.is_multiple_of(pff.window.max(1)) {
+ for (pid, old, new) in self.memory_manager.adjust_pff() {
+ println!("[PFF] Proceso {}: {} -> {} marcos", pid, old, new);
+ }
+ }
+
+ self.thrashing.record_tick(cpu_busy);
+ if !self.thrashing.window_complete() {
+ return;
+ }
+
+ let stats = self.memory_manager.stats();
+ match self.thrashing.evaluate(stats.page_faults, stats.total_accesses) {
+ ThrashingVerdict::Thrashing { fault_rate, cpu_utilization } => {
+ println!("[THRASHING] Tasa de fallos {:.2}, uso de CPU {:.2}", fault_rate, cpu_utilization);
+
+ // Víctima: el proceso activo con mayor tasa de fallos
+ let victim = self.processes
+ .values()
+ .filter(|p| p.state != ProcessState::Blocked && p.state != ProcessState::Terminated)
+ .map(|p| {
+ let rate = self.memory_manager.process_stats(p.pid).map(|s| s.fault_rate()).unwrap_or(0.0);
+ (p.pid, rate)
+ })
+ .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(b.0.cmp(&a.0)))
+ .map(|(pid, _)| pid);
+
+ if let Some(pid) = victim {
+ if self.suspend_process(pid).is_ok() {
+ let freed = self.memory_manager.release_frames(pid);
+ self.thrashing.suspended.push(pid);
+ self.rebalance_frames();
+ println!("[THRASHING] Proceso {} suspendido ({} marcos liberados)", pid, freed);
+ }
+ }
+ }
+ ThrashingVerdict::Recovered { fault_rate, .. } => {
+ // El primero que se pueda reanudar; resume_process lo saca de la lista
+ let candidates = self.thrashing.suspended.clone();
+ if let Some(pid) = candidates.into_iter().find(|pid| self.resume_process(*pid).is_ok()) {
+ self.rebalance_frames();
+ println!("[THRASHING] Tasa de fallos {:.2}: proceso {} reanudado", fault_rate, pid);
+ }
+ }
+ ThrashingVerdict::Normal { .. } => {}
+ }
+ }
+
+ /// Mostrar asignación de marcos por proceso y estado del monitor de thrashing
+ pub fn frame_allocation_status(&self) {
+ self.memory_manager.display_allocation();
+ if self.thrashing.config.enabled {
+ println!("Thrashing: tasa de fallos >= {:.2} con CPU < {:.2} (ventana {} ticks)",
+ self.thrashing.config.fault_rate, self.thrashing.config.cpu_utilization, self.thrashing.config.window);
+ println!("Detecciones: {} | Suspendidos por thrashing: {:?}", self.thrashing.detections, self.thrashing.suspended);
+ }
+ }
+
  /// Ejecutar n pasos completos
  pub fn run(&mut self, steps: u64) {
  self.tick(steps);
@@ -241,6 +644,20 @@ impl KernelState {
  println!("║ Aciertos (hits): {:6} ║", mem_stats.page_hits);
  println!("║ Tasa de aciertos: {:.2}% ║", mem_stats.hit_rate);
  println!("║ Accesos totales: {:6} ║", mem_stats.total_accesses);
+ if self.memory_manager.tlb().is_enabled() {
+ println!("╠═══════════════════════════════════════════════════════════╣");
+ println!("║ TLB hits: {:6} ║", mem_stats.tlb_hits);
+ println!("║ TLB misses: {:6} ║", mem_stats.tlb_misses);
+ println!("║ Tasa de aciertos TLB: {:.2}% ║", mem_stats.tlb_hit_rate);
+ }
+ let swap = self.memory_manager.swap();
+ if swap.is_enabled() {
+ println!("╠═══════════════════════════════════════════════════════════╣");
+ println!("║ Page-ins / page-outs: {} / {} ", swap.page_ins(), swap.page_outs());
+ println!("║ Movimiento de disco (swap): {} cilindros ", swap.total_movement());
+ println!("║ Tiempo de disco en fallos: {} ns ", swap.total_service_ns());
+ }
+ println!("║ Tiempo efectivo de acceso: {:.2} ns ║", mem_stats.effective_access_time);
  println!("╚═══════════════════════════════════════════════════════════╝");
 
  // Mostrar marcos de memoria
@@ -259,11 +676,11 @@ impl KernelState {
  let mut total_response = 0u64;
  let mut response_count = 0;
 
- println!("\n╔═══════════════════════════════════════════════════════════════════╗");
+ println!("\n╔════════════════════════════════════════════════════════════════════════════╗");
  println!("║ MÉTRICAS DE SCHEDULING ║");
- println!("╠═════╦═══════════╦═══════════════╦═══════════════╦═════════════════╣");
- println!("║ PID ║ Llegada ║ Tiempo Espera ║ Turnaround ║ Respuesta ║");
- println!("╠═════╬═══════════╬═══════════════╬═══════════════╬═════════════════╣");
+ println!("╠═════╦═══════════╦═══════════════╦═══════════════╦═════════════════╦════════╣");
+ println!("║ PID ║ Llegada ║ Tiempo Espera ║ Turnaround ║ Respuesta ║ Fallos ║");
+ println!("╠═════╬═══════════╬═══════════════╬═══════════════╬═════════════════╬════════╣");
 
  for proc in &self.finished_processes {
  let waiting = proc.waiting_time(self.current_time);
@@ -280,11 +697,11 @@ impl KernelState {
 
  let resp_str = response.map(|r| format!("{:6}", r)).unwrap_or_else(|| " N/A ".to_string());
  
- println!("║ {:3} ║ {:3} ║ {:4} ║ {:4} ║ {} ║",
- proc.pid, proc.arrival_time, waiting, turnaround, resp_str);
+ println!("║ {:3} ║ {:3} ║ {:4} ║ {:4} ║ {} ║ {:6} ║",
+ proc.pid, proc.arrival_time, waiting, turnaround, resp_str, proc.page_faults);
  }
 
- println!("╠═════╩═══════════╩═══════════════╩═══════════════╩═════════════════╣");
+ println!("╠═════╩═══════════╩═══════════════╩═══════════════╩═════════════════╩════════╣");
 
  let count = self.finished_processes.len() as f64;
  let avg_waiting = total_waiting as f64 / count;
@@ -299,18 +716,533 @@ impl KernelState {
  println!("║ Tiempo de espera: {:.2} ", avg_waiting);
  println!("║ Tiempo de retorno: {:.2} ", avg_turnaround);
  println!("║ Tiempo de respuesta: {:.2} ", avg_response);
- println!("╚═══════════════════════════════════════════════════════════════════╝");
+ println!("╚════════════════════════════════════════════════════════════════════════════╝");
+ }
+
+ /// Crear un semáforo con nombre
+ pub fn sem_create(&mut self, name: &str, value: i32) -> Result<()> {
+ if self.ipc.has_semaphore(name) {
+ return Err(anyhow::anyhow!("El semáforo '{}' ya existe", name));
+ }
+ if value < 0 {
+ return Err(anyhow::anyhow!("Valor inicial inválido: {}", value));
+ }
+ self.ipc.create_semaphore(name.to_string(), value);
+ Ok(())
+ }
+
+ /// wait (P) en nombre de un proceso; si el semáforo lo bloquea sale del scheduler
+ /// Devuelve true si el proceso pudo continuar
+ pub fn sem_wait(&mut self, name: &str, pid: u32) -> Result<bool> {
+ let proc = self.processes.get(&pid)
+ .ok_or_else(|| anyhow::anyhow!("Proceso {} no encontrado", pid))?;
+ if proc.state == ProcessState::Blocked {
+ return Err(anyhow::anyhow!("Proceso {} está bloqueado y no puede ejecutar wait", pid));
+ }
+ let sem = self.ipc.get_semaphore(name)
+ .ok_or_else(|| anyhow::anyhow!("Semáforo '{}' no encontrado", name))?;
+
+ if sem.wait(pid) {
+ return Ok(true);
+ }
+ self.block_process(pid);
+ Ok(false)
+ }
+
+ /// signal (V); el proceso despertado vuelve a la cola de listos
+ pub fn sem_signal(&mut self, name: &str) -> Result<Option<u32>> {
+ let sem = self.ipc.get_semaphore(name)
+ .ok_or_else(|| anyhow::anyhow!("Semáforo '{}' no encontrado", name))?;
+ let woken = sem.signal();
+ if let Some(pid) = woken {
+ self.unblock_process(pid);
+ }
+ Ok(woken)
+ }
+
+ /// Pasar un proceso a Blocked y sacarlo del scheduler
+ fn block_process(&mut self, pid: u32) {
+ if let Some(proc) = self.processes.get_mut(&pid) {
+ proc.state = ProcessState::Blocked;
+ self.scheduler.remove(pid);
+ }
+ }
+
+ /// Devolver un proceso bloqueado a la cola de listos
+ fn unblock_process(&mut self, pid: u32) {
+ if let Some(proc) = self.processes.get_mut(&pid) {
+ if proc.state == ProcessState::Blocked {
+ proc.state = ProcessState::Ready;
+ self.scheduler.push(proc.clone());
+ }
+ }
+ }
+
+ /// Fijar la prioridad base de un proceso (la efectiva puede quedar más alta por herencia o techo)
+ pub fn set_priority(&mut self, pid: u32, priority: u8) -> Result<()> {
+ let proc = self.processes.get_mut(&pid)
+ .ok_or_else(|| anyhow::anyhow!("Proceso {} no encontrado", pid))?;
+ proc.base_priority = priority;
+ self.refresh_priority(pid);
+ Ok(())
+ }
+
+ pub fn mutex_create(&mut self, name: &str, recursive: bool, protocol: MutexProtocol) -> Result<()> {
+ if self.ipc.has_mutex(name) {
+ return Err(anyhow::anyhow!("El mutex '{}' ya existe", name));
+ }
+ self.ipc.create_mutex(name.to_string(), recursive, protocol);
+ Ok(())
+ }
+
+ /// Tomar un mutex; si está ocupado el proceso se bloquea y el dueño puede heredar su prioridad
+ pub fn mutex_lock(&mut self, name: &str, pid: u32) -> Result<bool> {
+ let proc = self.processes.get(&pid)
+ .ok_or_else(|| anyhow::anyhow!("Proceso {} no encontrado", pid))?;
+ if proc.state == ProcessState::Blocked {
+ return Err(anyhow::anyhow!("Proceso {} está bloqueado y no puede tomar un mutex", pid));
+ }
+ let priority = proc.priority;
+ let mutex = self.ipc.get_mutex(name)
+ .ok_or_else(|| anyhow::anyhow!("Mutex '{}' no encontrado", name))?;
+
+ let acquired = mutex.lock(pid, priority).map_err(|e| anyhow::anyhow!(e))?;
+ let owner = mutex.owner();
+ if !acquired {
+ self.block_process(pid);
+ }
+ if let Some(owner) = owner {
+ self.refresh_priority(owner);
+ }
+ Ok(acquired)
+ }
+
+ /// Liberar un mutex; solo el dueño puede hacerlo. Devuelve el proceso que lo recibe
+ pub fn mutex_unlock(&mut self, name: &str, pid: u32) -> Result<Option<u32>> {
+ let mutex = self.ipc.get_mutex(name)
+ .ok_or_else(|| anyhow::anyhow!("Mutex '{}' no encontrado", name))?;
+ let next = mutex.unlock(pid).map_err(|e| anyhow::anyhow!(e))?;
+
+ self.refresh_priority(pid);
+ if let Some(next) = next {
+ self.unblock_process(next);
+ self.refresh_priority(next);
+ }
+ Ok(next)
+ }
+
+ /// Soltar los mutexes de un proceso que termina (se entregan al siguiente waiter)
+ fn release_mutexes(&mut self, pid: u32) {
+ for name in self.ipc.mutexes_owned_by(pid) {
+ let next = self.ipc.get_mutex(&name).and_then(|m| m.abandon(pid));
+ if let Some(next) = next {
+ println!("[Mutex {}] Dueño {} terminó: pasa al proceso {}", name, pid, next);
+ self.unblock_process(next);
+ self.refresh_priority(next);
+ }
+ }
+ // Si esperaba un mutex, su dueño puede perder la prioridad heredada
+ let owner = self.ipc.mutex_waited_by(pid).and_then(|m| {
+ m.remove_waiter(pid);
+ m.owner()
+ });
+ if let Some(owner) = owner {
+ self.refresh_priority(owner);
+ }
+ }
+
+ /// Recalcular la prioridad efectiva: base, herencia de waiters y techos de los mutexes que posee
+ /// El cambio se propaga al dueño del mutex que el proceso espera (herencia transitiva)
+ fn refresh_priority(&mut self, pid: u32) {
+ let mut current = Some(pid);
+ while let Some(pid) = current {
+ let imposed = self.ipc.mutexes_owned_by(pid).iter()
+ .filter_map(|name| self.ipc.get_mutex(name).and_then(|m| m.imposed_priority()))
+ .max();
+ let Some(proc) = self.processes.get_mut(&pid) else {
+ break;
+ };
+ let effective = proc.base_priority.max(imposed.unwrap_or(0));
+ if effective == proc.priority {
+ break;
+ }
+
+ println!("[Prioridad] Proceso {}: {} -> {}", pid, proc.priority, effective);
+ proc.priority = effective;
+ if proc.state == ProcessState::Ready && self.scheduler.remove(pid).is_some() {
+ self.scheduler.push(proc.clone());
+ }
+ current = self.ipc.mutex_waited_by(pid).and_then(|m| {
+ m.update_waiter(pid, effective);
+ m.owner()
+ });
+ }
+ }
+
+ pub fn mutex_status(&self) {
+ let mutexes = self.ipc.mutexes();
+ if mutexes.is_empty() {
+ println!("[INFO] No hay mutexes (usa mutex-create <nombre>)");
+ return;
+ }
+ println!("\n╔════════════╦═══════╦═══════╦═══════════════╦══════════════════════╦═══════════╗");
+ println!("║ Mutex ║ Dueño ║ Prof. ║ Protocolo ║ Espera (pid:prio) ║ Contención║");
+ println!("╠════════════╬═══════╬═══════╬═══════════════╬══════════════════════╬═══════════╣");
+ for mutex in mutexes {
+ let owner = mutex.owner().map(|p| p.to_string()).unwrap_or_else(|| "-".to_string());
+ let protocol = match mutex.protocol() {
+ MutexProtocol::None => "ninguno".to_string(),
+ MutexProtocol::Inheritance => "herencia".to_string(),
+ MutexProtocol::Ceiling(c) => format!("techo {}", c),
+ };
+ let protocol = if mutex.is_recursive() { format!("{} (rec)", protocol) } else { protocol };
+ let waiters: Vec<String> = mutex.waiters().iter().map(|(p, prio)| format!("{}:{}", p, prio)).collect();
+ let waiters = if waiters.is_empty() { "-".to_string() } else { waiters.join(" ") };
+ println!("║ {:10} ║ {:>5} ║ {:5} ║ {:13} ║ {:20} ║ {:9} ║",
+ mutex.name(), owner, mutex.depth(), protocol, waiters, mutex.contentions());
+ }
+ println!("╚════════════╩═══════╩═══════╩═══════════════╩══════════════════════╩═══════════╝");
+ }
+
+ pub fn sem_status(&self) {
+ let sems = self.ipc.semaphores();
+ if sems.is_empty() {
+ println!("[INFO] No hay semáforos (usa sem-create <nombre> <valor>)");
+ return;
+ }
+ println!("\n╔══════════════════╦═══════╦══════════════════════════════╗");
+ println!("║ Semáforo ║ Valor ║ Cola de espera (PIDs) ║");
+ println!("╠══════════════════╬═══════╬══════════════════════════════╣");
+ for sem in sems {
+ let queue: Vec<String> = sem.waiting().map(|p| p.to_string()).collect();
+ let queue = if queue.is_empty() { "-".to_string() } else { queue.join(", ") };
+ println!("║ {:16} ║ {:5} ║ {:28} ║", sem.name(), sem.count(), queue);
+ }
+ println!("╚══════════════════╩═══════╩══════════════════════════════╝");
+ }
+
+ /// Crear un buzón con nombre (direccionamiento indirecto)
+ pub fn mbox_create(&mut self, name: &str, capacity: usize) -> Result<()> {
+ if name.starts_with("pid:") {
+ return Err(anyhow::anyhow!("El prefijo 'pid:' está reservado para los buzones directos"));
+ }
+ if self.ipc.has_mailbox(name) {
+ return Err(anyhow::anyhow!("El buzón '{}' ya existe", name));
+ }
+ self.ipc.create_mailbox(name.to_string(), capacity, None);
+ Ok(())
+ }
+
+ /// Enviar un mensaje; con el buzón lleno un send bloqueante deja al emisor en Blocked
+ pub fn msg_send(&mut self, from: u32, to: &Address, payload: &str, priority: u8, blocking: bool) -> Result<SendOutcome> {
+ self.check_can_block(from)?;
+ let name = self.mailbox_for(to)?;
+ let mailbox = self.ipc.get_mailbox(&name)
+ .ok_or_else(|| anyhow::anyhow!("Buzón '{}' no encontrado", name))?;
+
+ let outcome = mailbox.send(Message::new(from, priority, payload), blocking);
+ match outcome {
+ SendOutcome::Delivered(receiver) => self.unblock_process(receiver),
+ SendOutcome::Blocked => self.block_process(from),
+ SendOutcome::Queued | SendOutcome::WouldBlock => {}
+ }
+ Ok(outcome)
+ }
+
+ /// Recibir de un buzón con nombre o, sin nombre, del buzón propio (opcionalmente solo de `from`)
+ pub fn msg_recv(&mut self, pid: u32, mailbox: Option<&str>, from: Option<u32>, blocking: bool) -> Result<RecvOutcome> {
+ self.check_can_block(pid)?;
+ let name = match mailbox {
+ Some(name) => name.to_string(),
+ None => self.mailbox_for(&Address::Process(pid))?,
+ };
+ let mailbox = self.ipc.get_mailbox(&name)
+ .ok_or_else(|| anyhow::anyhow!("Buzón '{}' no encontrado", name))?;
+
+ let outcome = mailbox.recv(pid, from, blocking).map_err(|e| anyhow::anyhow!(e))?;
+ match &outcome {
+ RecvOutcome::Received { unblocked, .. } => {
+ for sender in unblocked.clone() {
+ self.unblock_process(sender);
+ }
+ }
+ RecvOutcome::Blocked => self.block_process(pid),
+ RecvOutcome::WouldBlock => {}
+ }
+ Ok(outcome)
+ }
+
+ /// Un proceso bloqueado no puede ejecutar otra llamada que bloquee
+ fn check_can_block(&self, pid: u32) -> Result<()> {
+ let proc = self.processes.get(&pid)
+ .ok_or_else(|| anyhow::anyhow!("Proceso {} no encontrado", pid))?;
+ if proc.state == ProcessState::Blocked {
+ return Err(anyhow::anyhow!("Proceso {} está bloqueado", pid));
+ }
+ Ok(())
+ }
+
+ /// Nombre del buzón de un destino; el buzón directo se crea con el primer uso
+ fn mailbox_for(&mut self, to: &Address) -> Result<String> {
+ let name = to.mailbox_name();
+ if let Address::Process(pid) = to {
+ if !self.processes.contains_key(pid) {
+ return Err(anyhow::anyhow!("Proceso destino {} no encontrado", pid));
+ }
+ if !self.ipc.has_mailbox(&name) {
+ self.ipc.create_mailbox(name.clone(), mailbox::DIRECT_CAPACITY, Some(*pid));
+ }
+ }
+ Ok(name)
+ }
+
+ pub fn mbox_status(&self) {
+ let mailboxes = self.ipc.mailboxes();
+ if mailboxes.is_empty() {
+ println!("[INFO] No hay buzones (usa mbox-create <nombre> o msg-send --to <pid>)");
+ return;
+ }
+ println!("\n╔════════════╦══════╦══════════╦═════════════════╦═════════════════╦═════════╦═════════╗");
+ println!("║ Buzón ║ Cap. ║ Mensajes ║ Emisores bloq. ║ Receptores bloq.║ Enviados║ Recibid.║");
+ println!("╠════════════╬══════╬══════════╬═════════════════╬═════════════════╬═════════╬═════════╣");
+ for mbox in &mailboxes {
+ let list = |pids: Vec<u32>| {
+ let pids: Vec<String> = pids.iter().map(|p| p.to_string()).collect();
+ if pids.is_empty() { "-".to_string() } else { pids.join(", ") }
+ };
+ println!("║ {:10} ║ {:4} ║ {:8} ║ {:15} ║ {:15} ║ {:7} ║ {:7} ║",
+ mbox.name(), mbox.capacity(), mbox.messages().len(),
+ list(mbox.blocked_senders()), list(mbox.blocked_receivers()), mbox.sent(), mbox.received());
+ }
+ println!("╚════════════╩══════╩══════════╩═════════════════╩═════════════════╩═════════╩═════════╝");
+ for mbox in mailboxes {
+ for message in mbox.messages() {
+ println!(" {} <- [prio {}] de {}: '{}'", mbox.name(), message.priority, message.from, message.payload);
+ }
+ }
+ }
+
+ /// Crear un tipo de recurso con varias instancias
+ pub fn res_create(&mut self, name: &str, instances: u32) -> Result<()> {
+ self.resources.create_type(name, instances).map_err(|e| anyhow::anyhow!(e))?;
+ Ok(())
+ }
+
+ /// Pedir instancias de un recurso; si no hay suficientes (o el banquero la posterga) el proceso queda Blocked
+ pub fn res_request(&mut self, pid: u32, name: &str, count: u32) -> Result<RequestOutcome> {
+ self.check_can_block(pid)?;
+ let ty = self.resource_index(name)?;
+ let outcome = self.resources.request(pid, ty, count).map_err(|e| anyhow::anyhow!(e))?;
+ if outcome != RequestOutcome::Granted {
+ self.block_process(pid);
+ }
+ Ok(outcome)
+ }
+
+ /// Convertir pares (recurso, máximo) en un vector por tipo; el máximo no puede superar el total
+ pub fn claim_vector(&self, claims: &[(String, u32)]) -> Result<Vec<u32>> {
+ let mut vector = vec![0; self.resources.types().len()];
+ for (name, count) in claims {
+ let ty = self.resource_index(name)?;
+ let total = self.resources.types()[ty].total;
+ if *count > total {
+ return Err(anyhow::anyhow!("Máximo {} de '{}' supera sus {} instancias", count, name, total));
+ }
+ vector[ty] = *count;
+ }
+ Ok(vector)
+ }
+
+ /// Declarar el máximo de un proceso: sus peticiones pasarán por el banquero
+ pub fn declare_max(&mut self, pid: u32, claims: Vec<u32>) -> Result<()> {
+ if !self.processes.contains_key(&pid) {
+ return Err(anyhow::anyhow!("Proceso {} no encontrado", pid));
+ }
+ if self.resources.allocation_of(pid).iter().any(|n| *n > 0) {
+ return Err(anyhow::anyhow!("El máximo se declara antes de recibir recursos"));
+ }
+ self.resources.declare_max(pid, claims).map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Devolver instancias; devuelve los procesos cuya petición pudo atenderse
+ pub fn res_release(&mut self, pid: u32, name: &str, count: u32) -> Result<Vec<u32>> {
+ let ty = self.resource_index(name)?;
+ let served = self.resources.release(pid, ty, count).map_err(|e| anyhow::anyhow!(e))?;
+ for waiter in &served {
+ self.unblock_process(*waiter);
+ }
+ Ok(served)
+ }
+
+ fn resource_index(&self, name: &str) -> Result<usize> {
+ self.resources.type_index(name)
+ .ok_or_else(|| anyhow::anyhow!("Recurso '{}' no encontrado", name))
+ }
+
+ /// Detectar interbloqueos y, si se pide, recuperarse eligiendo víctimas hasta romperlos
+ /// Víctima: retiene algo, menor prioridad, menos veces expropiada, menos instancias retenidas, más joven
+ pub fn deadlock_check(&mut self, recovery: Option<Recovery>) -> DeadlockReport {
+ let report = self.resources.detect();
+ self.print_deadlock_report(&report);
+
+ let Some(recovery) = recovery.filter(|_| !report.deadlocked.is_empty()) else {
+ return report;
+ };
+ let mut current = report.clone();
+ while !current.deadlocked.is_empty() {
+ let Some(victim) = current.deadlocked.iter().copied().min_by_key(|pid| {
+ let priority = self.processes.get(pid).map(|p| p.priority).unwrap_or(0);
+ let held: u32 = self.resources.allocation_of(*pid).iter().sum();
+ // Quitarle recursos a quien no tiene ninguno no rompe el ciclo
+ (held == 0, priority, self.resources.preemptions(*pid), held, std::cmp::Reverse(*pid))
+ }) else {
+ break;
+ };
+ match recovery {
+ Recovery::Kill => {
+ println!("[RECUPERACIÓN] Terminando al proceso {}", victim);
+ if self.kill_process(victim).is_err() {
+ // Proceso inexistente: basta con soltar lo que tenía
+ for waiter in self.resources.release_all(victim) {
+ self.unblock_process(waiter);
+ }
+ }
+ }
+ Recovery::Preempt => {
+ println!("[RECUPERACIÓN] Expropiando los recursos del proceso {} (deberá pedirlos de nuevo)", victim);
+ for waiter in self.resources.preempt(victim) {
+ self.unblock_process(waiter);
+ }
+ self.unblock_process(victim);
+ }
+ }
+ current = self.resources.detect();
+ }
+ println!("[OK] Sin interbloqueos tras la recuperación");
+ report
+ }
+
+ fn print_deadlock_report(&self, report: &DeadlockReport) {
+ self.res_status();
+ if let Some(cycle) = &report.cycle {
+ let mut nodes: Vec<String> = cycle.iter().map(|n| self.resources.node_label(n)).collect();
+ nodes.push(self.resources.node_label(&cycle[0]));
+ println!("Ciclo en el grafo de asignación: {}", nodes.join(" -> "));
+ if !self.resources.single_instance() && report.deadlocked.is_empty() {
+ println!("[INFO] Con recursos de varias instancias un ciclo no implica interbloqueo");
+ }
+ }
+ if report.deadlocked.is_empty() {
+ let order: Vec<String> = report.safe_order.iter().map(|p| format!("P{}", p)).collect();
+ println!("[OK] No hay interbloqueo. Orden de terminación posible: {}",
+ if order.is_empty() { "-".to_string() } else { order.join(", ") });
+ } else {
+ let pids: Vec<String> = report.deadlocked.iter().map(|p| format!("P{}", p)).collect();
+ println!("[ERROR] Procesos interbloqueados: {}", pids.join(", "));
+ }
+ }
+
+ /// Grafo de asignación de recursos en formato DOT
+ pub fn deadlock_dot(&self) -> String {
+ self.resources.to_dot(&self.resources.detect().deadlocked)
+ }
+
+ /// Matrices del banquero, secuencia segura y motivo de cada petición postergada
+ pub fn banker_state(&self) {
+ let types = self.resources.types();
+ if types.is_empty() {
+ println!("[INFO] No hay recursos (usa res-create <nombre> <instancias>)");
+ return;
+ }
+ let vector = |v: Vec<u32>| v.iter().map(|n| format!("{:4}", n)).collect::<Vec<_>>().join("");
+ let header: String = types.iter().map(|t| format!("{:>4}", t.name.chars().take(3).collect::<String>())).collect();
+ let width = types.len() * 4;
+ let bar = "═".repeat(width + 2);
+
+ println!("\n╔═════╦{}╦{}╦{}╗", bar, bar, bar);
+ println!("║ PID ║ {:width$} ║ {:width$} ║ {:width$} ║", "Allocation", "Max", "Need", width = width);
+ println!("║ ║ {} ║ {} ║ {} ║", header, header, header);
+ println!("╠═════╬{}╬{}╬{}╣", bar, bar, bar);
+ for pid in self.resources.processes() {
+ let max = if self.resources.has_claims(pid) { vector(self.resources.max_of(pid)) } else { format!("{:>width$}", "-", width = width) };
+ println!("║ {:3} ║ {} ║ {} ║ {} ║", pid, vector(self.resources.allocation_of(pid)), max, vector(self.resources.need_of(pid)));
+ }
+ println!("╚═════╩{}╩{}╩{}╝", bar, bar, bar);
+ println!("Available: [{} ]", vector(self.resources.available()));
+
+ match self.resources.safety() {
+ Ok(sequence) => {
+ let sequence: Vec<String> = sequence.iter().map(|p| format!("P{}", p)).collect();
+ println!("[OK] Estado seguro. Secuencia segura: <{}>", sequence.join(", "));
+ }
+ Err(stuck) => {
+ let stuck: Vec<String> = stuck.iter().map(|p| format!("P{}", p)).collect();
+ println!("[WARN] Estado inseguro: {} no tienen garantizado terminar", stuck.join(", "));
+ }
+ }
+ for (pid, ty, count) in self.resources.pending_requests() {
+ let reason = if self.resources.has_claims(*pid) {
+ match self.resources.banker_check(*pid, *ty, *count) {
+ Ok(()) => "se concederá en la próxima liberación".to_string(),
+ Err(denial) => denial.to_string(),
+ }
+ } else {
+ "sin máximo declarado: espera instancias libres".to_string()
+ };
+ println!("[INFO] P{} espera {} de '{}': {}", pid, count, types[*ty].name, reason);
+ }
+ }
+
+ /// Matrices de asignación y petición
+ pub fn res_status(&self) {
+ let types = self.resources.types();
+ if types.is_empty() {
+ println!("[INFO] No hay recursos (usa res-create <nombre> <instancias>)");
+ return;
+ }
+ let vector = |v: Vec<u32>| v.iter().map(|n| format!("{:4}", n)).collect::<Vec<_>>().join("");
+ let header: String = types.iter().map(|t| format!("{:>4}", t.name.chars().take(3).collect::<String>())).collect();
+ let width = types.len() * 4;
+
+ println!("\n╔═════╦═{}═╦═{}═╗", "═".repeat(width), "═".repeat(width));
+ println!("║ PID ║ {:width$} ║ {:width$} ║", "Asig.", "Pide", width = width);
+ println!("║ ║ {} ║ {} ║", header, header);
+ println!("╠═════╬═{}═╬═{}═╣", "═".repeat(width), "═".repeat(width));
+ for pid in self.resources.processes() {
+ println!("║ {:3} ║ {} ║ {} ║", pid, vector(self.resources.allocation_of(pid)), vector(self.resources.request_of(pid)));
+ }
+ println!("╚═════╩═{}═╩═{}═╝", "═".repeat(width), "═".repeat(width));
+ let totals: Vec<String> = types.iter().map(|t| format!("{}={}", t.name, t.total)).collect();
+ println!("Total: {} | Disponible: [{} ]", totals.join(" "), vector(self.resources.available()));
  }
 
  /// Acceder a gestión de productor-consumidor
- pub fn produce(&mut self, item: String, producer_pid: u32) -> Result<()> {
- self.producer_consumer.produce(item, producer_pid)
- .map_err(|e| anyhow::anyhow!(e))
+ /// Un productor bloqueado pasa a Blocked y vuelve a listos cuando un consumidor lo despierta
+ pub fn produce(&mut self, item: String, producer_pid: u32) -> Result<BufferReport> {
+ self.buffer_operation(producer_pid, BufferOp::Produce(item))
  }
 
- pub fn consume(&mut self, consumer_pid: u32) -> Result<String> {
- self.producer_consumer.consume(consumer_pid)
- .map_err(|e| anyhow::anyhow!(e))
+ pub fn consume(&mut self, consumer_pid: u32) -> Result<BufferReport> {
+ self.buffer_operation(consumer_pid, BufferOp::Consume)
+ }
+
+ /// Los PIDs sin proceso (valores por defecto de la CLI) usan el buffer sin scheduler
+ fn buffer_operation(&mut self, pid: u32, op: BufferOp) -> Result<BufferReport> {
+ if self.processes.get(&pid).is_some_and(|p| p.state == ProcessState::Blocked) {
+ return Err(anyhow::anyhow!("Proceso {} está bloqueado", pid));
+ }
+ let report = match op {
+ BufferOp::Produce(item) => self.producer_consumer.produce(item, pid),
+ BufferOp::Consume => self.producer_consumer.consume(pid),
+ }.map_err(|e| anyhow::anyhow!(e))?;
+
+ if report.result == BufferResult::Blocked {
+ self.block_process(pid);
+ }
+ for (woken, _) in &report.resumed {
+ self.unblock_process(*woken);
+ }
+ Ok(report)
  }
 
  pub fn buffer_status(&self) {
@@ -320,12 +1252,23 @@ impl KernelState {
  /// Acceder a marcos de memoria
  pub fn display_memory(&self) {
  self.memory_manager.display_frames();
+ if let Some(cache) = &self.cache {
+ cache.display();
+ }
+ }
+
+ pub fn cache_status(&self) {
+ match &self.cache {
+ Some(cache) => cache.display(),
+ None => println!("[INFO] Caché desactivada (usa init --cache-size N)"),
+ }
  }
 
  /// Simular acceso a memoria con algoritmo FIFO
  pub fn access_memory_fifo(&mut self, pid: u32, page: usize) -> Result<()> {
  match self.memory_manager.access_page_fifo(pid, page) {
  Ok(frame) => {
+ self.feed_cache(self.frame_address(frame));
  println!("[OK] Acceso a página {} del proceso {} -> Marco {}", page, pid, frame);
  Ok(())
  }
@@ -337,6 +1280,7 @@ impl KernelState {
  pub fn access_memory_lru(&mut self, pid: u32, page: usize) -> Result<()> {
  match self.memory_manager.access_page_lru(pid, page) {
  Ok(frame) => {
+ self.feed_cache(self.frame_address(frame));
  println!("[OK] Acceso a página {} del proceso {} -> Marco {}", page, pid, frame);
  Ok(())
  }
@@ -348,6 +1292,7 @@ impl KernelState {
  pub fn access_memory_ws(&mut self, pid: u32, page: usize, window: usize) -> Result<()> {
  match self.memory_manager.access_page_working_set(pid, page, window) {
  Ok(frame) => {
+ self.feed_cache(self.frame_address(frame));
  println!("[OK] Acceso a página {} del proceso {} -> Marco {} (WS ventana={})", page, pid, frame, window);
  Ok(())
  }
@@ -355,23 +1300,342 @@ impl KernelState {
  }
  }
 
- /// Asignar memoria heap con Buddy Allocator
- pub fn heap_alloc(&mut self, pid: u32, size: usize) -> Result<usize> {
- self.heap_allocator.alloc(pid, size)
+ /// Reproducir una traza de archivo con el algoritmo dado, leyéndola en streaming
+ /// Las referencias sin pid (formato lackey) se atribuyen a `default_pid`
+ pub fn run_trace(&mut self, path: &str, default_pid: u32, algorithm: ReplacementAlgorithm) -> Result<()> {
+ let reader = TraceReader::open(path).map_err(|e| anyhow::anyhow!(e))?;
+ let page_size = self.memory_manager.addressing().page_size as u64;
+ let faults_before = self.memory_manager.page_faults();
+ let mut op_counts = [0u64; 4];
+ let mut pages: HashSet<(u32, usize)> = HashSet::new();
+ let mut malformed = 0u64;
+ let start = Instant::now();
+
+ for record in reader {
+ let record = match record {
+ Ok(record) => record,
+ Err(e) => {
+ malformed += 1;
+ if malformed <= 5 {
+ println!("[ERROR] {}", e);
+ }
+ continue;
+ }
+ };
+ let pid = record.pid.unwrap_or(default_pid);
+ let page = (record.address / page_size) as usize;
+ let offset = record.address % page_size;
+
+ let frame = if record.op.is_write() {
+ self.memory_manager.write_page(pid, page, algorithm)
+ } else {
+ self.memory_manager.access_page(pid, page, algorithm)
+ };
+ match frame {
+ Ok(frame) => self.feed_cache(self.frame_address(frame) + offset),
+ Err(e) => return Err(anyhow::anyhow!("Acceso a {:#x} (proceso {}): {}", record.address, pid, e)),
+ }
+
+ op_counts[match record.op {
+ TraceOp::Read => 0,
+ TraceOp::Write => 1,
+ TraceOp::Fetch => 2,
+ TraceOp::Modify => 3,
+ }] += 1;
+ pages.insert((pid, page));
+ }
+
+ let elapsed = start.elapsed().as_secs_f64();
+ let total: u64 = op_counts.iter().sum();
+ let faults = self.memory_manager.page_faults() - faults_before;
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ TRAZA: {} ", path);
+ println!("╠════════════════════════════════════════════════════╣");
+ println!("║ Referencias: {} (lecturas {}, escrituras {}, instrucciones {}, modificaciones {})",
+ total, op_counts[0], op_counts[1], op_counts[2], op_counts[3]);
+ println!("║ Líneas inválidas omitidas: {} ", malformed);
+ println!("║ Páginas distintas: {} ", pages.len());
+ println!("║ Fallos de página: {} ({:.2}%) ", faults, if total == 0 { 0.0 } else { faults as f64 / total as f64 * 100.0 });
+ println!("║ Tiempo: {:.3} s ({:.0} refs/s) ", elapsed, total as f64 / elapsed.max(1e-9));
+ println!("╚════════════════════════════════════════════════════╝");
+ Ok(())
+ }
+
+ /// Traducir dirección virtual mostrando cada paso y el costo de las tablas
+ pub fn translate_address(&mut self, pid: u32, vaddr: u64) -> Result<()> {
+ let translation = self.memory_manager.translate_address(pid, vaddr)
+ .map_err(|e| anyhow::anyhow!(e))?;
+ self.feed_cache(translation.physical_address);
+ let config = *self.memory_manager.addressing();
+
+ let kind_str = match config.kind {
+ PageTableKind::MultiLevel => format!("jerárquica de {} nivel(es)", config.levels),
+ PageTableKind::Inverted => "invertida".to_string(),
+ PageTableKind::Hashed => "hash".to_string(),
+ };
+
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ TRADUCCIÓN DE DIRECCIÓN (PID {}) ", pid);
+ println!("║ Página: {} bytes | VA: {} bits | Tabla {} ", config.page_size, config.address_bits, kind_str);
+ println!("╠════════════════════════════════════════════════════╣");
+ for (i, step) in translation.steps.iter().enumerate() {
+ println!("║ {}. {}", i + 1, step);
+ }
+ println!("╚════════════════════════════════════════════════════╝");
+
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ MEMORIA OCUPADA POR TABLAS DE PÁGINAS (PID {}) ", pid);
+ println!("╠══════════════════════════╦═════════╦═══════════════╣");
+ println!("║ Variante ║ Tablas ║ Bytes ║");
+ println!("╠══════════════════════════╬═════════╬═══════════════╣");
+ for overhead in self.memory_manager.table_overheads(pid) {
+ println!("║ {:24} ║ {:7} ║ {:13} ║", overhead.label, overhead.tables, overhead.bytes);
+ }
+ println!("╚══════════════════════════╩═════════╩═══════════════╝");
+ Ok(())
+ }
+
+ /// Crear la tabla de segmentos (código, datos, heap, pila) de un proceso
+ pub fn seg_setup(&mut self, pid: u32, sizes: [usize; 4], paged: bool) -> Result<()> {
+ if !self.processes.contains_key(&pid) {
+ return Err(anyhow::anyhow!("Proceso {} no encontrado", pid));
+ }
+ let mode = if paged { SegmentationMode::Paged } else { SegmentationMode::Pure };
+ let addressing = self.memory_manager.addressing();
+ let (page_size, virtual_pages) = (addressing.page_size, 1usize << addressing.page_number_bits());
+ if paged && virtual_pages >= SEGMENT_REGION_PAGES {
+ let region = virtual_pages - SEGMENT_REGION_PAGES..virtual_pages;
+ if let Some(page) = self.memory_manager.first_held_page(pid, region) {
+ return Err(anyhow::anyhow!("La página {:#x} del proceso {} ya está mapeada: choca con los segmentos paginados", page, pid));
+ }
+ }
+ self.segmentation.create(pid, sizes, mode, page_size, virtual_pages)
+ .map_err(|e| anyhow::anyhow!(e))?;
+ Ok(())
+ }
+
+ /// Traducir (segmento, desplazamiento); una violación termina el proceso
+ /// Devuelve la dirección física o None si hubo segmentation fault
+ pub fn seg_translate(&mut self, pid: u32, kind: SegmentKind, offset: usize, access: AccessKind) -> Result<Option<usize>> {
+ let table = self.segmentation.table(pid)
+ .ok_or_else(|| anyhow::anyhow!("El proceso {} no tiene tabla de segmentos (usa seg-setup)", pid))?;
+ let segment = table.segment(kind).cloned();
+
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ TRADUCCIÓN SEGMENTADA (PID {}) ", pid);
+ println!("╠════════════════════════════════════════════════════╣");
+ println!("║ 1. Selector {} ({:?}), desplazamiento {:#x}, acceso {:?}", kind.index(), kind, offset, access);
+ if let Some(seg) = &segment {
+ println!("║ 2. Entrada: base {:#x}, límite {:#x}, permisos {}", seg.base, seg.limit, seg.perms);
+ }
+
+ let target = match self.segmentation.translate(pid, kind, offset, access) {
+ Ok(target) => target,
+ Err(fault) => {
+ println!("║ 3. SEGMENTATION FAULT: {}", fault);
+ println!("╚════════════════════════════════════════════════════╝");
+ println!("[ERROR] Segmentation fault en proceso {}: se termina", pid);
+ self.kill_process(pid)?;
+ return Ok(None);
+ }
+ };
+
+ let physical = match target {
+ SegmentTarget::Physical(address) => {
+ println!("║ 3. Base + desplazamiento = {:#x}", address);
+ address
+ }
+ SegmentTarget::Page { page, offset: page_offset } => {
+ println!("║ 3. Segmento paginado: página {:#x}, desplazamiento {:#x}", page, page_offset);
+ let frame = self.memory_manager.access_page(pid, page, self.demand_paging.algorithm)
+ .map_err(|e| anyhow::anyhow!(e))?;
+ let address = frame * self.memory_manager.addressing().page_size + page_offset;
+ println!("║ 4. Marco {} -> dirección física {:#x}", frame, address);
+ address
+ }
+ };
+ println!("╚════════════════════════════════════════════════════╝");
+ self.feed_cache(physical as u64);
+ Ok(Some(physical))
+ }
+
+ pub fn seg_status(&self) {
+ self.segmentation.display();
+ }
+
+ /// Mostrar el área de swap
+ pub fn swap_status(&self) {
+ let swap = self.memory_manager.swap();
+ if swap.is_enabled() {
+ swap.display();
+ } else {
+ println!("[INFO] Swap desactivado (usa init --swap-slots N)");
+ }
+ }
+
+ /// Asignar memoria heap con Buddy Allocator en la zona pedida (con respaldo)
+ pub fn heap_alloc(&mut self, pid: u32, size: usize, zone: ZoneKind) -> Result<usize> {
+ self.heap.set_clock(self.current_time);
+ self.heap.alloc(pid, size, zone)
+ .map(|(address, _)| address)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Redimensionar un bloque del heap (en el lugar si su buddy está libre)
+ pub fn heap_realloc(&mut self, address: usize, size: usize) -> Result<usize> {
+ self.check_not_slab(address)?;
+ self.heap.set_clock(self.current_time);
+ self.heap.realloc(address, size)
  .map_err(|e| anyhow::anyhow!(e))
  }
 
  /// Liberar memoria heap
  pub fn heap_free(&mut self, address: usize) -> Result<()> {
- self.heap_allocator.free(address)
+ self.check_not_slab(address)?;
+ self.heap.free(address)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Los bloques que respaldan slabs solo se devuelven con slab-shrink
+ fn check_not_slab(&self, address: usize) -> Result<()> {
+ match self.slab.cache_of_slab(address) {
+ Some(cache) => Err(anyhow::anyhow!("El bloque {:#06x} respalda un slab de la caché '{}': usa slab-shrink", address, cache)),
+ None => Ok(()),
+ }
+ }
+
+ /// Liberar todos los bloques de un proceso en ambos heaps; devuelve cuántos
+ /// El pid del kernel se rechaza: sus bloques respaldan slabs
+ pub fn heap_free_pid(&mut self, pid: u32) -> Result<usize> {
+ if pid == KERNEL_PID {
+ return Err(anyhow::anyhow!("Los bloques del pid {} respaldan slabs del kernel: usa slab-shrink", pid));
+ }
+ let leaks = self.collect_heap_blocks(pid);
+ for leak in &leaks {
+ println!("[Heap] Liberado {:#06x} ({} B, {}) del proceso {}", leak.address, leak.size, leak.heap, pid);
+ }
+ Ok(leaks.len())
+ }
+
+ /// Quitar del heap buddy y del contiguo todo lo que posee un proceso
+ fn collect_heap_blocks(&mut self, pid: u32) -> Vec<HeapLeak> {
+ let now = self.current_time;
+ let buddy = self.heap.free_pid(pid).into_iter().map(|b| HeapLeak {
+ pid,
+ address: b.address,
+ heap: "buddy".to_string(),
+ requested: b.requested,
+ size: b.size,
+ allocated_at: b.allocated_at,
+ reclaimed_at: now,
+ });
+ let contiguous = self.contiguous_heap.free_pid(pid).into_iter().map(|p| HeapLeak {
+ pid,
+ address: p.address,
+ heap: "contiguo".to_string(),
+ requested: p.size,
+ size: p.size,
+ allocated_at: p.allocated_at,
+ reclaimed_at: now,
+ });
+ buddy.chain(contiguous).collect()
+ }
+
+ /// Reclamar el heap de un proceso que terminó y reportar las fugas
+ fn reclaim_heap(&mut self, pid: u32) {
+ let leaks = self.collect_heap_blocks(pid);
+ if leaks.is_empty() {
+ return;
+ }
+ let bytes: usize = leaks.iter().map(|l| l.size).sum();
+ println!("[MEM] Proceso {} terminó con {} bloque(s) de heap sin liberar ({} B): reclamados", pid, leaks.len(), bytes);
+ Self::print_leaks(&leaks);
+ self.heap_leaks.extend(leaks);
+ }
+
+ /// Reporte de fugas acumulado de todos los procesos terminados
+ pub fn heap_leak_report(&self) {
+ if self.heap_leaks.is_empty() {
+ println!("[INFO] Ningún proceso terminó con heap sin liberar");
+ return;
+ }
+ Self::print_leaks(&self.heap_leaks);
+ let bytes: usize = self.heap_leaks.iter().map(|l| l.size).sum();
+ println!("Total: {} bloque(s), {} bytes reclamados al terminar", self.heap_leaks.len(), bytes);
+ }
+
+ fn print_leaks(leaks: &[HeapLeak]) {
+ println!("\n╔═════╦══════════╦══════════╦═══════════╦═══════════╦═══════════╦═══════════╗");
+ println!("║ PID ║ Dirección║ Heap ║ Pedido(B) ║ Bloque(B) ║ Asignado ║ Reclamado ║");
+ println!("╠═════╬══════════╬══════════╬═══════════╬═══════════╬═══════════╬═══════════╣");
+ for leak in leaks {
+ println!("║ {:3} ║ {:#08x} ║ {:8} ║ {:9} ║ {:9} ║ t={:<7} ║ t={:<7} ║",
+ leak.pid, leak.address, leak.heap, leak.requested, leak.size, leak.allocated_at, leak.reclaimed_at);
+ }
+ println!("╚═════╩══════════╩══════════╩═══════════╩═══════════╩═══════════╩═══════════╝");
+ }
+
+ /// Asignar memoria heap con particiones variables
+ pub fn heap_alloc_contiguous(&mut self, pid: u32, size: usize, strategy: FitStrategy) -> Result<usize> {
+ self.contiguous_heap.set_clock(self.current_time);
+ self.contiguous_heap.alloc(pid, size, strategy)
  .map_err(|e| anyhow::anyhow!(e))
  }
 
+ /// Liberar memoria del heap contiguo
+ pub fn heap_free_contiguous(&mut self, address: usize) -> Result<()> {
+ self.contiguous_heap.free(address)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Compactar el heap contiguo mostrando las reubicaciones
+ pub fn heap_compact(&mut self) {
+ let relocations = self.contiguous_heap.compact();
+ if relocations.is_empty() {
+ println!("[INFO] Heap contiguo ya compacto");
+ }
+ for (old, new, pid) in relocations {
+ println!("[Contiguo] Bloque de proceso {} reubicado: {:#06x} -> {:#06x}", pid, old, new);
+ }
+ self.contiguous_heap.display();
+ }
+
+ /// Crear una caché de objetos de tamaño fijo
+ pub fn slab_create(&mut self, name: &str, object_size: usize) -> Result<()> {
+ let cache = self.slab.create_cache(name, object_size)
+ .map_err(|e| anyhow::anyhow!(e))?;
+ println!("[OK] Caché '{}' creada: objetos de {} B ({} B alineados), slabs de {} B con {} objetos",
+ cache.name, cache.object_size, cache.aligned_size, cache.slab_size, cache.objects_per_slab);
+ Ok(())
+ }
+
+ /// Asignar un objeto de una caché (los slabs salen del heap buddy)
+ pub fn slab_alloc(&mut self, name: &str) -> Result<usize> {
+ self.heap.set_clock(self.current_time);
+ self.slab.alloc(name, &mut self.heap)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ pub fn slab_free(&mut self, name: &str, address: usize) -> Result<()> {
+ self.slab.free(name, address)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ /// Devolver al buddy los slabs vacíos de una caché
+ pub fn slab_shrink(&mut self, name: &str) -> Result<usize> {
+ self.slab.shrink(name, &mut self.heap)
+ .map_err(|e| anyhow::anyhow!(e))
+ }
+
+ pub fn slab_status(&self) {
+ self.slab.report();
+ }
+
  /// Mostrar estado del heap allocator
  pub fn heap_status(&self) {
- self.heap_allocator.display();
+ self.heap.display();
  
- let stats = self.heap_allocator.stats();
+ let stats = self.heap.stats();
  println!("\n╔════════════════════════════════════════════════════╗");
  println!("║ ESTADÍSTICAS DEL HEAP ALLOCATOR ║");
  println!("╠════════════════════════════════════════════════════╣");
@@ -384,15 +1648,65 @@ impl KernelState {
  println!("║ Fragmentación interna: {} bytes ", stats.internal_fragmentation);
  println!("║ Fragmentación externa: {:.2}% ", stats.external_fragmentation);
  println!("╚════════════════════════════════════════════════════╝");
+
+ if self.contiguous_heap.partitions().len() > 1 {
+ self.contiguous_heap.display();
+ Self::compare_heaps(&stats, &self.contiguous_heap.stats(), self.contiguous_heap.failed_allocations());
+ }
+ }
+
+ /// Tabla comparativa buddy vs particiones variables
+ fn compare_heaps(buddy: &BuddyStats, contiguous: &BuddyStats, contiguous_failed: u64) {
+ println!("\n╔══════════════════════════════╦════════════╦════════════╗");
+ println!("║ Métrica ║ Buddy ║ Contiguo ║");
+ println!("╠══════════════════════════════╬════════════╬════════════╣");
+ println!("║ Memoria asignada (bytes) ║ {:10} ║ {:10} ║", buddy.allocated_memory, contiguous.allocated_memory);
+ println!("║ Memoria libre (bytes) ║ {:10} ║ {:10} ║", buddy.free_memory, contiguous.free_memory);
+ println!("║ Bloques libres ║ {:10} ║ {:10} ║", buddy.free_blocks, contiguous.free_blocks);
+ println!("║ Fragmentación interna (B) ║ {:10} ║ {:10} ║", buddy.internal_fragmentation, contiguous.internal_fragmentation);
+ println!("║ Fragmentación externa (%) ║ {:10.2} ║ {:10.2} ║", buddy.external_fragmentation, contiguous.external_fragmentation);
+ println!("║ Asignaciones fallidas ║ {:>10} ║ {:10} ║", "-", contiguous_failed);
+ println!("╚══════════════════════════════╩════════════╩════════════╝");
+ }
+}
+
+/// Paginación por demanda durante la ejecución de los procesos
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct DemandPagingConfig {
+ pub algorithm: ReplacementAlgorithm,
+ pub accesses_per_tick: u32,
+ pub fault_service_ticks: u64, // Ticks bloqueado por fallo de página
+}
+
+impl Default for DemandPagingConfig {
+ fn default() -> Self {
+ Self {
+ algorithm: ReplacementAlgorithm::Lru,
+ accesses_per_tick: 1,
+ fault_service_ticks: 2,
+ }
  }
 }
 
+/// Bloque de heap que seguía asignado cuando su proceso terminó
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HeapLeak {
+ pub pid: u32,
+ pub address: usize,
+ pub heap: String, // "buddy" o "contiguo"
+ pub requested: usize,
+ pub size: usize,
+ pub allocated_at: u64,
+ pub reclaimed_at: u64,
+}
+
 /// Tipos de scheduler disponibles
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum SchedulerType {
  RoundRobin(u64),
  SJF,
  FIFO,
+ Priority,
 }
 
 /// Función principal de arranque
@@ -401,3 +1715,275 @@ pub fn run(config: Option<String>) -> Result<()> {
  println!("Kernel simulado iniciado. Usa la CLI para interactuar.");
  Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_page_fault_blocks_and_other_process_runs() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let p1 = kernel.create_process_with_pattern(3, 100, Some(MemoryPattern::reference_string(vec![0, 1])));
+ let p2 = kernel.create_process(2, 100);
+
+ // t=1: P1 falla en la página 0 y P2 ocupa la CPU
+ kernel.tick(1);
+ assert_eq!(kernel.processes[&p1].state, ProcessState::Blocked);
+ assert_eq!(kernel.processes[&p2].remaining_burst, 1);
+
+ kernel.tick(20);
+ assert!(kernel.processes.is_empty());
+ let p1_done = kernel.finished_processes.iter().find(|p| p.pid == p1).unwrap();
+ // Cadena cíclica 0,1: solo fallan los dos primeros accesos
+ assert_eq!(p1_done.page_faults, 2);
+ assert!(p1_done.waiting_time(kernel.current_time) > 0);
+ }
+
+ #[test]
+ fn test_segfault_kills_process() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let pid = kernel.create_process(5, 100);
+ kernel.seg_setup(pid, [256, 256, 256, 256], false).unwrap();
+
+ assert_eq!(kernel.seg_translate(pid, SegmentKind::Stack, 4, AccessKind::Write).unwrap(), Some(768 + 4));
+ assert_eq!(kernel.seg_translate(pid, SegmentKind::Code, 0, AccessKind::Write).unwrap(), None);
+ assert!(!kernel.processes.contains_key(&pid));
+ assert!(kernel.segmentation.table(pid).is_none());
+ }
+
+ #[test]
+ fn test_paged_segments_refuse_mapped_pages() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let p1 = kernel.create_process(5, 100);
+ let p2 = kernel.create_process(5, 100);
+ let stack = SegmentationManager::paged_base(SegmentKind::Stack, 1 << 20);
+ kernel.memory_manager.access_page(p1, stack, kernel.demand_paging.algorithm).unwrap();
+ kernel.memory_manager.access_page(p2, 0, kernel.demand_paging.algorithm).unwrap();
+
+ // P1 ya usa una página de la región de segmentos; la página 0 de P2 no choca
+ assert!(kernel.seg_setup(p1, [256, 256, 256, 256], true).is_err());
+ kernel.seg_setup(p2, [256, 256, 256, 256], true).unwrap();
+ assert!(kernel.seg_translate(p2, SegmentKind::Code, 4, AccessKind::Read).unwrap().is_some());
+ }
+
+ #[test]
+ fn test_semaphore_blocks_and_wakes_process() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let p1 = kernel.create_process(5, 100);
+ let p2 = kernel.create_process(5, 100);
+ kernel.sem_create("s", 0).unwrap();
+
+ assert!(!kernel.sem_wait("s", p1).unwrap());
+ assert_eq!(kernel.processes[&p1].state, ProcessState::Blocked);
+ assert!(kernel.resume_process(p1).is_err());
+
+ // Solo P2 ejecuta mientras P1 espera
+ kernel.tick(2);
+ assert_eq!(kernel.processes[&p1].remaining_burst, 5);
+
+ assert_eq!(kernel.sem_signal("s").unwrap(), Some(p1));
+ assert_eq!(kernel.processes[&p1].state, ProcessState::Ready);
+
+ // Un proceso terminado mientras espera devuelve su unidad
+ kernel.sem_wait("s", p2).unwrap();
+ kernel.sem_wait("s", p1).unwrap();
+ kernel.kill_process(p1).unwrap();
+ assert_eq!(kernel.ipc.get_semaphore("s").unwrap().count(), -1);
+ }
+
+ #[test]
+ fn test_mutex_released_when_owner_finishes() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let p1 = kernel.create_process(1, 100);
+ let p2 = kernel.create_process(5, 100);
+ kernel.mutex_create("m", false, MutexProtocol::None).unwrap();
+
+ kernel.mutex_lock("m", p1).unwrap();
+ assert!(!kernel.mutex_lock("m", p2).unwrap());
+
+ // P1 termina su ráfaga con el mutex tomado: pasa a P2
+ kernel.tick(1);
+ assert!(!kernel.processes.contains_key(&p1));
+ assert_eq!(kernel.processes[&p2].state, ProcessState::Ready);
+ assert_eq!(kernel.ipc.mutexes_owned_by(p2), vec!["m".to_string()]);
+ }
+
+ #[test]
+ fn test_mailbox_blocks_receiver_and_sender() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let p1 = kernel.create_process(5, 100);
+ let p2 = kernel.create_process(5, 100);
+ let p3 = kernel.create_process(5, 100);
+
+ // Recepción directa bloqueante: P1 espera hasta que P2 le escribe
+ assert_eq!(kernel.msg_recv(p1, None, None, true).unwrap(), RecvOutcome::Blocked);
+ assert_eq!(kernel.processes[&p1].state, ProcessState::Blocked);
+ assert!(kernel.resume_process(p1).is_err());
+ assert_eq!(kernel.msg_send(p2, &Address::Process(p1), "hola", 0, true).unwrap(), SendOutcome::Delivered(p1));
+ assert_eq!(kernel.processes[&p1].state, ProcessState::Ready);
+
+ // Buzón indirecto de capacidad 1: el segundo emisor queda bloqueado hasta un recv
+ kernel.mbox_create("q", 1).unwrap();
+ let q = Address::Mailbox("q".to_string());
+ kernel.msg_send(p2, &q, "a", 0, true).unwrap();
+ assert_eq!(kernel.msg_send(p3, &q, "b", 0, false).unwrap(), SendOutcome::WouldBlock);
+ assert_eq!(kernel.msg_send(p3, &q, "b", 0, true).unwrap(), SendOutcome::Blocked);
+ assert_eq!(kernel.processes[&p3].state, ProcessState::Blocked);
+ kernel.msg_recv(p1, Some("q"), None, true).unwrap();
+ assert_eq!(kernel.processes[&p3].state, ProcessState::Ready);
+
+ // Al terminar su ráfaga, el buzón directo de P4 desaparece y su emisor bloqueado se despierta
+ let p4 = kernel.create_process(1, 100);
+ let to_p4 = Address::Process(p4);
+ for _ in 0..mailbox::DIRECT_CAPACITY {
+ kernel.msg_send(p2, &to_p4, "x", 0, true).unwrap();
+ }
+ assert_eq!(kernel.msg_send(p3, &to_p4, "y", 0, true).unwrap(), SendOutcome::Blocked);
+ kernel.kill_process(p1).unwrap();
+ kernel.kill_process(p2).unwrap();
+ kernel.tick(1);
+ assert!(!kernel.processes.contains_key(&p4));
+ assert_eq!(kernel.processes[&p3].state, ProcessState::Ready);
+ assert!(kernel.ipc.mailbox_waited_by(p3).is_none());
+ }
+
+ #[test]
+ fn test_deadlock_detected_and_recovered() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let p1 = kernel.create_process(5, 100);
+ let p2 = kernel.create_process(5, 100);
+ kernel.set_priority(p1, 3).unwrap();
+ kernel.res_create("disco", 1).unwrap();
+ kernel.res_create("cinta", 1).unwrap();
+ assert!(kernel.claim_vector(&[("disco".to_string(), 2)]).is_err());
+
+ assert_eq!(kernel.res_request(p1, "disco", 1).unwrap(), RequestOutcome::Granted);
+ assert_eq!(kernel.res_request(p2, "cinta", 1).unwrap(), RequestOutcome::Granted);
+ assert_eq!(kernel.res_request(p1, "cinta", 1).unwrap(), RequestOutcome::Blocked);
+ assert_eq!(kernel.res_request(p2, "disco", 1).unwrap(), RequestOutcome::Blocked);
+ assert!(kernel.deadlock_dot().contains("fillcolor"));
+
+ // La víctima es P2 (menor prioridad); P1 recibe la cinta y vuelve a listos
+ let report = kernel.deadlock_check(Some(Recovery::Kill));
+ assert_eq!(report.deadlocked, vec![p1, p2]);
+ assert!(!kernel.processes.contains_key(&p2));
+ assert_eq!(kernel.processes[&p1].state, ProcessState::Ready);
+ assert_eq!(kernel.resources.allocation_of(p1), vec![1, 1]);
+
+ // Al terminar su ráfaga P1 devuelve el disco y la cinta a quien los espera
+ let p3 = kernel.create_process(5, 100);
+ assert_eq!(kernel.res_request(p3, "disco", 1).unwrap(), RequestOutcome::Blocked);
+ kernel.tick(5);
+ assert!(!kernel.processes.contains_key(&p1));
+ assert_eq!(kernel.processes[&p3].state, ProcessState::Ready);
+ assert_eq!(kernel.resources.allocation_of(p3), vec![1, 0]);
+ }
+
+ #[test]
+ fn test_blocked_consumer_resumes_on_produce() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let consumer = kernel.create_process(5, 100);
+ let producer = kernel.create_process(5, 100);
+
+ assert_eq!(kernel.consume(consumer).unwrap().result, BufferResult::Blocked);
+ assert_eq!(kernel.processes[&consumer].state, ProcessState::Blocked);
+ assert!(kernel.consume(consumer).is_err());
+
+ let report = kernel.produce("x".to_string(), producer).unwrap();
+ assert_eq!(report.resumed, vec![(consumer, BufferResult::Consumed("x".to_string()))]);
+ assert_eq!(kernel.processes[&consumer].state, ProcessState::Ready);
+ }
+
+ #[test]
+ fn test_priority_inheritance_avoids_inversion() {
+ let mut kernel = KernelState::new(SchedulerType::Priority, 4);
+ let low = kernel.create_process(5, 100);
+ let high = kernel.create_process(5, 100);
+ let medium = kernel.create_process(5, 100);
+ kernel.set_priority(low, 1).unwrap();
+ kernel.set_priority(high, 10).unwrap();
+ kernel.set_priority(medium, 5).unwrap();
+ kernel.mutex_create("m", false, MutexProtocol::Inheritance).unwrap();
+
+ kernel.mutex_lock("m", low).unwrap();
+ assert!(!kernel.mutex_lock("m", high).unwrap());
+ assert!(kernel.mutex_unlock("m", medium).is_err());
+
+ // L hereda la prioridad de H y ejecuta antes que M
+ assert_eq!(kernel.processes[&low].priority, 10);
+ kernel.tick(1);
+ assert_eq!(kernel.processes[&low].remaining_burst, 4);
+ assert_eq!(kernel.processes[&medium].remaining_burst, 5);
+
+ assert_eq!(kernel.mutex_unlock("m", low).unwrap(), Some(high));
+ assert_eq!(kernel.processes[&low].priority, 1);
+ assert_eq!(kernel.processes[&high].state, ProcessState::Ready);
+ }
+
+ #[test]
+ fn test_thrashing_resume_keeps_unresumed_pids() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let thrashing = ThrashingConfig { enabled: true, window: 1, ..ThrashingConfig::default() };
+ kernel.configure_frame_allocation(FrameAllocationPolicy::default(), ReplacementScope::default(), PffConfig::default(), thrashing);
+ let p1 = kernel.create_process(5, 100);
+ let p2 = kernel.create_process(5, 100);
+ let p3 = kernel.create_process(5, 100);
+
+ // Un resume manual lo saca de la lista de suspendidos por thrashing
+ kernel.suspend_process(p1).unwrap();
+ kernel.thrashing.suspended.push(p1);
+ kernel.resume_process(p1).unwrap();
+ assert!(kernel.thrashing.suspended.is_empty());
+
+ // P3 no está bloqueado: no se puede reanudar y se reanuda P2 en su lugar
+ kernel.suspend_process(p2).unwrap();
+ kernel.thrashing.suspended = vec![p3, p2];
+ kernel.memory_housekeeping(true);
+ assert_eq!(kernel.processes[&p2].state, ProcessState::Ready);
+ assert_eq!(kernel.thrashing.suspended, vec![p3]);
+ }
+
+ #[test]
+ fn test_heap_reclaimed_on_exit() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ let p1 = kernel.create_process(1, 100);
+ let p2 = kernel.create_process(5, 100);
+ kernel.heap_alloc(p1, 100, ZoneKind::Normal).unwrap();
+ kernel.heap_alloc(p2, 50, ZoneKind::Normal).unwrap();
+ kernel.heap_alloc_contiguous(p1, 300, FitStrategy::First).unwrap();
+
+ // P1 termina en el primer tick sin liberar sus bloques
+ kernel.tick(1);
+ assert_eq!(kernel.heap_leaks.len(), 2);
+ assert_eq!(kernel.heap_leaks[0].requested, 100);
+ assert_eq!(kernel.heap.stats().allocated_blocks, 1);
+
+ assert_eq!(kernel.heap_free_pid(p2).unwrap(), 1);
+ kernel.kill_process(p2).unwrap();
+ assert_eq!(kernel.heap_leaks.len(), 2);
+ assert_eq!(kernel.heap.stats().allocated_memory, 0);
+ }
+
+ #[test]
+ fn test_slab_blocks_not_freed_through_heap() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ kernel.slab_create("pcb", 96).unwrap();
+ kernel.slab_alloc("pcb").unwrap();
+ let slab = kernel.slab.cache("pcb").unwrap().slabs()[0].address;
+
+ assert!(kernel.heap_free(slab).is_err());
+ assert!(kernel.heap_realloc(slab, 1024).is_err());
+ assert_eq!(kernel.heap.stats().allocated_blocks, 1);
+ }
+
+ #[test]
+ fn test_heap_free_pid_keeps_kernel_slabs() {
+ let mut kernel = KernelState::new(SchedulerType::FIFO, 4);
+ kernel.slab_create("pcb", 96).unwrap();
+ kernel.slab_alloc("pcb").unwrap();
+
+ assert!(kernel.heap_free_pid(KERNEL_PID).is_err());
+ assert_eq!(kernel.heap.stats().allocated_blocks, 1);
+ assert_eq!(kernel.slab.cache("pcb").unwrap().objects_in_use(), 1);
+ }
+}
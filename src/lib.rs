@@ -1,4 +1,5 @@
 pub mod kernel;
 pub mod process;
 pub mod scheduler;
-pub mod modules;
\ No newline at end of file
+pub mod modules;
+pub mod rng;
@@ -1,11 +1,31 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use anyhow::Result;
-use kernel_sim::kernel::{KernelState, SchedulerType};
+use kernel_sim::kernel::{DemandPagingConfig, KernelState, SchedulerType};
+use kernel_sim::process::MemoryPattern;
+use kernel_sim::modules::mem::paging::ReplacementAlgorithm;
+use kernel_sim::modules::mem::contiguous::FitStrategy;
+use kernel_sim::modules::mem::zone::ZoneKind;
+use kernel_sim::modules::mem::tlb::{AccessLatencies, TlbPolicy};
+use kernel_sim::modules::mem::address::{AddressingConfig, PageTableKind};
+use kernel_sim::modules::mem::swap::SwapConfig;
+use kernel_sim::modules::mem::allocation::{FrameAllocationPolicy, ReplacementScope, PffConfig, ThrashingConfig};
 use std::fs;
-use kernel_sim::modules::ipc::philosophers::DiningPhilosophers;
+use kernel_sim::modules::ipc::philosophers::{DiningPhilosophers, Strategy};
+use kernel_sim::modules::ipc::readers_writers::{self, ReadersWriters, RwPolicy};
+use kernel_sim::modules::ipc::barber::SleepingBarber;
+use kernel_sim::modules::ipc::smokers::CigaretteSmokers;
+use kernel_sim::modules::ipc::h2o::WaterFactory;
+use kernel_sim::modules::ipc::monitor::{self, BufferVariant, BufferWorkload, MonitorSemantics};
+use kernel_sim::modules::ipc::sync::{BufferReport, BufferResult, MutexProtocol};
+use kernel_sim::modules::ipc::deadlock::{Recovery, RequestOutcome};
+use kernel_sim::modules::ipc::mailbox::{Address, RecvOutcome, SendOutcome};
+use kernel_sim::modules::mem::buddy;
+use kernel_sim::modules::mem::cache::{CacheConfig, CacheHierarchy, CacheReplacement};
+use kernel_sim::modules::mem::trace::TraceReader;
+use kernel_sim::modules::mem::segmentation::{AccessKind, SegmentKind};
 use kernel_sim::modules::disk::scheduler::{
  FcfsScheduler, SstfScheduler, ScanScheduler, ScanDirection, 
- DiskRequest, DiskSimulator, DiskScheduler
+ DiskRequest, DiskSimulator, DiskScheduler, DiskSchedulerKind
 };
 
 #[derive(Parser)]
@@ -24,13 +44,16 @@ enum Commands {
  /// Inicializar el kernel con scheduler específico
  Init {
  #[arg(short, long, default_value = "rr")]
- scheduler: String, // rr, sjf, fifo
+ scheduler: String, // rr, sjf, fifo, priority
  
  #[arg(short, long, default_value = "4")]
  quantum: u64, // Solo para RR
  
  #[arg(short, long, default_value = "8")]
  frames: usize, // Número de marcos de memoria
+
+ #[command(flatten)]
+ memory: Box<MemoryOptions>,
  },
 
  /// Crear un nuevo proceso
@@ -40,6 +63,39 @@ enum Commands {
  
  #[arg(short, long)]
  mem: usize,
+
+ /// Cadena de referencias a páginas (ej: 0,1,2,0,3)
+ #[arg(long, value_delimiter = ',')]
+ refs: Vec<usize>,
+
+ /// Modelo de localidad: páginas por localidad
+ #[arg(long)]
+ locality: Option<usize>,
+
+ /// Páginas totales del proceso (modelo de localidad)
+ #[arg(long, default_value = "16")]
+ pages: usize,
+
+ /// Referencias por fase de localidad
+ #[arg(long, default_value = "20")]
+ phase: u64,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
+
+ /// Prioridad (mayor número = más prioridad; la usa el scheduler priority)
+ #[arg(long, default_value = "0")]
+ priority: u8,
+
+ /// Máximo por recurso para el banquero (ej: disco=2,cinta=1)
+ #[arg(long, value_delimiter = ',')]
+ max: Vec<String>,
+ },
+
+ /// Cambiar la prioridad base de un proceso
+ SetPriority {
+ pid: u32,
+ priority: u8,
  },
 
  /// Listar todos los procesos
@@ -94,11 +150,169 @@ enum Commands {
  /// Mostrar estado del buffer productor-consumidor
  BufferStat,
 
+ /// Crear un semáforo con nombre
+ SemCreate {
+ name: String,
+ value: i32,
+ },
+
+ /// wait (P) de un proceso: si el valor queda negativo el proceso se bloquea
+ SemWait {
+ name: String,
+
+ #[arg(short, long)]
+ pid: u32,
+ },
+
+ /// signal (V): despierta al primer proceso de la cola
+ SemSignal {
+ name: String,
+ },
+
+ /// Mostrar semáforos y sus colas
+ SemStatus,
+
+ /// Crear un mutex (dueño único; opcionalmente recursivo)
+ MutexCreate {
+ name: String,
+
+ #[arg(long)]
+ recursive: bool,
+
+ /// none, inherit (herencia de prioridad) o ceiling (techo de prioridad)
+ #[arg(long, default_value = "none")]
+ protocol: String,
+
+ /// Techo para --protocol ceiling
+ #[arg(long, default_value = "0")]
+ ceiling: u8,
+ },
+
+ /// Tomar un mutex; el proceso se bloquea si tiene otro dueño
+ MutexLock {
+ name: String,
+
+ #[arg(short, long)]
+ pid: u32,
+ },
+
+ /// Liberar un mutex (solo su dueño)
+ MutexUnlock {
+ name: String,
+
+ #[arg(short, long)]
+ pid: u32,
+ },
+
+ /// Mostrar mutexes, dueños y esperas
+ MutexStatus,
+
+ /// Crear un buzón con nombre (capacidad 0 = rendezvous)
+ MboxCreate {
+ name: String,
+
+ #[arg(long, default_value = "4")]
+ capacity: usize,
+ },
+
+ /// Enviar un mensaje a un buzón (--mailbox) o directamente a un proceso (--to)
+ MsgSend {
+ payload: String,
+
+ /// Proceso emisor
+ #[arg(short, long)]
+ pid: u32,
+
+ #[arg(long, conflicts_with = "mailbox")]
+ to: Option<u32>,
+
+ #[arg(long)]
+ mailbox: Option<String>,
+
+ /// Mayor número = se entrega antes
+ #[arg(long, default_value = "0")]
+ priority: u8,
+
+ /// No bloquear si el buzón está lleno
+ #[arg(long)]
+ nonblocking: bool,
+ },
+
+ /// Recibir un mensaje de un buzón (--mailbox) o del buzón propio del proceso
+ MsgRecv {
+ /// Proceso receptor
+ #[arg(short, long)]
+ pid: u32,
+
+ #[arg(long)]
+ mailbox: Option<String>,
+
+ /// Aceptar solo mensajes de este emisor
+ #[arg(long)]
+ from: Option<u32>,
+
+ /// No bloquear si no hay mensajes
+ #[arg(long)]
+ nonblocking: bool,
+ },
+
+ /// Mostrar buzones, mensajes y procesos bloqueados
+ MboxStatus,
+
+ /// Crear un tipo de recurso con varias instancias
+ ResCreate {
+ name: String,
+ instances: u32,
+ },
+
+ /// Pedir instancias de un recurso; el proceso se bloquea si no alcanzan
+ ResRequest {
+ name: String,
+
+ #[arg(short, long)]
+ pid: u32,
+
+ #[arg(short, long, default_value = "1")]
+ count: u32,
+ },
+
+ /// Devolver instancias de un recurso
+ ResRelease {
+ name: String,
+
+ #[arg(short, long)]
+ pid: u32,
+
+ #[arg(short, long, default_value = "1")]
+ count: u32,
+ },
+
+ /// Mostrar asignaciones y peticiones de recursos
+ ResStatus,
+
+ /// Mostrar matrices Allocation, Max, Need y Available del banquero y una secuencia segura
+ BankerState,
+
+ /// Detectar interbloqueos (grafo de asignación y algoritmo de detección)
+ DeadlockCheck {
+ /// Exportar el grafo en formato DOT a un archivo ("-" = pantalla)
+ #[arg(long)]
+ dot: Option<String>,
+
+ /// Recuperarse: kill (terminar víctimas) o preempt (expropiar recursos)
+ #[arg(long)]
+ recover: Option<String>,
+ },
+
  /// Simular acceso a memoria con FIFO
  MemFifo {
  #[arg(long)]
  pid: u32,
  pages: Vec<usize>, // Lista de páginas a acceder
+
+ /// Archivo de traza (`pid op dirección` o Valgrind lackey) en lugar de páginas
+ #[arg(long, conflicts_with = "pages")]
+ trace: Option<String>,
  },
 
  /// Simular acceso a memoria con LRU
@@ -106,6 +320,9 @@ enum Commands {
  #[arg(long)]
  pid: u32,
  pages: Vec<usize>,
+
+ #[arg(long, conflicts_with = "pages")]
+ trace: Option<String>,
  },
 
  /// Simular acceso a memoria con Working Set
@@ -117,34 +334,350 @@ enum Commands {
  window: usize,
  
  pages: Vec<usize>,
+
+ #[arg(long, conflicts_with = "pages")]
+ trace: Option<String>,
+ },
+
+ /// Traducir una dirección virtual (ej: 0x1a2b3)
+ MemTranslate {
+ #[arg(long)]
+ pid: u32,
+
+ #[arg(long, value_parser = parse_address)]
+ vaddr: u64,
+ },
+
+ /// Mostrar asignación de marcos por proceso
+ MemAlloc,
+
+ /// Crear la tabla de segmentos de un proceso (código, datos, heap, pila)
+ SegSetup {
+ pid: u32,
+
+ #[arg(long, default_value = "1024")]
+ code: usize,
+
+ #[arg(long, default_value = "512")]
+ data: usize,
+
+ #[arg(long, default_value = "1024")]
+ heap: usize,
+
+ #[arg(long, default_value = "512")]
+ stack: usize,
+
+ /// Segmentación paginada: cada segmento se divide en páginas
+ #[arg(long)]
+ paged: bool,
+ },
+
+ /// Traducir (segmento, desplazamiento); una violación termina el proceso
+ SegTranslate {
+ #[arg(long)]
+ pid: u32,
+
+ /// code, data, heap o stack
+ #[arg(long)]
+ seg: String,
+
+ #[arg(long, value_parser = parse_address)]
+ offset: u64,
+
+ /// Tipo de acceso: r, w o x
+ #[arg(long, default_value = "r")]
+ op: String,
+ },
+
+ /// Mostrar las tablas de segmentos
+ SegStatus,
+
+ /// Estadísticas de la caché de CPU configurada en init
+ CacheStatus,
+
+ /// Simular una caché con una traza de direcciones (ej: cache-sim 0x0 0x40 0x1000)
+ CacheSim {
+ #[arg(value_parser = parse_address)]
+ addresses: Vec<u64>,
+
+ #[arg(long, default_value = "1024")]
+ size: usize,
+
+ #[arg(long, default_value = "64")]
+ line: usize,
+
+ #[arg(long, default_value = "2")]
+ assoc: usize,
+
+ /// lru, fifo o random
+ #[arg(long, default_value = "lru")]
+ policy: String,
+
+ /// Tamaño de L2 (0 = sin L2)
+ #[arg(long, default_value = "0")]
+ l2_size: usize,
+
+ #[arg(long, default_value = "8")]
+ l2_assoc: usize,
+
+ /// Archivo de traza (mismos formatos que mem-* --trace)
+ #[arg(long, conflicts_with = "addresses")]
+ trace: Option<String>,
+ },
+
+ /// Crear un hijo con fork (páginas compartidas copy-on-write)
+ Fork {
+ pid: u32,
+ },
+
+ /// Reemplazar la imagen de un proceso (exec): descarta sus páginas
+ Exec {
+ pid: u32,
+
+ #[arg(long, value_delimiter = ',')]
+ refs: Vec<usize>, // Nueva cadena de referencias
+ },
+
+ /// Escribir en páginas de un proceso (dispara copy-on-write)
+ MemWrite {
+ #[arg(long)]
+ pid: u32,
+ pages: Vec<usize>,
+ },
+
+ /// Mostrar métricas de copy-on-write
+ CowStats,
+
+ /// Crear un segmento de memoria compartida
+ ShmCreate {
+ name: String,
+
+ #[arg(long, default_value = "1")]
+ pages: usize,
+ },
+
+ /// Conectar un proceso a un segmento compartido
+ ShmAttach {
+ pid: u32,
+ name: String,
+
+ /// Página virtual base (por defecto la del segmento)
+ #[arg(long)]
+ at: Option<usize>,
  },
 
+ /// Desconectar un proceso de un segmento compartido
+ ShmDetach {
+ pid: u32,
+ name: String,
+ },
+
+ /// Destruir un segmento (al desconectarse el último proceso)
+ ShmDestroy {
+ name: String,
+ },
+
+ /// Mostrar segmentos de memoria compartida
+ ShmStatus,
+
+ /// Mostrar el área de swap
+ SwapStatus,
+
  /// Mostrar marcos de memoria
  MemDisplay,
 
- /// Asignar memoria heap (Buddy Allocator)
+ /// Asignar memoria heap (Buddy Allocator o particiones variables con --fit)
  HeapAlloc {
  #[arg(short, long)]
  pid: u32,
  
+ size: usize,
+
+ /// first, best, worst o next: usa el heap contiguo
+ #[arg(long)]
+ fit: Option<String>,
+
+ /// Zona del heap buddy: normal o dma
+ #[arg(long, default_value = "normal")]
+ zone: String,
+ },
+
+ /// Cambiar el tamaño de un bloque del heap buddy
+ HeapRealloc {
+ address: usize,
  size: usize,
  },
 
  /// Liberar memoria heap
  HeapFree {
- address: usize,
+ #[arg(required_unless_present = "pid")]
+ address: Option<usize>,
+
+ /// Liberar todos los bloques de un proceso (ambos heaps)
+ #[arg(long, conflicts_with = "address")]
+ pid: Option<u32>,
+
+ /// Liberar en el heap contiguo
+ #[arg(long)]
+ contiguous: bool,
+ },
+
+ /// Reporte de bloques de heap que seguían asignados al terminar su proceso
+ HeapLeaks,
+
+ /// Compactar el heap contiguo (reubica bloques)
+ HeapCompact,
+
+ /// Crear una caché slab (ej: slab-create pcb 96)
+ SlabCreate {
+ name: String,
+ size: usize,
+ },
+
+ /// Asignar objetos de una caché slab
+ SlabAlloc {
+ name: String,
+
+ #[arg(short, long, default_value = "1")]
+ count: usize,
+ },
+
+ /// Liberar un objeto de una caché slab
+ SlabFree {
+ name: String,
+
+ #[arg(value_parser = parse_address)]
+ address: u64,
+ },
+
+ /// Devolver al heap los slabs vacíos de una caché
+ SlabShrink {
+ name: String,
  },
 
+ /// Reporte de cachés slab
+ SlabStatus,
+
  /// Mostrar estado del heap allocator
  HeapStatus,
 
+ /// Benchmark del buddy allocator con alloc/free aleatorios
+ HeapBench {
+ #[arg(long, default_value = "1000000")]
+ ops: u64,
+
+ #[arg(long, default_value = "1048576")]
+ size: usize,
+
+ #[arg(long, default_value = "64")]
+ min_block: usize,
+
+ /// Tamaño máximo de cada petición
+ #[arg(long, default_value = "4096")]
+ max_request: usize,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
+ },
+
  /// Simular cena de los filósofos
  Philosophers {
  #[arg(short, long, default_value = "5")]
  count: usize,
  
- #[arg(short, long, default_value = "10")]
+ #[arg(short, long, default_value = "30")]
+ steps: usize,
+
+ /// naive (puede interbloquearse), asymmetric, waiter, hierarchy o chandy-misra
+ #[arg(long, default_value = "asymmetric")]
+ strategy: String,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
+ },
+
+ /// Simular el barbero dormilón
+ Barber {
+ #[arg(short, long, default_value = "3")]
+ chairs: usize,
+
+ #[arg(short, long, default_value = "40")]
+ steps: usize,
+
+ /// Probabilidad (%) de que llegue un cliente en cada tick
+ #[arg(short, long, default_value = "40")]
+ arrival: usize,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
+ },
+
+ /// Simular los fumadores de cigarrillos
+ Smokers {
+ #[arg(short, long, default_value = "30")]
+ steps: usize,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
+ },
+
+ /// Simular la formación de agua (H2O) con barrera
+ H2o {
+ #[arg(short, long, default_value = "30")]
+ steps: usize,
+
+ /// Probabilidad (%) de que llegue un átomo en cada tick
+ #[arg(short, long, default_value = "70")]
+ arrival: usize,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
+ },
+
+ /// Simular lectores-escritores con una política de admisión
+ ReadersWriters {
+ #[arg(short, long, default_value = "4")]
+ readers: usize,
+
+ #[arg(short, long, default_value = "2")]
+ writers: usize,
+
+ #[arg(short, long, default_value = "30")]
  steps: usize,
+
+ /// reader (preferencia lectores), writer (preferencia escritores), fair (FIFO) o all (comparar)
+ #[arg(short, long, default_value = "fair")]
+ policy: String,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
+ },
+
+ /// Comparar el buffer acotado con semáforos y con monitor (Hoare/Mesa)
+ MonitorBuffer {
+ #[arg(long, default_value = "2")]
+ producers: usize,
+
+ #[arg(long, default_value = "3")]
+ consumers: usize,
+
+ /// Items por productor
+ #[arg(long, default_value = "6")]
+ items: usize,
+
+ #[arg(long, default_value = "2")]
+ capacity: usize,
+
+ /// Semántica del monitor: hoare, mesa o both
+ #[arg(long, default_value = "both")]
+ semantics: String,
+
+ /// Despertar con broadcast en vez de signal
+ #[arg(long)]
+ broadcast: bool,
+
+ #[arg(long, default_value = "42")]
+ seed: u64,
  },
 
  /// Simular planificación de disco FCFS
@@ -187,273 +720,1242 @@ enum Commands {
  },
 }
 
-const KERNEL_STATE_FILE: &str = "kernel_state.json";
+/// Opciones de memoria virtual para `init`
+#[derive(Args)]
+struct MemoryOptions {
+ #[arg(long, default_value = "0")]
+ tlb_size: usize, // Entradas de la TLB (0 = sin TLB)
+
+ #[arg(long, default_value = "lru")]
+ tlb_policy: String, // lru, fifo
+
+ #[arg(long)]
+ tlb_flush: bool, // Vaciar la TLB en cada cambio de contexto
+
+ #[arg(long, default_value = "20")]
+ tlb_ns: u64, // Latencia de la TLB
+
+ #[arg(long, default_value = "100")]
+ mem_ns: u64, // Latencia de memoria
+
+ #[arg(long, default_value = "8000000")]
+ fault_ns: u64, // Tiempo de servicio de un fallo de página
+
+ #[arg(long, default_value = "4096")]
+ page_size: usize, // Bytes por página (potencia de 2)
+
+ #[arg(long, default_value = "32")]
+ vaddr_bits: u32, // Ancho de la dirección virtual
+
+ #[arg(long, default_value = "1")]
+ pt_levels: u32, // Niveles de la tabla de páginas (1-3)
+
+ #[arg(long, default_value = "multilevel")]
+ pt_kind: String, // multilevel, inverted, hashed
+
+ #[arg(long, default_value = "equal")]
+ frame_alloc: String, // equal, proportional
+
+ #[arg(long, default_value = "global")]
+ replacement: String, // global, local
+
+ #[arg(long)]
+ pff: bool, // Activar control Page Fault Frequency
+
+ #[arg(long, default_value = "0.1")]
+ pff_lower: f64,
+
+ #[arg(long, default_value = "0.5")]
+ pff_upper: f64,
+
+ #[arg(long)]
+ thrashing: bool, // Activar detección de thrashing
+
+ #[arg(long, default_value = "0.5")]
+ thrash_fault_rate: f64,
+
+ #[arg(long, default_value = "0.5")]
+ thrash_cpu: f64,
+
+ #[arg(long, default_value = "10")]
+ mem_window: u64, // Ticks por ventana de PFF/thrashing
+
+ #[arg(long, default_value = "0")]
+ swap_slots: usize, // Slots del área de swap (0 = sin swap)
+
+ #[arg(long, default_value = "fcfs")]
+ swap_sched: String, // fcfs, sstf, scan
+
+ #[arg(long, default_value = "100")]
+ swap_start: usize, // Primer cilindro del área de swap
+
+ #[arg(long, default_value = "100000")]
+ seek_ns: u64, // Costo por cilindro recorrido
+
+ #[arg(long, default_value = "1000000")]
+ transfer_ns: u64, // Costo de transferir una página
+
+ #[arg(long, default_value = "lru")]
+ paging_algo: String, // fifo, lru, ws: reemplazo en los accesos de cada tick
+
+ #[arg(long, default_value = "10")]
+ ws_window: usize, // Ventana Δ para ws
+
+ #[arg(long, default_value = "1")]
+ refs_per_tick: u32, // Referencias a memoria por tick de CPU
+
+ #[arg(long, default_value = "2")]
+ fault_ticks: u64, // Ticks bloqueado por fallo de página (0 = sin bloqueo)
+
+ #[arg(long, default_value = "0")]
+ cache_size: usize, // Bytes de la caché L1 (0 = sin caché)
+
+ #[arg(long, default_value = "64")]
+ cache_line: usize, // Bytes por línea (L1 y L2)
+
+ #[arg(long, default_value = "4")]
+ cache_assoc: usize, // Vías por conjunto de L1
+
+ #[arg(long, default_value = "lru")]
+ cache_policy: String, // lru, fifo, random
+
+ #[arg(long, default_value = "0")]
+ l2_size: usize, // Bytes de la caché L2 (0 = sin L2)
+
+ #[arg(long, default_value = "8")]
+ l2_assoc: usize,
+
+ #[arg(long, default_value = "4096")]
+ heap_size: usize, // Bytes de la zona normal del heap (potencia de 2)
+
+ #[arg(long, default_value = "64")]
+ heap_min_block: usize, // Bloque mínimo del buddy (potencia de 2)
+
+ #[arg(long, default_value = "0")]
+ dma_size: usize, // Bytes de la zona DMA (0 = sin zona DMA)
+}
+
+const KERNEL_STATE_FILE: &str = "kernel_state.json";
+
+/// Interpreta direcciones en hexadecimal (0x...) o decimal
+fn parse_address(s: &str) -> Result<u64, String> {
+ let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+ Some(hex) => u64::from_str_radix(hex, 16),
+ None => s.parse::<u64>(),
+ };
+ parsed.map_err(|_| format!("Dirección inválida: {}", s))
+}
+
+/// Carga el estado del kernel desde un archivo JSON.
+fn load_kernel() -> Result<Option<KernelState>> {
+ if let Ok(data) = fs::read_to_string(KERNEL_STATE_FILE) {
+ // Deserializa el estado
+ let mut kernel: KernelState = serde_json::from_str(&data)?;
+ 
+ // 1. Reconstruye el scheduler, que no se guarda directamente
+ let scheduler_type = kernel.scheduler_type.clone();
+ kernel.scheduler = KernelState::create_scheduler(scheduler_type);
+
+ // 2. Repuebla el scheduler con los procesos que están en estado 'Ready'
+ for process in kernel.get_ready_processes() {
+ kernel.scheduler.push(process);
+ }
+
+ Ok(Some(kernel))
+ } else {
+ Ok(None)
+ }
+}
+
+/// Guarda el estado del kernel en un archivo JSON.
+fn save_kernel(kernel: &KernelState) -> Result<()> {
+ let data = serde_json::to_string_pretty(kernel)?;
+ fs::write(KERNEL_STATE_FILE, data)?;
+ Ok(())
+}
+
+/// Resultado de produce/consume para el llamador y los procesos que despertó
+fn print_buffer_report(pid: u32, report: &BufferReport) {
+ let describe = |pid: u32, result: &BufferResult| match result {
+ BufferResult::Produced(item) => format!("[OK] Item '{}' producido por proceso {}", item, pid),
+ BufferResult::Consumed(item) => format!("[OK] Proceso {} consumió: '{}'", pid, item),
+ BufferResult::Blocked => format!("[INFO] Proceso {} bloqueado: esperará hasta que lo despierten", pid),
+ };
+ println!("{}", describe(pid, &report.result));
+ for (woken, result) in &report.resumed {
+ println!("{} (despertado)", describe(*woken, result));
+ }
+}
+
+/// Política de reemplazo de caché a partir de su nombre en la CLI
+fn parse_cache_policy(policy: &str) -> Result<CacheReplacement, String> {
+ match policy {
+ "lru" => Ok(CacheReplacement::Lru),
+ "fifo" => Ok(CacheReplacement::Fifo),
+ "random" => Ok(CacheReplacement::Random),
+ _ => Err("Política de caché inválida. Usa: lru, fifo o random".to_string()),
+ }
+}
+
+/// Aplica las opciones de memoria de `init` al kernel recién creado
+fn configure_memory(kernel: &mut KernelState, opts: &MemoryOptions) -> Result<(), String> {
+ let tlb_policy = match opts.tlb_policy.as_str() {
+ "lru" => TlbPolicy::Lru,
+ "fifo" => TlbPolicy::Fifo,
+ _ => return Err("Política de TLB inválida. Usa: lru o fifo".to_string()),
+ };
+ let kind = match opts.pt_kind.as_str() {
+ "multilevel" => PageTableKind::MultiLevel,
+ "inverted" => PageTableKind::Inverted,
+ "hashed" => PageTableKind::Hashed,
+ _ => return Err("Tipo de tabla inválido. Usa: multilevel, inverted o hashed".to_string()),
+ };
+ let alloc_policy = match opts.frame_alloc.as_str() {
+ "equal" => FrameAllocationPolicy::Equal,
+ "proportional" => FrameAllocationPolicy::Proportional,
+ _ => return Err("Asignación de marcos inválida. Usa: equal o proportional".to_string()),
+ };
+ let scope = match opts.replacement.as_str() {
+ "global" => ReplacementScope::Global,
+ "local" => ReplacementScope::Local,
+ _ => return Err("Reemplazo inválido. Usa: global o local".to_string()),
+ };
+ let swap_kind = match opts.swap_sched.as_str() {
+ "fcfs" => DiskSchedulerKind::Fcfs,
+ "sstf" => DiskSchedulerKind::Sstf,
+ "scan" => DiskSchedulerKind::Scan,
+ _ => return Err("Planificador de swap inválido. Usa: fcfs, sstf o scan".to_string()),
+ };
+ let algorithm = match opts.paging_algo.as_str() {
+ "fifo" => ReplacementAlgorithm::Fifo,
+ "lru" => ReplacementAlgorithm::Lru,
+ "ws" => ReplacementAlgorithm::WorkingSet(opts.ws_window),
+ _ => return Err("Algoritmo de paginación inválido. Usa: fifo, lru o ws".to_string()),
+ };
+
+ kernel.configure_heap(opts.heap_size, opts.heap_min_block, opts.dma_size)?;
+ if opts.cache_size > 0 {
+ let replacement = parse_cache_policy(&opts.cache_policy)?;
+ let l1 = CacheConfig { size: opts.cache_size, line_size: opts.cache_line, associativity: opts.cache_assoc, replacement };
+ let l2 = (opts.l2_size > 0).then_some(CacheConfig { size: opts.l2_size, associativity: opts.l2_assoc, ..l1 });
+ kernel.configure_cache(l1, l2)?;
+ }
+ kernel.configure_addressing(AddressingConfig::new(opts.page_size, opts.vaddr_bits, opts.pt_levels, kind)?);
+ kernel.configure_swap(SwapConfig {
+ slots: opts.swap_slots,
+ start_cylinder: opts.swap_start,
+ scheduler: swap_kind,
+ seek_ns: opts.seek_ns,
+ transfer_ns: opts.transfer_ns,
+ ..SwapConfig::default()
+ });
+ kernel.configure_frame_allocation(alloc_policy, scope, PffConfig {
+ enabled: opts.pff,
+ lower: opts.pff_lower,
+ upper: opts.pff_upper,
+ window: opts.mem_window,
+ }, ThrashingConfig {
+ enabled: opts.thrashing,
+ window: opts.mem_window,
+ fault_rate: opts.thrash_fault_rate,
+ cpu_utilization: opts.thrash_cpu,
+ });
+ kernel.configure_tlb(opts.tlb_size, tlb_policy, opts.tlb_flush, AccessLatencies {
+ tlb_ns: opts.tlb_ns,
+ memory_ns: opts.mem_ns,
+ page_fault_ns: opts.fault_ns,
+ });
+ kernel.configure_demand_paging(DemandPagingConfig {
+ algorithm,
+ accesses_per_tick: opts.refs_per_tick,
+ fault_service_ticks: opts.fault_ticks,
+ });
+ Ok(())
+}
+
+fn main() -> Result<()> {
+ env_logger::init();
+ let cli = Cli::parse();
+
+ match cli.command {
+ Commands::Reset => {
+ if fs::remove_file(KERNEL_STATE_FILE).is_ok() {
+ println!("[OK] Estado del kernel reseteado. Puedes iniciar uno nuevo con `init`.");
+ } else {
+ println!("[INFO] No había un estado del kernel para resetear.");
+ }
+ return Ok(()); // Salir después de resetear
+ }
+
+ Commands::Init { scheduler, quantum, frames, memory } => {
+ let sched_type = match scheduler.as_str() {
+ "rr" => SchedulerType::RoundRobin(quantum),
+ "sjf" => SchedulerType::SJF,
+ "fifo" => SchedulerType::FIFO,
+ "priority" => SchedulerType::Priority,
+ _ => {
+ eprintln!("[ERROR] Scheduler inválido. Usa: rr, sjf, fifo o priority");
+ return Ok(());
+ }
+ };
+ let mut kernel = KernelState::new(sched_type, frames);
+ if let Err(e) = configure_memory(&mut kernel, &memory) {
+ eprintln!("[ERROR] {}", e);
+ return Ok(());
+ }
+ save_kernel(&kernel)?;
+ println!("[OK] Kernel inicializado:");
+ println!(" Scheduler: {}", scheduler);
+ if scheduler == "rr" {
+ println!(" Quantum: {}", quantum);
+ }
+ println!(" Marcos de memoria: {}", frames);
+ println!(" Tamaño de página: {} bytes ({} bits de dirección, tabla {})", memory.page_size, memory.vaddr_bits, memory.pt_kind);
+ println!(" Asignación de marcos: {} (reemplazo {})", memory.frame_alloc, memory.replacement);
+ if memory.swap_slots > 0 {
+ println!(" Swap: {} slots desde el cilindro {} ({})", memory.swap_slots, memory.swap_start, memory.swap_sched);
+ }
+ if memory.tlb_size > 0 {
+ println!(" TLB: {} entradas ({}{})", memory.tlb_size, memory.tlb_policy, if memory.tlb_flush { ", flush en cambio de contexto" } else { "" });
+ }
+ }
+
+ Commands::New { burst, mem, refs, locality, pages, phase, seed, priority, max } => {
+ let mut claims = Vec::new();
+ for claim in &max {
+ match claim.split_once('=').map(|(name, n)| (name, n.parse::<u32>())) {
+ Some((name, Ok(count))) => claims.push((name.to_string(), count)),
+ _ => {
+ eprintln!("[ERROR] Máximo inválido '{}'. Usa: recurso=cantidad", claim);
+ return Ok(());
+ }
+ }
+ }
+ let pattern = match locality {
+ Some(size) => Some(MemoryPattern::locality(size, pages, phase, seed)),
+ None if !refs.is_empty() => Some(MemoryPattern::reference_string(refs)),
+ None => None,
+ };
+ if let Some(mut kernel) = load_kernel()? {
+ let claims = match kernel.claim_vector(&claims) {
+ Ok(vector) => (!claims.is_empty()).then_some(vector),
+ Err(e) => {
+ eprintln!("[ERROR] Error: {}", e);
+ return Ok(());
+ }
+ };
+ if let Some(pattern) = &pattern {
+ println!("[INFO] Patrón de memoria: {}", pattern.describe());
+ }
+ let pid = kernel.create_process_with_pattern(burst, mem, pattern);
+ if priority > 0 {
+ kernel.set_priority(pid, priority)?;
+ }
+ if let Some(claims) = claims {
+ match kernel.declare_max(pid, claims) {
+ Ok(()) => println!("[INFO] Máximo declarado: sus peticiones pasan por el algoritmo del banquero"),
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ }
+ println!("[OK] Proceso {} creado (burst={}, mem={})", pid, burst, mem);
+ save_kernel(&kernel)?;
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado. Ejecuta: kernel-sim init");
+ }
+ }
+
+ Commands::SetPriority { pid, priority } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.set_priority(pid, priority) {
+ Ok(()) => {
+ println!("[OK] Prioridad base del proceso {}: {}", pid, priority);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Ps => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.list_processes();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Tick { steps } => {
+ if let Some(mut kernel) = load_kernel()? {
+ kernel.tick(steps);
+ save_kernel(&kernel)?;
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Kill { pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.kill_process(pid) {
+ Ok(_) => {
+ println!("[OK] Proceso {} terminado", pid);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Suspend { pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.suspend_process(pid) {
+ Ok(_) => {
+ println!("[PAUSE] Proceso {} suspendido (bloqueado)", pid);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Resume { pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.resume_process(pid) {
+ Ok(_) => {
+ println!("[RESUME] Proceso {} reanudado", pid);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Run { steps } => {
+ if let Some(mut kernel) = load_kernel()? {
+ kernel.run(steps);
+ save_kernel(&kernel)?;
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Status => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Metrics => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.compute_metrics();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Produce { item, pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.produce(item.clone(), pid) {
+ Ok(report) => {
+ print_buffer_report(pid, &report);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[WARN] {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::Consume { pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.consume(pid) {
+ Ok(report) => {
+ print_buffer_report(pid, &report);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[WARN] {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::BufferStat => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.buffer_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::SemCreate { name, value } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.sem_create(&name, value) {
+ Ok(()) => {
+ println!("[OK] Semáforo '{}' creado con valor {}", name, value);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::SemWait { name, pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.sem_wait(&name, pid) {
+ Ok(true) => {
+ println!("[OK] Proceso {} continúa", pid);
+ save_kernel(&kernel)?;
+ }
+ Ok(false) => {
+ println!("[INFO] Proceso {} bloqueado en '{}'", pid, name);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::SemSignal { name } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.sem_signal(&name) {
+ Ok(woken) => {
+ if let Some(pid) = woken {
+ println!("[OK] Proceso {} despertado: vuelve a la cola de listos", pid);
+ } else {
+ println!("[OK] Signal en '{}' sin procesos en espera", name);
+ }
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::SemStatus => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.sem_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MutexCreate { name, recursive, protocol, ceiling } => {
+ let protocol = match protocol.as_str() {
+ "none" => MutexProtocol::None,
+ "inherit" => MutexProtocol::Inheritance,
+ "ceiling" => MutexProtocol::Ceiling(ceiling),
+ _ => {
+ eprintln!("[ERROR] Protocolo inválido. Usa: none, inherit o ceiling");
+ return Ok(());
+ }
+ };
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.mutex_create(&name, recursive, protocol) {
+ Ok(()) => {
+ println!("[OK] Mutex '{}' creado", name);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MutexLock { name, pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.mutex_lock(&name, pid) {
+ Ok(true) => {
+ println!("[OK] Proceso {} posee '{}'", pid, name);
+ save_kernel(&kernel)?;
+ }
+ Ok(false) => {
+ println!("[INFO] Proceso {} bloqueado esperando '{}'", pid, name);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MutexUnlock { name, pid } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.mutex_unlock(&name, pid) {
+ Ok(next) => {
+ match next {
+ Some(next) => println!("[OK] '{}' liberado: ahora lo posee el proceso {}", name, next),
+ None => println!("[OK] '{}' liberado por el proceso {}", name, pid),
+ }
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MutexStatus => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.mutex_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MboxCreate { name, capacity } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.mbox_create(&name, capacity) {
+ Ok(()) => {
+ println!("[OK] Buzón '{}' creado (capacidad {})", name, capacity);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MsgSend { payload, pid, to, mailbox, priority, nonblocking } => {
+ let address = match (to, mailbox) {
+ (Some(to), None) => Address::Process(to),
+ (None, Some(name)) => Address::Mailbox(name),
+ _ => {
+ eprintln!("[ERROR] Indica el destino con --to <pid> o --mailbox <nombre>");
+ return Ok(());
+ }
+ };
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.msg_send(pid, &address, &payload, priority, !nonblocking) {
+ Ok(outcome) => {
+ match outcome {
+ SendOutcome::Queued => println!("[OK] Mensaje encolado para {}", address),
+ SendOutcome::Delivered(receiver) => println!("[OK] Mensaje entregado al proceso {}: vuelve a la cola de listos", receiver),
+ SendOutcome::Blocked => println!("[INFO] {} lleno: proceso {} bloqueado en send", address, pid),
+ SendOutcome::WouldBlock => println!("[WARN] {} lleno: mensaje no enviado", address),
+ }
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MsgRecv { pid, mailbox, from, nonblocking } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.msg_recv(pid, mailbox.as_deref(), from, !nonblocking) {
+ Ok(outcome) => {
+ match outcome {
+ RecvOutcome::Received { message, unblocked } => {
+ println!("[OK] Proceso {} recibió de {} (prioridad {}): '{}'", pid, message.from, message.priority, message.payload);
+ for sender in unblocked {
+ println!("[OK] Emisor {} desbloqueado: vuelve a la cola de listos", sender);
+ }
+ }
+ RecvOutcome::Blocked => println!("[INFO] Sin mensajes: proceso {} bloqueado en recv", pid),
+ RecvOutcome::WouldBlock => println!("[WARN] Sin mensajes para el proceso {}", pid),
+ }
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::ResCreate { name, instances } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.res_create(&name, instances) {
+ Ok(()) => {
+ println!("[OK] Recurso '{}' creado con {} instancia(s)", name, instances);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::ResRequest { name, pid, count } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.res_request(pid, &name, count) {
+ Ok(outcome) => {
+ match outcome {
+ RequestOutcome::Granted => println!("[OK] Proceso {} recibió {} instancia(s) de '{}'", pid, count, name),
+ RequestOutcome::Blocked => println!("[INFO] Proceso {} bloqueado esperando {} instancia(s) de '{}'", pid, count, name),
+ RequestOutcome::Denied(reason) => println!("[INFO] Banquero: petición de P{} postergada, {}. El proceso queda bloqueado", pid, reason),
+ }
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::ResRelease { name, pid, count } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.res_release(pid, &name, count) {
+ Ok(served) => {
+ println!("[OK] Proceso {} liberó {} instancia(s) de '{}'", pid, count, name);
+ for waiter in served {
+ println!("[OK] Petición del proceso {} atendida: vuelve a la cola de listos", waiter);
+ }
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::ResStatus => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.res_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::BankerState => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.banker_state();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::DeadlockCheck { dot, recover } => {
+ let recovery = match recover.as_deref() {
+ None => None,
+ Some("kill") => Some(Recovery::Kill),
+ Some("preempt") => Some(Recovery::Preempt),
+ Some(_) => {
+ eprintln!("[ERROR] Recuperación inválida. Usa: kill, preempt");
+ return Ok(());
+ }
+ };
+ if let Some(mut kernel) = load_kernel()? {
+ // El grafo se exporta antes de recuperarse, con los interbloqueados marcados
+ match dot.as_deref() {
+ Some("-") => print!("{}", kernel.deadlock_dot()),
+ Some(path) => {
+ fs::write(path, kernel.deadlock_dot())?;
+ println!("[OK] Grafo de asignación exportado a {}", path);
+ }
+ None => {}
+ }
+ kernel.deadlock_check(recovery);
+ if recovery.is_some() {
+ save_kernel(&kernel)?;
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MboxStatus => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.mbox_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MemFifo { pid, pages, trace } => {
+ if let Some(mut kernel) = load_kernel()? {
+ println!("\n Simulando accesos con FIFO para proceso {}", pid);
+ if let Some(path) = trace {
+ if let Err(e) = kernel.run_trace(&path, pid, ReplacementAlgorithm::Fifo) {
+ eprintln!("[ERROR] Error: {}", e);
+ }
+ }
+ for page in pages {
+ let _ = kernel.access_memory_fifo(pid, page);
+ }
+ kernel.display_memory();
+ kernel.status(); // Mostrar estado general, que incluye métricas de memoria
+ save_kernel(&kernel)?;
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MemLru { pid, pages, trace } => {
+ if let Some(mut kernel) = load_kernel()? {
+ println!("\n Simulando accesos con LRU para proceso {}", pid);
+ if let Some(path) = trace {
+ if let Err(e) = kernel.run_trace(&path, pid, ReplacementAlgorithm::Lru) {
+ eprintln!("[ERROR] Error: {}", e);
+ }
+ }
+ for page in pages {
+ let _ = kernel.access_memory_lru(pid, page);
+ }
+ kernel.display_memory();
+ kernel.status(); // Mostrar estado general, que incluye métricas de memoria
+ save_kernel(&kernel)?;
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::MemWs { pid, window, pages, trace } => {
+ if let Some(mut kernel) = load_kernel()? {
+ println!("\n Simulando accesos con Working Set para proceso {} (ventana={})", pid, window);
+ if let Some(path) = trace {
+ if let Err(e) = kernel.run_trace(&path, pid, ReplacementAlgorithm::WorkingSet(window)) {
+ eprintln!("[ERROR] Error: {}", e);
+ }
+ }
+ for page in pages {
+ let _ = kernel.access_memory_ws(pid, page, window);
+ }
+ kernel.display_memory();
+ kernel.status();
+ save_kernel(&kernel)?;
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
 
-/// Carga el estado del kernel desde un archivo JSON.
-fn load_kernel() -> Result<Option<KernelState>> {
- if let Ok(data) = fs::read_to_string(KERNEL_STATE_FILE) {
- // Deserializa el estado
- let mut kernel: KernelState = serde_json::from_str(&data)?;
- 
- // 1. Reconstruye el scheduler, que no se guarda directamente
- let scheduler_type = kernel.scheduler_type.clone();
- kernel.scheduler = KernelState::create_scheduler(scheduler_type);
+ Commands::MemTranslate { pid, vaddr } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.translate_address(pid, vaddr) {
+ Ok(_) => save_kernel(&kernel)?,
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
 
- // 2. Repuebla el scheduler con los procesos que están en estado 'Ready'
- for process in kernel.get_ready_processes() {
- kernel.scheduler.push(process);
+ Commands::MemAlloc => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.frame_allocation_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
  }
 
- Ok(Some(kernel))
+ Commands::SegSetup { pid, code, data, heap, stack, paged } => {
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.seg_setup(pid, [code, data, heap, stack], paged) {
+ Ok(()) => {
+ println!("[OK] Tabla de segmentos creada para proceso {}", pid);
+ kernel.seg_status();
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
  } else {
- Ok(None)
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
  }
-}
 
-/// Guarda el estado del kernel en un archivo JSON.
-fn save_kernel(kernel: &KernelState) -> Result<()> {
- let data = serde_json::to_string_pretty(kernel)?;
- fs::write(KERNEL_STATE_FILE, data)?;
- Ok(())
-}
+ Commands::SegTranslate { pid, seg, offset, op } => {
+ let kind = match seg.as_str() {
+ "code" => SegmentKind::Code,
+ "data" => SegmentKind::Data,
+ "heap" => SegmentKind::Heap,
+ "stack" => SegmentKind::Stack,
+ _ => {
+ eprintln!("[ERROR] Segmento inválido. Usa: code, data, heap o stack");
+ return Ok(());
+ }
+ };
+ let access = match op.as_str() {
+ "r" => AccessKind::Read,
+ "w" => AccessKind::Write,
+ "x" => AccessKind::Execute,
+ _ => {
+ eprintln!("[ERROR] Acceso inválido. Usa: r, w o x");
+ return Ok(());
+ }
+ };
+ if let Some(mut kernel) = load_kernel()? {
+ match kernel.seg_translate(pid, kind, offset as usize, access) {
+ Ok(Some(address)) => {
+ println!("[OK] Dirección física: {:#x}", address);
+ save_kernel(&kernel)?;
+ }
+ Ok(None) => save_kernel(&kernel)?,
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
 
-fn main() -> Result<()> {
- env_logger::init();
- let cli = Cli::parse();
+ Commands::SegStatus => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.seg_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
 
- match cli.command {
- Commands::Reset => {
- if fs::remove_file(KERNEL_STATE_FILE).is_ok() {
- println!("[OK] Estado del kernel reseteado. Puedes iniciar uno nuevo con `init`.");
+ Commands::CacheStatus => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.cache_status();
  } else {
- println!("[INFO] No había un estado del kernel para resetear.");
+ eprintln!("[ERROR] Kernel no inicializado");
  }
- return Ok(()); // Salir después de resetear
  }
 
- Commands::Init { scheduler, quantum, frames } => {
- let sched_type = match scheduler.as_str() {
- "rr" => SchedulerType::RoundRobin(quantum),
- "sjf" => SchedulerType::SJF,
- "fifo" => SchedulerType::FIFO,
- _ => {
- eprintln!("[ERROR] Scheduler inválido. Usa: rr, sjf, o fifo");
+ Commands::CacheSim { addresses, size, line, assoc, policy, l2_size, l2_assoc, trace } => {
+ let caches = parse_cache_policy(&policy).and_then(|replacement| {
+ let l1 = CacheConfig { size, line_size: line, associativity: assoc, replacement };
+ let l2 = (l2_size > 0).then_some(CacheConfig { size: l2_size, associativity: l2_assoc, ..l1 });
+ CacheHierarchy::new(l1, l2)
+ });
+ match caches {
+ Ok(mut caches) => {
+ if let Some(path) = trace {
+ println!("\n Simulación de caché con la traza {}", path);
+ match TraceReader::open(&path) {
+ Ok(reader) => {
+ for record in reader.flatten() {
+ caches.access(record.address);
+ }
+ caches.display();
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
  return Ok(());
  }
+ println!("\n Simulación de caché ({} accesos)", addresses.len());
+ for address in addresses {
+ let served_by = match caches.access(address) {
+ 1 => "acierto L1",
+ 2 => "acierto L2",
+ _ => "fallo -> memoria",
  };
- let kernel = KernelState::new(sched_type, frames);
- save_kernel(&kernel)?;
- println!("[OK] Kernel inicializado:");
- println!(" Scheduler: {}", scheduler);
- if scheduler == "rr" {
- println!(" Quantum: {}", quantum);
+ println!(" {:#010x}: {}", address, served_by);
+ }
+ caches.display();
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
  }
- println!(" Marcos de memoria: {}", frames);
  }
 
- Commands::New { burst, mem } => {
+ Commands::Fork { pid } => {
  if let Some(mut kernel) = load_kernel()? {
- let pid = kernel.create_process(burst, mem);
- println!("[OK] Proceso {} creado (burst={}, mem={})", pid, burst, mem);
+ match kernel.fork_process(pid) {
+ Ok(child) => {
+ println!("[OK] fork: proceso {} -> hijo {}", pid, child);
  save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] {}", e),
+ }
  } else {
- eprintln!("[ERROR] Kernel no inicializado. Ejecuta: kernel-sim init");
+ eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Ps => {
- if let Some(kernel) = load_kernel()? {
- kernel.list_processes();
+ Commands::Exec { pid, refs } => {
+ if let Some(mut kernel) = load_kernel()? {
+ let pattern = (!refs.is_empty()).then(|| MemoryPattern::reference_string(refs));
+ match kernel.exec_process(pid, pattern) {
+ Ok(()) => {
+ println!("[OK] exec: proceso {} con espacio de direcciones nuevo", pid);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] {}", e),
+ }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Tick { steps } => {
+ Commands::MemWrite { pid, pages } => {
  if let Some(mut kernel) = load_kernel()? {
- kernel.tick(steps);
+ for page in pages {
+ if let Err(e) = kernel.write_memory(pid, page) {
+ eprintln!("[ERROR] {}", e);
+ }
+ }
+ kernel.display_memory();
  save_kernel(&kernel)?;
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Kill { pid } => {
+ Commands::CowStats => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.cow_status();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::ShmCreate { name, pages } => {
  if let Some(mut kernel) = load_kernel()? {
- match kernel.kill_process(pid) {
- Ok(_) => {
- println!("[OK] Proceso {} terminado", pid);
+ match kernel.shm_create(&name, pages) {
+ Ok(id) => {
+ println!("[OK] Segmento '{}' creado (id={}, {} páginas)", name, id, pages);
  save_kernel(&kernel)?;
  }
- Err(e) => eprintln!("[ERROR] Error: {}", e),
+ Err(e) => eprintln!("[ERROR] {}", e),
  }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Suspend { pid } => {
+ Commands::ShmAttach { pid, name, at } => {
  if let Some(mut kernel) = load_kernel()? {
- match kernel.suspend_process(pid) {
- Ok(_) => {
- println!("[PAUSE] Proceso {} suspendido (bloqueado)", pid);
+ match kernel.shm_attach(pid, &name, at) {
+ Ok(base) => {
+ println!("[OK] Proceso {} conectado a '{}' desde la página {:#x} ({})", pid, name, base, base);
  save_kernel(&kernel)?;
  }
- Err(e) => eprintln!("[ERROR] Error: {}", e),
+ Err(e) => eprintln!("[ERROR] {}", e),
  }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Resume { pid } => {
+ Commands::ShmDetach { pid, name } => {
  if let Some(mut kernel) = load_kernel()? {
- match kernel.resume_process(pid) {
- Ok(_) => {
- println!("[RESUME] Proceso {} reanudado", pid);
+ match kernel.shm_detach(pid, &name) {
+ Ok(()) => {
+ println!("[OK] Proceso {} desconectado de '{}'", pid, name);
  save_kernel(&kernel)?;
  }
- Err(e) => eprintln!("[ERROR] Error: {}", e),
+ Err(e) => eprintln!("[ERROR] {}", e),
  }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Run { steps } => {
+ Commands::ShmDestroy { name } => {
  if let Some(mut kernel) = load_kernel()? {
- kernel.run(steps);
+ match kernel.shm_destroy(&name) {
+ Ok(true) => println!("[OK] Segmento '{}' eliminado", name),
+ Ok(false) => println!("[OK] Segmento '{}' marcado: se eliminará al desconectarse el último proceso", name),
+ Err(e) => eprintln!("[ERROR] {}", e),
+ }
  save_kernel(&kernel)?;
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Status => {
+ Commands::ShmStatus => {
  if let Some(kernel) = load_kernel()? {
- kernel.status();
+ kernel.shm_status();
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Metrics => {
+ Commands::SwapStatus => {
  if let Some(kernel) = load_kernel()? {
- kernel.compute_metrics();
+ kernel.swap_status();
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Produce { item, pid } => {
+ Commands::MemDisplay => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.display_memory();
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::HeapAlloc { pid, size, fit, zone } => {
+ let zone = match zone.as_str() {
+ "normal" => ZoneKind::Normal,
+ "dma" => ZoneKind::Dma,
+ _ => {
+ eprintln!("[ERROR] Zona inválida. Usa: normal o dma");
+ return Ok(());
+ }
+ };
+ let strategy = match fit.as_deref() {
+ None => None,
+ Some("first") => Some(FitStrategy::First),
+ Some("best") => Some(FitStrategy::Best),
+ Some("worst") => Some(FitStrategy::Worst),
+ Some("next") => Some(FitStrategy::Next),
+ Some(_) => {
+ eprintln!("[ERROR] Estrategia inválida. Usa: first, best, worst o next");
+ return Ok(());
+ }
+ };
  if let Some(mut kernel) = load_kernel()? {
- match kernel.produce(item.clone(), pid) {
- Ok(_) => {
- println!("[OK] Item '{}' producido por proceso {}", item, pid);
+ let result = match strategy {
+ Some(strategy) => kernel.heap_alloc_contiguous(pid, size, strategy),
+ None => kernel.heap_alloc(pid, size, zone),
+ };
+ match result {
+ Ok(address) => {
+ println!("[OK] Heap: Asignado {} bytes para proceso {} en dirección {:#x}", size, pid, address);
  save_kernel(&kernel)?;
  }
- Err(e) => eprintln!("[WARN] {}", e),
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
  }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::Consume { pid } => {
+ Commands::HeapFree { address: None, pid: Some(pid), .. } => {
  if let Some(mut kernel) = load_kernel()? {
- match kernel.consume(pid) {
- Ok(item) => {
- println!("[OK] Proceso {} consumió: '{}'", pid, item);
+ match kernel.heap_free_pid(pid) {
+ Ok(freed) => {
+ println!("[OK] Heap: {} bloque(s) del proceso {} liberados", freed, pid);
  save_kernel(&kernel)?;
  }
- Err(e) => eprintln!("[WARN] {}", e),
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
  }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::BufferStat => {
+ Commands::HeapFree { address, contiguous, .. } => {
+ let address = address.unwrap_or_default();
+ if let Some(mut kernel) = load_kernel()? {
+ let result = if contiguous {
+ kernel.heap_free_contiguous(address)
+ } else {
+ kernel.heap_free(address)
+ };
+ match result {
+ Ok(_) => {
+ println!("[OK] Heap: Liberada memoria en dirección {:#x}", address);
+ save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
+ }
+ }
+
+ Commands::HeapLeaks => {
  if let Some(kernel) = load_kernel()? {
- kernel.buffer_status();
+ kernel.heap_leak_report();
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::MemFifo { pid, pages } => {
+ Commands::HeapRealloc { address, size } => {
  if let Some(mut kernel) = load_kernel()? {
- println!("\n Simulando accesos con FIFO para proceso {}", pid);
- for page in pages {
- let _ = kernel.access_memory_fifo(pid, page);
- }
- kernel.display_memory();
- kernel.status(); // Mostrar estado general, que incluye métricas de memoria
+ match kernel.heap_realloc(address, size) {
+ Ok(new_address) => {
+ println!("[OK] Heap: bloque {:#x} ahora de {} bytes en {:#x}", address, size, new_address);
  save_kernel(&kernel)?;
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::MemLru { pid, pages } => {
+ Commands::HeapCompact => {
  if let Some(mut kernel) = load_kernel()? {
- println!("\n Simulando accesos con LRU para proceso {}", pid);
- for page in pages {
- let _ = kernel.access_memory_lru(pid, page);
- }
- kernel.display_memory();
- kernel.status(); // Mostrar estado general, que incluye métricas de memoria
+ kernel.heap_compact();
  save_kernel(&kernel)?;
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::MemWs { pid, window, pages } => {
+ Commands::SlabCreate { name, size } => {
  if let Some(mut kernel) = load_kernel()? {
- println!("\n Simulando accesos con Working Set para proceso {} (ventana={})", pid, window);
- for page in pages {
- let _ = kernel.access_memory_ws(pid, page, window);
+ match kernel.slab_create(&name, size) {
+ Ok(()) => save_kernel(&kernel)?,
+ Err(e) => eprintln!("[ERROR] {}", e),
  }
- kernel.display_memory();
- kernel.status();
- save_kernel(&kernel)?;
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::MemDisplay => {
- if let Some(kernel) = load_kernel()? {
- kernel.display_memory();
+ Commands::SlabAlloc { name, count } => {
+ if let Some(mut kernel) = load_kernel()? {
+ for _ in 0..count {
+ match kernel.slab_alloc(&name) {
+ Ok(address) => println!("[OK] Objeto de '{}' en dirección {:#x}", name, address),
+ Err(e) => {
+ eprintln!("[ERROR] {}", e);
+ break;
+ }
+ }
+ }
+ save_kernel(&kernel)?;
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::HeapAlloc { pid, size } => {
+ Commands::SlabFree { name, address } => {
  if let Some(mut kernel) = load_kernel()? {
- match kernel.heap_alloc(pid, size) {
- Ok(address) => {
- println!("[OK] Heap: Asignado {} bytes para proceso {} en dirección {:#x}", size, pid, address);
+ match kernel.slab_free(&name, address as usize) {
+ Ok(()) => {
+ println!("[OK] Objeto {:#x} de '{}' liberado", address, name);
  save_kernel(&kernel)?;
  }
- Err(e) => eprintln!("[ERROR] Error: {}", e),
+ Err(e) => eprintln!("[ERROR] {}", e),
  }
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
  }
 
- Commands::HeapFree { address } => {
+ Commands::SlabShrink { name } => {
  if let Some(mut kernel) = load_kernel()? {
- match kernel.heap_free(address) {
- Ok(_) => {
- println!("[OK] Heap: Liberada memoria en dirección {:#x}", address);
+ match kernel.slab_shrink(&name) {
+ Ok(freed) => {
+ println!("[OK] {} slab(s) vacíos de '{}' devueltos al heap", freed, name);
  save_kernel(&kernel)?;
  }
- Err(e) => eprintln!("[ERROR] Error: {}", e),
+ Err(e) => eprintln!("[ERROR] {}", e),
+ }
+ } else {
+ eprintln!("[ERROR] Kernel no inicializado");
  }
+ }
+
+ Commands::SlabStatus => {
+ if let Some(kernel) = load_kernel()? {
+ kernel.slab_status();
  } else {
  eprintln!("[ERROR] Kernel no inicializado");
  }
@@ -467,12 +1969,112 @@ fn main() -> Result<()> {
  }
  }
 
- Commands::Philosophers { count, steps } => {
+ Commands::HeapBench { ops, size, min_block, max_request, seed } => {
+ println!("\n Benchmark: Buddy Allocator ({} operaciones)", ops);
+ match buddy::bench(size, min_block, max_request.max(1), ops, seed) {
+ Ok(result) => {
+ let stats = &result.final_stats;
+ println!("╔════════════════════════════════════════════════════╗");
+ println!("║ RESULTADO DEL BENCHMARK ║");
+ println!("╠════════════════════════════════════════════════════╣");
+ println!("║ Asignaciones: {:>12} ║", result.allocations);
+ println!("║ Liberaciones: {:>12} ║", result.frees);
+ println!("║ Fallidas: {:>12} ║", result.failures);
+ println!("║ Tiempo: {:>10.3} s ║", result.elapsed.as_secs_f64());
+ println!("║ Throughput: {:>12.0} ops/s ║", result.ops_per_second());
+ println!("║ Bloques vivos al final: {:>8} ║", stats.allocated_blocks);
+ println!("║ Fragmentación externa: {:>8.2}% ║", stats.external_fragmentation);
+ println!("╚════════════════════════════════════════════════════╝");
+ }
+ Err(e) => eprintln!("[ERROR] Error: {}", e),
+ }
+ }
+
+ Commands::Philosophers { count, steps, strategy, seed } => {
+ let strategy = match strategy.to_lowercase().as_str() {
+ "naive" => Strategy::Naive,
+ "asymmetric" => Strategy::Asymmetric,
+ "waiter" => Strategy::Waiter,
+ "hierarchy" => Strategy::Hierarchy,
+ "chandy-misra" => Strategy::ChandyMisra,
+ _ => {
+ eprintln!("[ERROR] Estrategia inválida. Usa: naive, asymmetric, waiter, hierarchy, chandy-misra");
+ return Ok(());
+ }
+ };
+ if count < 2 {
+ eprintln!("[ERROR] Hacen falta al menos 2 filósofos");
+ return Ok(());
+ }
  println!("\n Iniciando simulación: Cena de los Filósofos");
- let mut dining = DiningPhilosophers::new(count);
+ let mut dining = DiningPhilosophers::new(count, strategy, seed);
  dining.simulate(steps);
  }
 
+ Commands::ReadersWriters { readers, writers, steps, policy, seed } => {
+ let policy = match policy.to_lowercase().as_str() {
+ "reader" => Some(RwPolicy::ReaderPreference),
+ "writer" => Some(RwPolicy::WriterPreference),
+ "fair" => Some(RwPolicy::Fair),
+ "all" => None,
+ _ => {
+ eprintln!("[ERROR] Política inválida. Usa: reader, writer, fair, all");
+ return Ok(());
+ }
+ };
+ match policy {
+ Some(policy) => {
+ println!("\n Iniciando simulación: Lectores-Escritores");
+ let mut sim = ReadersWriters::new(readers, writers, policy, seed);
+ sim.simulate(steps);
+ sim.display_status();
+ sim.display_summary();
+ }
+ None => readers_writers::compare_policies(readers, writers, steps, seed),
+ }
+ }
+
+ Commands::Barber { chairs, steps, arrival, seed } => {
+ println!("\n Iniciando simulación: Barbero Dormilón");
+ let mut shop = SleepingBarber::new(chairs, arrival, seed);
+ shop.simulate(steps);
+ }
+
+ Commands::Smokers { steps, seed } => {
+ println!("\n Iniciando simulación: Fumadores de Cigarrillos");
+ let mut table = CigaretteSmokers::new(seed);
+ table.simulate(steps);
+ }
+
+ Commands::H2o { steps, arrival, seed } => {
+ println!("\n Iniciando simulación: Formación de Agua");
+ let mut factory = WaterFactory::new(arrival, seed);
+ factory.simulate(steps);
+ }
+
+ Commands::MonitorBuffer { producers, consumers, items, capacity, semantics, broadcast, seed } => {
+ let semantics = match semantics.to_lowercase().as_str() {
+ "hoare" => vec![MonitorSemantics::Hoare],
+ "mesa" => vec![MonitorSemantics::Mesa],
+ "both" => vec![MonitorSemantics::Hoare, MonitorSemantics::Mesa],
+ _ => {
+ eprintln!("[ERROR] Semántica inválida. Usa: hoare, mesa, both");
+ return Ok(());
+ }
+ };
+ if producers == 0 || consumers == 0 || capacity == 0 {
+ eprintln!("[ERROR] Se necesita al menos un productor, un consumidor y capacidad > 0");
+ return Ok(());
+ }
+
+ let workload = BufferWorkload { producers, consumers, items, capacity, seed };
+ let mut runs = vec![monitor::run_workload(workload, BufferVariant::Semaphores, false)];
+ for semantics in semantics {
+ runs.push(monitor::run_workload(workload, BufferVariant::Monitor(semantics), broadcast));
+ }
+ monitor::display_comparison(&workload, &runs);
+ }
+
  Commands::DiskFcfs { start, cylinders } => {
  println!("\n Simulación de Disco - FCFS");
  let mut fcfs = FcfsScheduler::new();
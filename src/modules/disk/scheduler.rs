@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
 
 /// Solicitud de acceso a disco
 #[derive(Debug, Clone)]
@@ -16,7 +17,27 @@ pub trait DiskScheduler {
  fn name(&self) -> &str;
 }
 
+/// Algoritmo de disco seleccionable (serializable, p. ej. para el área de swap)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiskSchedulerKind {
+ #[default]
+ Fcfs,
+ Sstf,
+ Scan,
+}
+
+impl DiskSchedulerKind {
+ pub fn build(&self) -> Box<dyn DiskScheduler> {
+ match self {
+ DiskSchedulerKind::Fcfs => Box::new(FcfsScheduler::new()),
+ DiskSchedulerKind::Sstf => Box::new(SstfScheduler::new()),
+ DiskSchedulerKind::Scan => Box::new(ScanScheduler::new(ScanDirection::Up)),
+ }
+ }
+}
+
 /// FCFS (First Come First Served) - Orden de llegada
+#[derive(Default)]
 pub struct FcfsScheduler {
  queue: VecDeque<DiskRequest>,
 }
@@ -48,6 +69,7 @@ impl DiskScheduler for FcfsScheduler {
 }
 
 /// SSTF (Shortest Seek Time First) - Más cercano primero
+#[derive(Default)]
 pub struct SstfScheduler {
  requests: Vec<DiskRequest>,
 }
@@ -75,11 +97,7 @@ impl DiskScheduler for SstfScheduler {
  let mut closest_idx = 0;
 
  for (idx, req) in self.requests.iter().enumerate() {
- let distance = if req.cylinder > current_position {
- req.cylinder - current_position
- } else {
- current_position - req.cylinder
- };
+ let distance = req.cylinder.abs_diff(current_position);
 
  if distance < min_distance {
  min_distance = distance;
@@ -205,11 +223,7 @@ impl DiskSimulator {
 
  /// Servir una solicitud
  fn serve_request(&mut self, request: DiskRequest) {
- let movement = if request.cylinder > self.current_position {
- request.cylinder - self.current_position
- } else {
- self.current_position - request.cylinder
- };
+ let movement = request.cylinder.abs_diff(self.current_position);
 
  println!(" Cabezal: {:3} -> {:3} (movimiento: {:3}) [PID {}]",
  self.current_position, request.cylinder, movement, request.pid);
@@ -0,0 +1,208 @@
+//! Barbero dormilón con semáforos: `customers` despierta al barbero y `barber`
+//! llama al siguiente cliente. Cada acción es atómica dentro del tick, así que
+//! el contador de sillas no necesita mutex
+
+use super::sync::Semaphore;
+use crate::rng::XorShift;
+
+/// Ticks que dura un corte
+pub const CUT_TICKS: u32 = 3;
+/// El barbero es el pid 0; los clientes van del 1 en adelante
+const BARBER: u32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BarberState {
+ Sleeping, // Bloqueado en `customers`
+ Woken, // Despertado: ya consumió la señal de un cliente
+ Cutting { customer: u32, left: u32 },
+}
+
+/// Simulación del barbero dormilón por ticks
+pub struct SleepingBarber {
+ customers: Semaphore, // Clientes sentados esperando
+ barber: Semaphore, // El barbero llama al siguiente
+ chairs: usize,
+ waiting: usize,
+ state: BarberState,
+ arrival_rate: usize, // Probabilidad (%) de que llegue un cliente en cada tick
+ arrived_at: Vec<u64>, // Tick de llegada de cada cliente (índice pid - 1)
+ rng: XorShift,
+ tick: u64,
+ served: u32,
+ turned_away: u32,
+ total_wait: u64,
+ max_wait: u64,
+ busy_ticks: u64,
+ max_queue: usize,
+}
+
+impl SleepingBarber {
+ pub fn new(chairs: usize, arrival_rate: usize, seed: u64) -> Self {
+ let mut customers = Semaphore::new(0, "customers");
+ // El barbero empieza durmiendo en la cola de `customers`
+ customers.wait(BARBER);
+ Self {
+ customers,
+ barber: Semaphore::new(0, "barber"),
+ chairs,
+ waiting: 0,
+ state: BarberState::Sleeping,
+ arrival_rate: arrival_rate.min(100),
+ arrived_at: Vec::new(),
+ rng: XorShift::new(seed),
+ tick: 0,
+ served: 0,
+ turned_away: 0,
+ total_wait: 0,
+ max_wait: 0,
+ busy_ticks: 0,
+ max_queue: 0,
+ }
+ }
+
+ /// Avanzar un tick: quizá llega un cliente y luego actúa el barbero
+ pub fn step(&mut self) {
+ self.tick += 1;
+ if self.rng.next_range(100) < self.arrival_rate {
+ self.arrive();
+ }
+
+ match self.state {
+ BarberState::Cutting { customer, left } if left > 1 => {
+ self.state = BarberState::Cutting { customer, left: left - 1 };
+ }
+ BarberState::Cutting { customer, .. } => {
+ self.served += 1;
+ println!("[Barbero] Termina con el cliente {}", customer);
+ self.next_customer();
+ }
+ BarberState::Woken => self.call_customer(),
+ BarberState::Sleeping => {}
+ }
+ if matches!(self.state, BarberState::Cutting { .. }) {
+ self.busy_ticks += 1;
+ }
+ }
+
+ fn arrive(&mut self) {
+ self.arrived_at.push(self.tick);
+ let pid = self.arrived_at.len() as u32;
+ if self.waiting >= self.chairs {
+ self.turned_away += 1;
+ println!("[Cliente {}] Sala llena, se va", pid);
+ return;
+ }
+ self.waiting += 1;
+ self.max_queue = self.max_queue.max(self.waiting);
+ println!("[Cliente {}] Se sienta a esperar ({}/{} sillas)", pid, self.waiting, self.chairs);
+
+ if self.customers.signal() == Some(BARBER) {
+ self.state = BarberState::Woken;
+ println!("[Barbero] Se despierta");
+ }
+ // Siempre se bloquea: el barbero solo hace signal tras consumir un cliente
+ self.barber.wait(pid);
+ }
+
+ /// Esperar al siguiente cliente o dormirse
+ fn next_customer(&mut self) {
+ if self.customers.wait(BARBER) {
+ self.call_customer();
+ } else {
+ self.state = BarberState::Sleeping;
+ println!("[Barbero] No hay clientes: se duerme");
+ }
+ }
+
+ /// Llamar al primero de la sala y empezar el corte
+ fn call_customer(&mut self) {
+ self.waiting -= 1;
+ let Some(customer) = self.barber.signal() else {
+ self.state = BarberState::Sleeping;
+ return;
+ };
+ let wait = self.tick - self.arrived_at[customer as usize - 1];
+ self.total_wait += wait;
+ self.max_wait = self.max_wait.max(wait);
+ self.state = BarberState::Cutting { customer, left: CUT_TICKS };
+ println!("[Barbero] Corta el pelo al cliente {} (esperó {} ticks)", customer, wait);
+ }
+
+ /// Simular varios ticks
+ pub fn simulate(&mut self, steps: usize) {
+ println!("\n╔═══════════════════════════════════════════════════╗");
+ println!("║ SIMULACIÓN: BARBERO DORMILÓN ║");
+ println!("║ {} sillas, llegada {}% por tick ║", self.chairs, self.arrival_rate);
+ println!("╚═══════════════════════════════════════════════════╝");
+
+ for _ in 0..steps {
+ println!("\n--- Tick {} ---", self.tick + 1);
+ self.step();
+ }
+
+ self.display_summary();
+ }
+
+ pub fn arrivals(&self) -> usize {
+ self.arrived_at.len()
+ }
+
+ pub fn served(&self) -> u32 {
+ self.served
+ }
+
+ pub fn turned_away(&self) -> u32 {
+ self.turned_away
+ }
+
+ pub fn waiting(&self) -> usize {
+ self.waiting
+ }
+
+ /// Resumen final
+ pub fn display_summary(&self) {
+ let in_chair = usize::from(matches!(self.state, BarberState::Cutting { .. }));
+ let started = self.served as u64 + in_chair as u64;
+ let avg_wait = if started > 0 { self.total_wait as f64 / started as f64 } else { 0.0 };
+ let utilization = if self.tick > 0 { self.busy_ticks as f64 * 100.0 / self.tick as f64 } else { 0.0 };
+
+ println!("\n╔═══════════════════════════════════════════╗");
+ println!("║ RESUMEN FINAL ║");
+ println!("╠═══════════════════════════════════════════╣");
+ println!("║ Ticks simulados: {:6} ║", self.tick);
+ println!("║ Clientes llegados: {:6} ║", self.arrivals());
+ println!("║ Atendidos: {:6} ║", self.served);
+ println!("║ En el sillón / esperando: {} / {} ║", in_chair, self.waiting);
+ println!("║ Se fueron (sala llena): {:6} ║", self.turned_away);
+ println!("╠═══════════════════════════════════════════╣");
+ println!("║ Espera media: {:.2} | máxima: {} ║", avg_wait, self.max_wait);
+ println!("║ Ocupación del barbero: {:.1}% ║", utilization);
+ println!("║ Cola máxima: {} de {} sillas ║", self.max_queue, self.chairs);
+
+ if self.turned_away == 0 {
+ println!("║ [OK] Ningún cliente se quedó sin atender ║");
+ } else {
+ println!("║ [WARN] {} clientes encontraron la sala llena ║", self.turned_away);
+ }
+
+ println!("╚═══════════════════════════════════════════╝");
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_barber_conserves_customers() {
+ let mut shop = SleepingBarber::new(2, 60, 42);
+ for _ in 0..200 {
+ shop.step();
+ let in_chair = usize::from(matches!(shop.state, BarberState::Cutting { .. }));
+ assert_eq!(shop.arrivals(), shop.served() as usize + shop.turned_away() as usize + shop.waiting() + in_chair);
+ assert!(shop.waiting() <= 2);
+ }
+ // Llegan más clientes de los que puede atender: algunos se van
+ assert!(shop.served() > 0 && shop.turned_away() > 0);
+ }
+}
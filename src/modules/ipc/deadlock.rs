@@ -0,0 +1,504 @@
+//! Recursos con varias instancias, detección y evitación de interbloqueos
+//! Detecta con el algoritmo de Coffman (Work/Finish sobre Allocation y Request)
+//! y busca ciclos en el grafo de asignación de recursos, exportable a DOT.
+//! Los procesos que declaran su máximo pasan por el algoritmo del banquero
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+/// Tipo de recurso con un número fijo de instancias
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceType {
+ pub name: String,
+ pub total: u32,
+}
+
+/// Resultado de una petición
+#[derive(Debug, Clone, PartialEq)]
+pub enum RequestOutcome {
+ Granted,
+ Blocked, // No hay instancias libres: el proceso espera
+ Denied(BankerDenial), // El banquero la posterga: el proceso espera
+}
+
+/// Por qué el banquero no concede una petición
+#[derive(Debug, Clone, PartialEq)]
+pub enum BankerDenial {
+ Unavailable { resource: String, available: u32, requested: u32 },
+ Unsafe { stuck: Vec<u32> }, // Procesos que no podrían terminar tras concederla
+}
+
+impl fmt::Display for BankerDenial {
+ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+ match self {
+ BankerDenial::Unavailable { resource, available, requested } => {
+ write!(f, "pide {} instancia(s) de '{}' y solo hay {} libre(s)", requested, resource, available)
+ }
+ BankerDenial::Unsafe { stuck } => {
+ let pids: Vec<String> = stuck.iter().map(|p| format!("P{}", p)).collect();
+ write!(f, "el estado resultante sería inseguro: {} no podrían terminar", pids.join(", "))
+ }
+ }
+ }
+}
+
+/// Cómo romper un interbloqueo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+ Kill, // Terminar a la víctima
+ Preempt, // Quitarle sus recursos y hacerla reintentar (rollback)
+}
+
+/// Nodo del grafo de asignación de recursos
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RagNode {
+ Process(u32),
+ Resource(usize),
+}
+
+/// Resultado de la detección
+#[derive(Debug, Clone, Default)]
+pub struct DeadlockReport {
+ pub deadlocked: Vec<u32>,
+ pub cycle: Option<Vec<RagNode>>, // Con una instancia por tipo, un ciclo implica interbloqueo
+ pub safe_order: Vec<u32>, // Orden en que el algoritmo pudo terminar a los demás
+}
+
+/// Asignaciones y peticiones pendientes de todos los procesos
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceManager {
+ types: Vec<ResourceType>,
+ allocation: BTreeMap<u32, Vec<u32>>, // pid -> instancias asignadas por tipo
+ requests: Vec<(u32, usize, u32)>, // (pid, tipo, cantidad) bloqueados, en orden de llegada
+ preemptions: BTreeMap<u32, u32>, // Veces que cada proceso fue víctima de expropiación
+ #[serde(default)]
+ max: BTreeMap<u32, Vec<u32>>, // Máximo declarado (banquero)
+}
+
+impl ResourceManager {
+ pub fn create_type(&mut self, name: &str, instances: u32) -> Result<usize, String> {
+ if self.type_index(name).is_some() {
+ return Err(format!("El recurso '{}' ya existe", name));
+ }
+ if instances == 0 {
+ return Err("Un recurso necesita al menos una instancia".to_string());
+ }
+ self.types.push(ResourceType { name: name.to_string(), total: instances });
+ // Las filas de asignación y de máximos crecen con el nuevo tipo
+ for row in self.allocation.values_mut().chain(self.max.values_mut()) {
+ row.push(0);
+ }
+ Ok(self.types.len() - 1)
+ }
+
+ pub fn type_index(&self, name: &str) -> Option<usize> {
+ self.types.iter().position(|t| t.name == name)
+ }
+
+ pub fn types(&self) -> &[ResourceType] {
+ &self.types
+ }
+
+ /// Instancias asignadas a un proceso (ceros si no tiene nada)
+ pub fn allocation_of(&self, pid: u32) -> Vec<u32> {
+ self.allocation.get(&pid).cloned().unwrap_or_else(|| vec![0; self.types.len()])
+ }
+
+ /// Petición pendiente de un proceso, como vector por tipo
+ pub fn request_of(&self, pid: u32) -> Vec<u32> {
+ let mut request = vec![0; self.types.len()];
+ for (_, ty, count) in self.requests.iter().filter(|(p, _, _)| *p == pid) {
+ request[*ty] += count;
+ }
+ request
+ }
+
+ /// Peticiones pendientes (pid, tipo, cantidad) en orden de llegada
+ pub fn pending_requests(&self) -> &[(u32, usize, u32)] {
+ &self.requests
+ }
+
+ pub fn is_waiting(&self, pid: u32) -> bool {
+ self.requests.iter().any(|(p, _, _)| *p == pid)
+ }
+
+ /// Procesos con recursos asignados o pedidos
+ pub fn processes(&self) -> Vec<u32> {
+ let mut pids: Vec<u32> = self.allocation.iter()
+ .filter(|(_, alloc)| alloc.iter().any(|n| *n > 0))
+ .map(|(pid, _)| *pid)
+ .chain(self.requests.iter().map(|(p, _, _)| *p))
+ .chain(self.max.keys().copied())
+ .collect();
+ pids.sort();
+ pids.dedup();
+ pids
+ }
+
+ pub fn available(&self) -> Vec<u32> {
+ let mut available: Vec<u32> = self.types.iter().map(|t| t.total).collect();
+ for alloc in self.allocation.values() {
+ for (free, used) in available.iter_mut().zip(alloc) {
+ *free -= used;
+ }
+ }
+ available
+ }
+
+ /// Declarar el máximo de instancias por tipo que un proceso podrá pedir
+ pub fn declare_max(&mut self, pid: u32, claims: Vec<u32>) -> Result<(), String> {
+ if claims.len() != self.types.len() {
+ return Err(format!("Se esperaban {} máximos (uno por tipo)", self.types.len()));
+ }
+ for (claim, resource) in claims.iter().zip(&self.types) {
+ if *claim > resource.total {
+ return Err(format!("Máximo {} de '{}' supera sus {} instancias", claim, resource.name, resource.total));
+ }
+ }
+ self.max.insert(pid, claims);
+ Ok(())
+ }
+
+ pub fn has_claims(&self, pid: u32) -> bool {
+ self.max.contains_key(&pid)
+ }
+
+ pub fn max_of(&self, pid: u32) -> Vec<u32> {
+ self.max.get(&pid).cloned().unwrap_or_else(|| vec![0; self.types.len()])
+ }
+
+ /// Need = Max - Allocation; sin máximo declarado se asume que no pedirá más
+ pub fn need_of(&self, pid: u32) -> Vec<u32> {
+ match self.max.get(&pid) {
+ Some(max) => max.iter().zip(self.allocation_of(pid)).map(|(m, a)| m.saturating_sub(a)).collect(),
+ None => vec![0; self.types.len()],
+ }
+ }
+
+ /// Algoritmo de seguridad: Ok(secuencia segura) o Err(procesos que no pueden terminar)
+ pub fn safety(&self) -> Result<Vec<u32>, Vec<u32>> {
+ let mut work = self.available();
+ let mut pending = self.processes();
+ let mut sequence = Vec::new();
+ while let Some(idx) = pending.iter().position(|pid| {
+ self.need_of(*pid).iter().zip(&work).all(|(need, free)| need <= free)
+ }) {
+ let pid = pending.remove(idx);
+ for (free, held) in work.iter_mut().zip(self.allocation_of(pid)) {
+ *free += held;
+ }
+ sequence.push(pid);
+ }
+ if pending.is_empty() { Ok(sequence) } else { Err(pending) }
+ }
+
+ /// ¿Se puede conceder ahora? Hay instancias libres y el estado resultante es seguro
+ pub fn banker_check(&self, pid: u32, ty: usize, count: u32) -> Result<(), BankerDenial> {
+ let available = self.available()[ty];
+ if count > available {
+ return Err(BankerDenial::Unavailable { resource: self.types[ty].name.clone(), available, requested: count });
+ }
+ let mut trial = self.clone();
+ trial.grant(pid, ty, count);
+ trial.safety().map(|_| ()).map_err(|stuck| BankerDenial::Unsafe { stuck })
+ }
+
+ /// Validar una petición sin aplicarla
+ pub fn check_request(&self, pid: u32, ty: usize, count: u32) -> Result<(), String> {
+ let resource = self.types.get(ty).ok_or_else(|| format!("Tipo de recurso {} inexistente", ty))?;
+ if count == 0 {
+ return Err("La cantidad debe ser mayor que 0".to_string());
+ }
+ if self.is_waiting(pid) {
+ return Err(format!("Proceso {} ya espera un recurso", pid));
+ }
+ let held = self.allocation_of(pid)[ty];
+ if held + count > resource.total {
+ return Err(format!("'{}' tiene {} instancias y el proceso {} ya posee {}", resource.name, resource.total, pid, held));
+ }
+ if let Some(max) = self.max.get(&pid) {
+ if held + count > max[ty] {
+ return Err(format!("Proceso {} declaró un máximo de {} para '{}' y ya posee {}", pid, max[ty], resource.name, held));
+ }
+ }
+ Ok(())
+ }
+
+ /// Pedir instancias; si no alcanzan (o el banquero la posterga), la petición queda pendiente
+ pub fn request(&mut self, pid: u32, ty: usize, count: u32) -> Result<RequestOutcome, String> {
+ self.check_request(pid, ty, count)?;
+ if self.has_claims(pid) {
+ return Ok(match self.banker_check(pid, ty, count) {
+ Ok(()) => {
+ self.grant(pid, ty, count);
+ RequestOutcome::Granted
+ }
+ Err(denial) => {
+ self.requests.push((pid, ty, count));
+ RequestOutcome::Denied(denial)
+ }
+ });
+ }
+ if self.available()[ty] >= count {
+ self.grant(pid, ty, count);
+ Ok(RequestOutcome::Granted)
+ } else {
+ self.requests.push((pid, ty, count));
+ Ok(RequestOutcome::Blocked)
+ }
+ }
+
+ /// Asignar sin comprobar disponibilidad
+ pub fn grant(&mut self, pid: u32, ty: usize, count: u32) {
+ let types = self.types.len();
+ self.allocation.entry(pid).or_insert_with(|| vec![0; types])[ty] += count;
+ }
+
+ /// Devolver instancias; atiende las peticiones pendientes que ahora caben (en orden)
+ pub fn release(&mut self, pid: u32, ty: usize, count: u32) -> Result<Vec<u32>, String> {
+ let name = &self.types.get(ty).ok_or_else(|| format!("Tipo de recurso {} inexistente", ty))?.name;
+ let held = self.allocation_of(pid)[ty];
+ if count == 0 || count > held {
+ return Err(format!("Proceso {} posee {} instancia(s) de '{}', no puede liberar {}", pid, held, name, count));
+ }
+ if let Some(alloc) = self.allocation.get_mut(&pid) {
+ alloc[ty] -= count;
+ }
+ Ok(self.serve_pending())
+ }
+
+ /// Liberar todo lo de un proceso y descartar su petición (terminó o fue expropiado)
+ pub fn release_all(&mut self, pid: u32) -> Vec<u32> {
+ self.allocation.remove(&pid);
+ self.requests.retain(|(p, _, _)| *p != pid);
+ self.serve_pending()
+ }
+
+ /// Olvidar a un proceso que terminó (también su máximo declarado)
+ pub fn remove_process(&mut self, pid: u32) -> Vec<u32> {
+ self.max.remove(&pid);
+ self.preemptions.remove(&pid);
+ self.release_all(pid)
+ }
+
+ /// Expropiar todo a una víctima; cuenta para no elegir siempre a la misma
+ pub fn preempt(&mut self, pid: u32) -> Vec<u32> {
+ *self.preemptions.entry(pid).or_insert(0) += 1;
+ self.release_all(pid)
+ }
+
+ pub fn preemptions(&self, pid: u32) -> u32 {
+ self.preemptions.get(&pid).copied().unwrap_or(0)
+ }
+
+ /// Conceder las peticiones pendientes que caben; devuelve los procesos desbloqueados
+ fn serve_pending(&mut self) -> Vec<u32> {
+ let mut served = Vec::new();
+ let mut idx = 0;
+ while idx < self.requests.len() {
+ let (pid, ty, count) = self.requests[idx];
+ let grantable = if self.has_claims(pid) {
+ self.banker_check(pid, ty, count).is_ok()
+ } else {
+ self.available()[ty] >= count
+ };
+ if grantable {
+ self.requests.remove(idx);
+ self.grant(pid, ty, count);
+ served.push(pid);
+ } else {
+ idx += 1;
+ }
+ }
+ served
+ }
+
+ /// Algoritmo de detección: termina a quien pueda con lo disponible y libera lo suyo
+ pub fn detect(&self) -> DeadlockReport {
+ let mut work = self.available();
+ let mut pending: Vec<u32> = self.processes();
+ let mut safe_order = Vec::new();
+
+ loop {
+ let next = pending.iter().position(|pid| {
+ self.request_of(*pid).iter().zip(&work).all(|(need, free)| need <= free)
+ });
+ let Some(idx) = next else {
+ break;
+ };
+ let pid = pending.remove(idx);
+ for (free, held) in work.iter_mut().zip(self.allocation_of(pid)) {
+ *free += held;
+ }
+ safe_order.push(pid);
+ }
+
+ DeadlockReport { deadlocked: pending, cycle: self.find_cycle(), safe_order }
+ }
+
+ /// Aristas del grafo: petición P -> R y asignación R -> P, con su cantidad
+ pub fn edges(&self) -> Vec<(RagNode, RagNode, u32)> {
+ let mut edges = Vec::new();
+ for (pid, ty, count) in &self.requests {
+ edges.push((RagNode::Process(*pid), RagNode::Resource(*ty), *count));
+ }
+ for (pid, alloc) in &self.allocation {
+ for (ty, count) in alloc.iter().enumerate().filter(|(_, n)| **n > 0) {
+ edges.push((RagNode::Resource(ty), RagNode::Process(*pid), *count));
+ }
+ }
+ edges
+ }
+
+ /// Buscar un ciclo en el grafo de asignación (DFS)
+ pub fn find_cycle(&self) -> Option<Vec<RagNode>> {
+ let edges = self.edges();
+ let successors = |node: &RagNode| -> Vec<RagNode> {
+ edges.iter().filter(|(from, _, _)| from == node).map(|(_, to, _)| to.clone()).collect()
+ };
+
+ let mut done: HashSet<RagNode> = HashSet::new();
+ for start in self.processes().into_iter().map(RagNode::Process) {
+ if done.contains(&start) {
+ continue;
+ }
+ // Pila de (nodo, sucesores pendientes) y camino actual
+ let mut path: Vec<RagNode> = vec![start.clone()];
+ let mut stack: Vec<Vec<RagNode>> = vec![successors(&start)];
+ while let Some(children) = stack.last_mut() {
+ match children.pop() {
+ Some(child) => {
+ if let Some(pos) = path.iter().position(|n| *n == child) {
+ // P -> R -> P solo dice que pide más instancias de un tipo que ya usa
+ if path.len() - pos > 2 {
+ return Some(path[pos..].to_vec());
+ }
+ continue;
+ }
+ if !done.contains(&child) {
+ stack.push(successors(&child));
+ path.push(child);
+ }
+ }
+ None => {
+ stack.pop();
+ if let Some(node) = path.pop() {
+ done.insert(node);
+ }
+ }
+ }
+ }
+ }
+ None
+ }
+
+ /// ¿Todos los tipos tienen una sola instancia? (entonces ciclo = interbloqueo)
+ pub fn single_instance(&self) -> bool {
+ self.types.iter().all(|t| t.total == 1)
+ }
+
+ pub fn node_label(&self, node: &RagNode) -> String {
+ match node {
+ RagNode::Process(pid) => format!("P{}", pid),
+ RagNode::Resource(ty) => self.types.get(*ty).map(|t| t.name.clone()).unwrap_or_default(),
+ }
+ }
+
+ /// Grafo de asignación en formato DOT; los procesos interbloqueados van en rojo
+ pub fn to_dot(&self, deadlocked: &[u32]) -> String {
+ let mut dot = String::from("digraph RAG {\n rankdir=LR;\n");
+ for pid in self.processes() {
+ let style = if deadlocked.contains(&pid) { ", style=filled, fillcolor=\"#f4a6a6\"" } else { "" };
+ dot.push_str(&format!(" \"P{}\" [shape=circle{}];\n", pid, style));
+ }
+ for (ty, resource) in self.types.iter().enumerate() {
+ dot.push_str(&format!(" \"R{}\" [shape=box, label=\"{} ({}/{})\"];\n",
+ ty, resource.name, self.available()[ty], resource.total));
+ }
+ for (from, to, count) in self.edges() {
+ let id = |node: &RagNode| match node {
+ RagNode::Process(pid) => format!("P{}", pid),
+ RagNode::Resource(ty) => format!("R{}", ty),
+ };
+ let style = if matches!(from, RagNode::Process(_)) { ", style=dashed" } else { "" };
+ dot.push_str(&format!(" \"{}\" -> \"{}\" [label=\"{}\"{}];\n", id(&from), id(&to), count, style));
+ }
+ dot.push_str("}\n");
+ dot
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_single_instance_cycle_is_deadlock() {
+ let mut rm = ResourceManager::default();
+ let a = rm.create_type("A", 1).unwrap();
+ let b = rm.create_type("B", 1).unwrap();
+ rm.request(1, a, 1).unwrap();
+ rm.request(2, b, 1).unwrap();
+ assert_eq!(rm.request(1, b, 1).unwrap(), RequestOutcome::Blocked);
+ assert_eq!(rm.request(2, a, 1).unwrap(), RequestOutcome::Blocked);
+
+ let report = rm.detect();
+ assert_eq!(report.deadlocked, vec![1, 2]);
+ assert_eq!(report.cycle.map(|c| c.len()), Some(4));
+ assert!(rm.to_dot(&report.deadlocked).contains("\"P1\" -> \"R1\" [label=\"1\", style=dashed]"));
+
+ // Expropiar a 1 desbloquea a 2
+ assert_eq!(rm.release_all(1), vec![2]);
+ assert!(rm.detect().deadlocked.is_empty());
+ }
+
+ #[test]
+ fn test_multi_instance_cycle_without_deadlock() {
+ // Ejemplo clásico: hay ciclo pero P2 y P4 pueden terminar y liberar instancias
+ let mut rm = ResourceManager::default();
+ let r1 = rm.create_type("R1", 2).unwrap();
+ let r2 = rm.create_type("R2", 2).unwrap();
+ rm.request(2, r1, 1).unwrap();
+ rm.request(3, r1, 1).unwrap();
+ rm.request(1, r2, 1).unwrap();
+ rm.request(4, r2, 1).unwrap();
+ rm.request(1, r1, 1).unwrap();
+ rm.request(3, r2, 1).unwrap();
+
+ let report = rm.detect();
+ assert!(report.cycle.is_some());
+ assert!(report.deadlocked.is_empty());
+ assert_eq!(report.safe_order.len(), 4);
+ }
+
+ #[test]
+ fn test_banker_textbook_example() {
+ let mut rm = ResourceManager::default();
+ for (name, total) in [("A", 10), ("B", 5), ("C", 7)] {
+ rm.create_type(name, total).unwrap();
+ }
+ let max = [[7, 5, 3], [3, 2, 2], [9, 0, 2], [2, 2, 2], [4, 3, 3]];
+ let alloc = [[0, 1, 0], [2, 0, 0], [3, 0, 2], [2, 1, 1], [0, 0, 2]];
+ for (pid, (max, alloc)) in max.iter().zip(alloc).enumerate() {
+ rm.declare_max(pid as u32, max.to_vec()).unwrap();
+ for (ty, count) in alloc.into_iter().enumerate().filter(|(_, n)| *n > 0) {
+ rm.grant(pid as u32, ty, count);
+ }
+ }
+ assert_eq!(rm.safety(), Ok(vec![1, 3, 0, 2, 4]));
+
+ // P1 pide (1, 0, 2): sigue siendo seguro
+ assert_eq!(rm.request(1, 0, 1).unwrap(), RequestOutcome::Granted);
+ assert_eq!(rm.request(1, 2, 2).unwrap(), RequestOutcome::Granted);
+ // P0 pide 2 de B: hay instancias, pero el estado quedaría inseguro
+ assert!(matches!(rm.request(0, 1, 2).unwrap(), RequestOutcome::Denied(BankerDenial::Unsafe { .. })));
+ // Pedir más que el máximo declarado es un error
+ assert!(rm.request(3, 1, 2).is_err());
+
+ // Un tipo creado después de declarar máximos tiene máximo 0 para todos
+ let d = rm.create_type("D", 1).unwrap();
+ assert_eq!(rm.max_of(0), vec![7, 5, 3, 0]);
+ assert!(rm.request(0, d, 1).is_err());
+ }
+}
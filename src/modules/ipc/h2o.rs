@@ -0,0 +1,202 @@
+//! Formación de agua con semáforos: los átomos esperan en `oxy_queue` o
+//! `hydro_queue` hasta que hay 2 H + 1 O, y el trío se reúne en una barrera
+//! reutilizable de 3 antes de enlazarse
+
+use super::sync::Semaphore;
+use crate::rng::XorShift;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+ Hydrogen,
+ Oxygen,
+}
+
+impl Element {
+ fn symbol(self) -> &'static str {
+ match self {
+ Element::Hydrogen => "H",
+ Element::Oxygen => "O",
+ }
+ }
+}
+
+/// Un átomo (su pid es su índice + 1)
+#[derive(Debug, Clone)]
+struct Atom {
+ element: Element,
+ arrived: u64,
+ bonded: Option<u64>, // Tick en que formó la molécula
+}
+
+/// Simulación de la fábrica de agua por ticks
+pub struct WaterFactory {
+ oxy_queue: Semaphore,
+ hydro_queue: Semaphore,
+ turnstile: Semaphore, // Barrera: los dos primeros del trío esperan al tercero
+ oxygen: usize, // Átomos esperando que aún no se han emparejado
+ hydrogen: usize,
+ released: Vec<u32>, // Liberados este tick: llegan a la barrera en el siguiente
+ at_barrier: Vec<u32>,
+ atoms: Vec<Atom>,
+ molecules: Vec<[u32; 3]>,
+ malformed: u32,
+ arrival_rate: usize, // Probabilidad (%) de que llegue un átomo en cada tick
+ rng: XorShift,
+ tick: u64,
+}
+
+impl WaterFactory {
+ pub fn new(arrival_rate: usize, seed: u64) -> Self {
+ Self {
+ oxy_queue: Semaphore::new(0, "oxy_queue"),
+ hydro_queue: Semaphore::new(0, "hydro_queue"),
+ turnstile: Semaphore::new(0, "barrier"),
+ oxygen: 0,
+ hydrogen: 0,
+ released: Vec::new(),
+ at_barrier: Vec::new(),
+ atoms: Vec::new(),
+ molecules: Vec::new(),
+ malformed: 0,
+ arrival_rate: arrival_rate.min(100),
+ rng: XorShift::new(seed),
+ tick: 0,
+ }
+ }
+
+ /// Avanzar un tick: los liberados llegan a la barrera y quizá aparece un átomo (2/3 de H)
+ pub fn step(&mut self) {
+ self.tick += 1;
+ for pid in std::mem::take(&mut self.released) {
+ self.reach_barrier(pid);
+ }
+ if self.rng.next_range(100) < self.arrival_rate {
+ let element = if self.rng.next_range(3) < 2 { Element::Hydrogen } else { Element::Oxygen };
+ self.arrive(element);
+ }
+ }
+
+ fn arrive(&mut self, element: Element) {
+ self.atoms.push(Atom { element, arrived: self.tick, bonded: None });
+ let pid = self.atoms.len() as u32;
+ println!("[Átomo {} ({})] Llega", pid, element.symbol());
+
+ match element {
+ Element::Hydrogen => self.hydrogen += 1,
+ Element::Oxygen => self.oxygen += 1,
+ }
+ // Quien completa el trío libera a los otros dos y a sí mismo
+ if self.hydrogen >= 2 && self.oxygen >= 1 {
+ self.hydrogen -= 2;
+ self.oxygen -= 1;
+ println!("[Átomo {}] Completa 2 H + 1 O: libera el trío", pid);
+ for _ in 0..2 {
+ let woken = self.hydro_queue.signal();
+ self.released.extend(woken);
+ }
+ let woken = self.oxy_queue.signal();
+ self.released.extend(woken);
+ }
+
+ let queue = match element {
+ Element::Hydrogen => &mut self.hydro_queue,
+ Element::Oxygen => &mut self.oxy_queue,
+ };
+ if queue.wait(pid) {
+ self.released.push(pid);
+ }
+ }
+
+ /// Barrera de 3: el último del trío abre el torniquete a los otros dos
+ fn reach_barrier(&mut self, pid: u32) {
+ self.at_barrier.push(pid);
+ if self.at_barrier.len() < 3 {
+ self.turnstile.wait(pid);
+ return;
+ }
+ for _ in 0..2 {
+ self.turnstile.signal();
+ }
+
+ let trio: Vec<u32> = self.at_barrier.drain(..).collect();
+ let hydrogens = trio.iter().filter(|p| self.atoms[**p as usize - 1].element == Element::Hydrogen).count();
+ if hydrogens != 2 {
+ self.malformed += 1;
+ }
+ for p in &trio {
+ self.atoms[*p as usize - 1].bonded = Some(self.tick);
+ }
+ self.molecules.push([trio[0], trio[1], trio[2]]);
+ println!("[Barrera] Molécula #{} de H2O con los átomos {:?}", self.molecules.len(), trio);
+ }
+
+ /// Simular varios ticks
+ pub fn simulate(&mut self, steps: usize) {
+ println!("\n╔═══════════════════════════════════════════════════╗");
+ println!("║ SIMULACIÓN: FORMACIÓN DE AGUA (H2O) ║");
+ println!("║ Llegada {}% por tick, 2/3 de hidrógeno ║", self.arrival_rate);
+ println!("╚═══════════════════════════════════════════════════╝");
+
+ for _ in 0..steps {
+ println!("\n--- Tick {} ---", self.tick + 1);
+ self.step();
+ }
+
+ self.display_summary();
+ }
+
+ pub fn molecules(&self) -> usize {
+ self.molecules.len()
+ }
+
+ /// Átomos de un elemento, cuántos se enlazaron y su espera media y máxima
+ fn element_stats(&self, element: Element) -> (usize, usize, f64, u64) {
+ let atoms: Vec<&Atom> = self.atoms.iter().filter(|a| a.element == element).collect();
+ let waits: Vec<u64> = atoms.iter().filter_map(|a| a.bonded.map(|t| t - a.arrived)).collect();
+ let avg = if waits.is_empty() { 0.0 } else { waits.iter().sum::<u64>() as f64 / waits.len() as f64 };
+ (atoms.len(), waits.len(), avg, waits.iter().copied().max().unwrap_or(0))
+ }
+
+ /// Resumen final
+ pub fn display_summary(&self) {
+ println!("\n╔═══════════════════════════════════════════╗");
+ println!("║ RESUMEN FINAL ║");
+ println!("╠═══════════════════════════════════════════╣");
+ println!("║ Ticks simulados: {:6} ║", self.tick);
+ println!("║ Moléculas formadas: {:6} ║", self.molecules());
+ println!("╠═══════════════════════════════════════════╣");
+ for element in [Element::Hydrogen, Element::Oxygen] {
+ let (arrived, bonded, avg, max) = self.element_stats(element);
+ println!("║ {}: {} llegaron, {} enlazados, espera media {:.2}, máx {} ║",
+ element.symbol(), arrived, bonded, avg, max);
+ }
+ println!("║ Esperando al final: {} H, {} O ║", self.hydrogen, self.oxygen);
+ println!("╠═══════════════════════════════════════════╣");
+
+ if self.malformed == 0 {
+ println!("║ [OK] Todas las moléculas tienen 2 H y 1 O ║");
+ } else {
+ println!("║ [ERROR] {} moléculas mal formadas ║", self.malformed);
+ }
+
+ println!("╚═══════════════════════════════════════════╝");
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_molecules_are_well_formed() {
+ let mut factory = WaterFactory::new(80, 42);
+ for _ in 0..300 {
+ factory.step();
+ }
+ assert!(factory.molecules() > 0);
+ assert_eq!(factory.malformed, 0);
+ // Nadie queda en la barrera más de un tick y los sobrantes no completan un trío
+ assert!(factory.at_barrier.is_empty());
+ assert!(factory.hydrogen < 2 || factory.oxygen == 0);
+ }
+}
@@ -0,0 +1,265 @@
+//! Paso de mensajes: buzones con capacidad acotada y prioridades
+//! Direccionamiento indirecto (buzón con nombre, cualquiera envía y recibe) o directo
+//! (cada proceso tiene su buzón `pid:N` y solo él recibe de ahí)
+
+use std::collections::VecDeque;
+use std::fmt;
+use serde::{Serialize, Deserialize};
+
+/// Capacidad del buzón propio de cada proceso
+pub const DIRECT_CAPACITY: usize = 4;
+
+/// Un mensaje; a igual prioridad se entrega en orden de llegada
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+ pub from: u32,
+ pub priority: u8, // Mayor número = se entrega antes
+ pub payload: String,
+ seq: u64,
+}
+
+impl Message {
+ pub fn new(from: u32, priority: u8, payload: &str) -> Self {
+ Self { from, priority, payload: payload.to_string(), seq: 0 }
+ }
+}
+
+/// Destino de un envío
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Address {
+ Mailbox(String), // Indirecto
+ Process(u32), // Directo: buzón propio del proceso
+}
+
+impl Address {
+ pub fn mailbox_name(&self) -> String {
+ match self {
+ Address::Mailbox(name) => name.clone(),
+ Address::Process(pid) => direct_mailbox(*pid),
+ }
+ }
+}
+
+impl fmt::Display for Address {
+ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+ match self {
+ Address::Mailbox(name) => write!(f, "buzón '{}'", name),
+ Address::Process(pid) => write!(f, "proceso {}", pid),
+ }
+ }
+}
+
+/// Nombre del buzón propio de un proceso
+pub fn direct_mailbox(pid: u32) -> String {
+ format!("pid:{}", pid)
+}
+
+/// Resultado de un send
+#[derive(Debug, Clone, PartialEq)]
+pub enum SendOutcome {
+ Queued,
+ Delivered(u32), // Entregado en mano a un receptor bloqueado, que se despierta
+ Blocked, // Buzón lleno: el emisor espera
+ WouldBlock, // Buzón lleno y envío no bloqueante
+}
+
+/// Resultado de un recv
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecvOutcome {
+ Received { message: Message, unblocked: Vec<u32> }, // Emisores que pudieron dejar su mensaje
+ Blocked,
+ WouldBlock,
+}
+
+/// Buzón: cola de mensajes por prioridad más las colas de procesos bloqueados
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mailbox {
+ name: String,
+ capacity: usize, // 0 = rendezvous: el emisor espera hasta que alguien reciba
+ owner: Option<u32>, // Solo en buzones directos
+ messages: Vec<Message>,
+ senders: VecDeque<(u32, Message)>, // Emisores bloqueados con su mensaje
+ receivers: VecDeque<(u32, Option<u32>)>, // Receptores bloqueados y emisor que esperan
+ next_seq: u64,
+ sent: u64,
+ received: u64,
+}
+
+impl Mailbox {
+ pub fn new(name: &str, capacity: usize, owner: Option<u32>) -> Self {
+ Self {
+ name: name.to_string(),
+ capacity,
+ owner,
+ messages: Vec::new(),
+ senders: VecDeque::new(),
+ receivers: VecDeque::new(),
+ next_seq: 0,
+ sent: 0,
+ received: 0,
+ }
+ }
+
+ /// Enviar; si hay un receptor esperando (y acepta a este emisor) se le entrega directamente
+ pub fn send(&mut self, mut message: Message, blocking: bool) -> SendOutcome {
+ message.seq = self.next_seq;
+ self.next_seq += 1;
+
+ let waiting = self.receivers.iter()
+ .position(|(_, filter)| filter.is_none_or(|from| from == message.from))
+ .and_then(|idx| self.receivers.remove(idx));
+ if let Some((receiver, _)) = waiting {
+ self.sent += 1;
+ self.received += 1;
+ println!("[Buzón {}] Mensaje de {} entregado al proceso {}: '{}'", self.name, message.from, receiver, message.payload);
+ return SendOutcome::Delivered(receiver);
+ }
+
+ if self.messages.len() < self.capacity {
+ println!("[Buzón {}] Mensaje de {} encolado (prioridad {})", self.name, message.from, message.priority);
+ self.messages.push(message);
+ self.sent += 1;
+ return SendOutcome::Queued;
+ }
+
+ if !blocking {
+ return SendOutcome::WouldBlock;
+ }
+ println!("[Buzón {}] Lleno: proceso {} bloqueado en send", self.name, message.from);
+ self.senders.push_back((message.from, message));
+ SendOutcome::Blocked
+ }
+
+ /// Recibir el mensaje más prioritario (opcionalmente solo de un emisor)
+ pub fn recv(&mut self, pid: u32, from: Option<u32>, blocking: bool) -> Result<RecvOutcome, String> {
+ if self.owner.is_some_and(|owner| owner != pid) {
+ return Err(format!("Solo el proceso {} recibe del buzón '{}'", self.owner.unwrap_or_default(), self.name));
+ }
+ if self.receivers.iter().any(|(p, _)| *p == pid) {
+ return Err(format!("Proceso {} ya espera en el buzón '{}'", pid, self.name));
+ }
+
+ let matches = |m: &Message| from.is_none_or(|f| f == m.from);
+ let best = (0..self.messages.len())
+ .filter(|i| matches(&self.messages[*i]))
+ .max_by_key(|i| (self.messages[*i].priority, std::cmp::Reverse(self.messages[*i].seq)));
+
+ let mut unblocked = Vec::new();
+ let message = match best {
+ Some(idx) => self.messages.remove(idx),
+ // Sin mensaje encolado: tomarlo de un emisor bloqueado (rendezvous o filtro por emisor)
+ None => match self.senders.iter().position(|(_, m)| matches(m)).and_then(|idx| self.senders.remove(idx)) {
+ Some((sender, message)) => {
+ self.sent += 1;
+ unblocked.push(sender);
+ message
+ }
+ None if blocking => {
+ println!("[Buzón {}] Vacío: proceso {} bloqueado en recv", self.name, pid);
+ self.receivers.push_back((pid, from));
+ return Ok(RecvOutcome::Blocked);
+ }
+ None => return Ok(RecvOutcome::WouldBlock),
+ },
+ };
+ self.received += 1;
+
+ // El hueco liberado deja pasar a los emisores bloqueados, en orden
+ while self.messages.len() < self.capacity {
+ let Some((sender, message)) = self.senders.pop_front() else {
+ break;
+ };
+ self.messages.push(message);
+ self.sent += 1;
+ unblocked.push(sender);
+ }
+ println!("[Buzón {}] Proceso {} recibió de {}: '{}'", self.name, pid, message.from, message.payload);
+ Ok(RecvOutcome::Received { message, unblocked })
+ }
+
+ /// Sacar a un proceso de las colas de bloqueados (su mensaje pendiente se descarta)
+ pub fn remove_waiter(&mut self, pid: u32) -> bool {
+ let before = self.senders.len() + self.receivers.len();
+ self.senders.retain(|(p, _)| *p != pid);
+ self.receivers.retain(|(p, _)| *p != pid);
+ self.senders.len() + self.receivers.len() < before
+ }
+
+ pub fn is_waiting(&self, pid: u32) -> bool {
+ self.senders.iter().any(|(p, _)| *p == pid) || self.receivers.iter().any(|(p, _)| *p == pid)
+ }
+
+ /// Emisores bloqueados (para despertarlos si el buzón desaparece)
+ pub fn blocked_senders(&self) -> Vec<u32> {
+ self.senders.iter().map(|(p, _)| *p).collect()
+ }
+
+ pub fn blocked_receivers(&self) -> Vec<u32> {
+ self.receivers.iter().map(|(p, _)| *p).collect()
+ }
+
+ /// Mensajes en orden de entrega
+ pub fn messages(&self) -> Vec<&Message> {
+ let mut messages: Vec<&Message> = self.messages.iter().collect();
+ messages.sort_by_key(|m| (std::cmp::Reverse(m.priority), m.seq));
+ messages
+ }
+
+ pub fn name(&self) -> &str {
+ &self.name
+ }
+
+ pub fn capacity(&self) -> usize {
+ self.capacity
+ }
+
+ pub fn owner(&self) -> Option<u32> {
+ self.owner
+ }
+
+ pub fn sent(&self) -> u64 {
+ self.sent
+ }
+
+ pub fn received(&self) -> u64 {
+ self.received
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_priority_order_and_blocked_senders() {
+ let mut mbox = Mailbox::new("m", 2, None);
+ assert_eq!(mbox.send(Message::new(1, 0, "a"), true), SendOutcome::Queued);
+ assert_eq!(mbox.send(Message::new(2, 5, "b"), true), SendOutcome::Queued);
+ assert_eq!(mbox.send(Message::new(3, 9, "c"), false), SendOutcome::WouldBlock);
+ assert_eq!(mbox.send(Message::new(3, 9, "c"), true), SendOutcome::Blocked);
+
+ // Sale primero el más prioritario y el hueco despierta al emisor bloqueado
+ let RecvOutcome::Received { message, unblocked } = mbox.recv(10, None, true).unwrap() else {
+ panic!("se esperaba un mensaje");
+ };
+ assert_eq!((message.payload.as_str(), unblocked), ("b", vec![3]));
+ let order: Vec<&str> = mbox.messages().iter().map(|m| m.payload.as_str()).collect();
+ assert_eq!(order, vec!["c", "a"]);
+ }
+
+ #[test]
+ fn test_rendezvous_and_direct_filter() {
+ let mut direct = Mailbox::new(&direct_mailbox(7), 0, Some(7));
+ assert!(direct.recv(8, None, true).is_err());
+ assert_eq!(direct.recv(7, Some(2), true).unwrap(), RecvOutcome::Blocked);
+
+ // Capacidad 0: el mensaje de 1 no coincide con el filtro y su emisor espera
+ assert_eq!(direct.send(Message::new(1, 0, "x"), true), SendOutcome::Blocked);
+ assert_eq!(direct.send(Message::new(2, 0, "y"), true), SendOutcome::Delivered(7));
+ let RecvOutcome::Received { message, unblocked } = direct.recv(7, None, false).unwrap() else {
+ panic!("se esperaba el mensaje de 1");
+ };
+ assert_eq!((message.from, unblocked), (1, vec![1]));
+ assert_eq!(direct.recv(7, None, false).unwrap(), RecvOutcome::WouldBlock);
+ }
+}
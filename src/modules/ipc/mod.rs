@@ -2,16 +2,29 @@
 
 pub mod sync;
 pub mod philosophers;
+pub mod readers_writers;
+pub mod barber;
+pub mod smokers;
+pub mod h2o;
+pub mod monitor;
+pub mod mailbox;
+pub mod deadlock;
 
 use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
 
+#[derive(Default, Serialize, Deserialize)]
 pub struct IpcRegistry {
  sems: HashMap<String, sync::Semaphore>,
+ #[serde(default)]
+ mutexes: HashMap<String, sync::Mutex>,
+ #[serde(default)]
+ mailboxes: HashMap<String, mailbox::Mailbox>,
 }
 
 impl IpcRegistry {
  pub fn new() -> Self {
- Self { sems: HashMap::new() }
+ Self { sems: HashMap::new(), mutexes: HashMap::new(), mailboxes: HashMap::new() }
  }
 
  pub fn create_semaphore(&mut self, name: String, initial_value: i32) {
@@ -21,4 +34,102 @@ impl IpcRegistry {
  pub fn get_semaphore(&mut self, name: &str) -> Option<&mut sync::Semaphore> {
  self.sems.get_mut(name)
  }
+
+ pub fn has_semaphore(&self, name: &str) -> bool {
+ self.sems.contains_key(name)
+ }
+
+ /// Semáforos ordenados por nombre
+ pub fn semaphores(&self) -> Vec<&sync::Semaphore> {
+ let mut sems: Vec<&sync::Semaphore> = self.sems.values().collect();
+ sems.sort_by(|a, b| a.name().cmp(b.name()));
+ sems
+ }
+
+ /// Semáforo en cuya cola espera un proceso
+ pub fn waiting_on(&self, pid: u32) -> Option<&str> {
+ self.sems.values()
+ .find(|s| s.is_waiting(pid))
+ .map(|s| s.name())
+ }
+
+ /// Sacar a un proceso de todas las colas (al terminar)
+ pub fn remove_waiter(&mut self, pid: u32) {
+ for sem in self.sems.values_mut() {
+ sem.remove_waiter(pid);
+ }
+ for mutex in self.mutexes.values_mut() {
+ mutex.remove_waiter(pid);
+ }
+ for mailbox in self.mailboxes.values_mut() {
+ mailbox.remove_waiter(pid);
+ }
+ }
+
+ pub fn create_mutex(&mut self, name: String, recursive: bool, protocol: sync::MutexProtocol) {
+ self.mutexes.insert(name.clone(), sync::Mutex::new(&name, recursive, protocol));
+ }
+
+ pub fn get_mutex(&mut self, name: &str) -> Option<&mut sync::Mutex> {
+ self.mutexes.get_mut(name)
+ }
+
+ pub fn has_mutex(&self, name: &str) -> bool {
+ self.mutexes.contains_key(name)
+ }
+
+ /// Mutexes ordenados por nombre
+ pub fn mutexes(&self) -> Vec<&sync::Mutex> {
+ let mut mutexes: Vec<&sync::Mutex> = self.mutexes.values().collect();
+ mutexes.sort_by(|a, b| a.name().cmp(b.name()));
+ mutexes
+ }
+
+ /// Mutex por el que espera un proceso
+ pub fn mutex_waited_by(&mut self, pid: u32) -> Option<&mut sync::Mutex> {
+ self.mutexes.values_mut().find(|m| m.is_waiting(pid))
+ }
+
+ /// Nombres de los mutexes que posee un proceso
+ pub fn mutexes_owned_by(&self, pid: u32) -> Vec<String> {
+ let mut names: Vec<String> = self.mutexes.values()
+ .filter(|m| m.owner() == Some(pid))
+ .map(|m| m.name().to_string())
+ .collect();
+ names.sort();
+ names
+ }
+
+ pub fn create_mailbox(&mut self, name: String, capacity: usize, owner: Option<u32>) {
+ self.mailboxes.insert(name.clone(), mailbox::Mailbox::new(&name, capacity, owner));
+ }
+
+ pub fn get_mailbox(&mut self, name: &str) -> Option<&mut mailbox::Mailbox> {
+ self.mailboxes.get_mut(name)
+ }
+
+ pub fn has_mailbox(&self, name: &str) -> bool {
+ self.mailboxes.contains_key(name)
+ }
+
+ /// Buzones ordenados por nombre
+ pub fn mailboxes(&self) -> Vec<&mailbox::Mailbox> {
+ let mut mailboxes: Vec<&mailbox::Mailbox> = self.mailboxes.values().collect();
+ mailboxes.sort_by(|a, b| a.name().cmp(b.name()));
+ mailboxes
+ }
+
+ /// Buzón en el que un proceso está bloqueado (enviando o recibiendo)
+ pub fn mailbox_waited_by(&self, pid: u32) -> Option<&str> {
+ self.mailboxes.values()
+ .find(|m| m.is_waiting(pid))
+ .map(|m| m.name())
+ }
+
+ /// Eliminar un buzón; devuelve los procesos que quedaron bloqueados en él
+ pub fn remove_mailbox(&mut self, name: &str) -> Vec<u32> {
+ self.mailboxes.remove(name)
+ .map(|m| m.blocked_senders().into_iter().chain(m.blocked_receivers()).collect())
+ .unwrap_or_default()
+ }
 }
@@ -0,0 +1,415 @@
+//! Monitores: variables de condición (wait/signal/broadcast) ligadas a un Mutex
+//! Con semántica Hoare el despertado entra al monitor en el acto; con Mesa solo vuelve
+//! a competir por él y debe recomprobar la condición (puede despertar en falso)
+
+use std::collections::VecDeque;
+use super::sync::{BufferOp, BufferReport, BufferResult, Mutex, MutexProtocol, PendingOp, ProducerConsumerBuffer};
+use crate::rng::XorShift;
+
+/// Qué pasa con el proceso despertado por un signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorSemantics {
+ Hoare, // Signal-and-wait: el despertado recibe el monitor de inmediato
+ Mesa, // Signal-and-continue: el despertado pasa a listo y reintenta después
+}
+
+/// Variable de condición: cola FIFO de procesos que esperan dentro de un monitor
+#[derive(Debug, Clone)]
+pub struct Condition {
+ name: String,
+ queue: VecDeque<u32>,
+ signals: u64,
+ broadcasts: u64,
+ lost_signals: u64, // Signals sin nadie esperando (no se acumulan, a diferencia de un semáforo)
+}
+
+impl Condition {
+ pub fn new(name: &str) -> Self {
+ Self {
+ name: name.to_string(),
+ queue: VecDeque::new(),
+ signals: 0,
+ broadcasts: 0,
+ lost_signals: 0,
+ }
+ }
+
+ /// Esperar: el proceso debe tener el mutex, que se libera por completo mientras espera.
+ /// Devuelve el proceso al que se entregó el mutex, si había alguno esperándolo
+ pub fn wait(&mut self, pid: u32, mutex: &mut Mutex) -> Result<Option<u32>, String> {
+ if mutex.owner() != Some(pid) {
+ return Err(format!("Proceso {} hace wait en '{}' sin tener el mutex '{}'", pid, self.name, mutex.name()));
+ }
+ self.queue.push_back(pid);
+ println!("[Condición {}] Proceso {} espera", self.name, pid);
+ Ok(mutex.abandon(pid))
+ }
+
+ /// Despertar al primero de la cola
+ pub fn signal(&mut self) -> Option<u32> {
+ self.signals += 1;
+ let woken = self.queue.pop_front();
+ match woken {
+ Some(pid) => println!("[Condición {}] Signal despierta al proceso {}", self.name, pid),
+ None => {
+ self.lost_signals += 1;
+ println!("[Condición {}] Signal sin procesos esperando", self.name);
+ }
+ }
+ woken
+ }
+
+ /// Despertar a todos los que esperan
+ pub fn broadcast(&mut self) -> Vec<u32> {
+ self.broadcasts += 1;
+ let woken: Vec<u32> = self.queue.drain(..).collect();
+ println!("[Condición {}] Broadcast despierta a {:?}", self.name, woken);
+ woken
+ }
+
+ pub fn name(&self) -> &str {
+ &self.name
+ }
+
+ pub fn waiting_count(&self) -> usize {
+ self.queue.len()
+ }
+
+ pub fn lost_signals(&self) -> u64 {
+ self.lost_signals
+ }
+}
+
+/// Contadores para comparar con la versión de semáforos
+#[derive(Debug, Clone, Default)]
+pub struct MonitorStats {
+ pub waits: u64, // Veces que un proceso esperó en una condición
+ pub wakeups: u64, // Procesos despertados por signal/broadcast
+ pub spurious: u64, // Despertados que encontraron la condición falsa y volvieron a esperar
+}
+
+/// Buffer acotado implementado como monitor: un mutex y las condiciones not_full/not_empty.
+/// Cada operación hace el signal al salir, así la cola urgente de Hoare nunca se llena
+#[derive(Debug)]
+pub struct MonitorBuffer {
+ buffer: VecDeque<String>,
+ capacity: usize,
+ semantics: MonitorSemantics,
+ use_broadcast: bool,
+ lock: Mutex,
+ not_full: Condition,
+ not_empty: Condition,
+ waiting: Vec<PendingOp>, // Operaciones detenidas en una condición
+ ready: Vec<PendingOp>, // Despertados (Mesa) que aún no volvieron a entrar
+ stats: MonitorStats,
+ total_produced: u64,
+ total_consumed: u64,
+}
+
+impl MonitorBuffer {
+ pub fn new(capacity: usize, semantics: MonitorSemantics, use_broadcast: bool) -> Self {
+ Self {
+ buffer: VecDeque::with_capacity(capacity),
+ capacity,
+ semantics,
+ use_broadcast,
+ lock: Mutex::new("monitor", false, MutexProtocol::None),
+ not_full: Condition::new("not_full"),
+ not_empty: Condition::new("not_empty"),
+ waiting: Vec::new(),
+ ready: Vec::new(),
+ stats: MonitorStats::default(),
+ total_produced: 0,
+ total_consumed: 0,
+ }
+ }
+
+ pub fn produce(&mut self, item: String, producer_pid: u32) -> Result<BufferReport, String> {
+ println!("\n[Productor {}] Entra al monitor para producir '{}'", producer_pid, item);
+ self.start(producer_pid, BufferOp::Produce(item))
+ }
+
+ pub fn consume(&mut self, consumer_pid: u32) -> Result<BufferReport, String> {
+ println!("\n[Consumidor {}] Entra al monitor para consumir", consumer_pid);
+ self.start(consumer_pid, BufferOp::Consume)
+ }
+
+ /// Volver a entrar tras un signal Mesa y recomprobar la condición
+ pub fn resume(&mut self, pid: u32) -> Result<BufferReport, String> {
+ let idx = self.ready.iter().position(|p| p.pid == pid)
+ .ok_or_else(|| format!("Proceso {} no fue despertado", pid))?;
+ let pending = self.ready.remove(idx);
+ println!("\n[Proceso {}] Vuelve a entrar al monitor", pid);
+ self.enter(pending.pid, pending.op, true)
+ }
+
+ fn start(&mut self, pid: u32, op: BufferOp) -> Result<BufferReport, String> {
+ if self.is_pending(pid) {
+ return Err(format!("Proceso {} ya está esperando en el monitor", pid));
+ }
+ self.enter(pid, op, false)
+ }
+
+ fn enter(&mut self, pid: u32, op: BufferOp, retry: bool) -> Result<BufferReport, String> {
+ let (result, woken) = self.attempt(pid, op, retry)?;
+ let mut report = BufferReport { result, resumed: Vec::new(), woken: Vec::new() };
+
+ match self.semantics {
+ // El monitor pasa directamente a cada despertado, en orden
+ MonitorSemantics::Hoare => {
+ let mut queue: VecDeque<u32> = woken.into();
+ while let Some(next) = queue.pop_front() {
+ let Some(idx) = self.waiting.iter().position(|p| p.pid == next) else {
+ continue;
+ };
+ let pending = self.waiting.remove(idx);
+ let (next_result, next_woken) = self.attempt(pending.pid, pending.op, true)?;
+ if next_result != BufferResult::Blocked {
+ report.resumed.push((next, next_result));
+ }
+ queue.extend(next_woken);
+ }
+ }
+ // Los despertados quedan listos; cualquiera puede entrar antes que ellos
+ MonitorSemantics::Mesa => {
+ for pid in woken {
+ if let Some(idx) = self.waiting.iter().position(|p| p.pid == pid) {
+ self.ready.push(self.waiting.remove(idx));
+ report.woken.push(pid);
+ }
+ }
+ }
+ }
+ Ok(report)
+ }
+
+ /// Cuerpo de la operación con el mutex tomado; devuelve el resultado y los despertados
+ fn attempt(&mut self, pid: u32, op: BufferOp, retry: bool) -> Result<(BufferResult, Vec<u32>), String> {
+ if !self.lock.lock(pid, 0)? {
+ return Err("Monitor ocupado (no debería pasar)".to_string());
+ }
+
+ // while (!condición) wait(cond, mutex)
+ let full = self.buffer.len() >= self.capacity;
+ let blocked_on = match op {
+ BufferOp::Produce(_) if full => Some(&mut self.not_full),
+ BufferOp::Consume if self.buffer.is_empty() => Some(&mut self.not_empty),
+ _ => None,
+ };
+ if let Some(cond) = blocked_on {
+ if retry {
+ self.stats.spurious += 1;
+ println!("[Monitor] Proceso {} despertó y la condición sigue falsa", pid);
+ }
+ cond.wait(pid, &mut self.lock)?;
+ self.stats.waits += 1;
+ self.waiting.push(PendingOp { pid, op });
+ return Ok((BufferResult::Blocked, Vec::new()));
+ }
+
+ let (result, cond) = match op {
+ BufferOp::Produce(item) => {
+ self.buffer.push_back(item.clone());
+ self.total_produced += 1;
+ println!("[Productor {}] Item '{}' producido. Buffer: {}/{}", pid, item, self.buffer.len(), self.capacity);
+ (BufferResult::Produced(item), &mut self.not_empty)
+ }
+ BufferOp::Consume => {
+ let item = self.buffer.pop_front().unwrap_or_default();
+ self.total_consumed += 1;
+ println!("[Consumidor {}] Item '{}' consumido. Buffer: {}/{}", pid, item, self.buffer.len(), self.capacity);
+ (BufferResult::Consumed(item), &mut self.not_full)
+ }
+ };
+ let woken = if self.use_broadcast {
+ cond.broadcast()
+ } else {
+ cond.signal().into_iter().collect()
+ };
+ self.stats.wakeups += woken.len() as u64;
+
+ self.lock.unlock(pid)?;
+ Ok((result, woken))
+ }
+
+ /// ¿El proceso espera en una condición o para volver a entrar?
+ pub fn is_pending(&self, pid: u32) -> bool {
+ self.waiting.iter().chain(&self.ready).any(|p| p.pid == pid)
+ }
+
+ pub fn stats(&self) -> &MonitorStats {
+ &self.stats
+ }
+}
+
+/// Carga de trabajo común para comparar ambos buffers
+#[derive(Debug, Clone, Copy)]
+pub struct BufferWorkload {
+ pub producers: usize,
+ pub consumers: usize,
+ pub items: usize, // Items por productor
+ pub capacity: usize,
+ pub seed: u64,
+}
+
+/// Implementación a ejercitar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferVariant {
+ Semaphores,
+ Monitor(MonitorSemantics),
+}
+
+/// Resultado de una corrida
+#[derive(Debug, Clone, Default)]
+pub struct BufferRun {
+ pub name: String,
+ pub produced: u64,
+ pub consumed: u64,
+ pub waits: u64,
+ pub wakeups: u64,
+ pub spurious: u64,
+ pub steps: u64,
+ pub stalled: bool, // Quedaron procesos bloqueados sin nadie que los despierte
+}
+
+enum AnyBuffer {
+ Semaphores(ProducerConsumerBuffer),
+ Monitor(MonitorBuffer),
+}
+
+/// Ejecutar la carga con un orden de planificación aleatorio pero reproducible (misma semilla, mismo orden)
+pub fn run_workload(workload: BufferWorkload, variant: BufferVariant, use_broadcast: bool) -> BufferRun {
+ let mut rng = XorShift::new(workload.seed);
+ let mut buffer = match variant {
+ BufferVariant::Semaphores => AnyBuffer::Semaphores(ProducerConsumerBuffer::new(workload.capacity)),
+ BufferVariant::Monitor(semantics) => AnyBuffer::Monitor(MonitorBuffer::new(workload.capacity, semantics, use_broadcast)),
+ };
+ let name = match variant {
+ BufferVariant::Semaphores => "Semáforos".to_string(),
+ BufferVariant::Monitor(semantics) if use_broadcast => format!("{:?} + broadcast", semantics),
+ BufferVariant::Monitor(semantics) => format!("{:?}", semantics),
+ };
+
+ // Productores 1.., consumidores 101..; los consumidores se reparten todos los items
+ let total = workload.producers * workload.items;
+ let mut remaining: Vec<(u32, bool, usize)> = (0..workload.producers)
+ .map(|i| (i as u32 + 1, true, workload.items))
+ .collect();
+ for i in 0..workload.consumers {
+ let share = total / workload.consumers + usize::from(i < total % workload.consumers);
+ remaining.push((i as u32 + 101, false, share));
+ }
+ let mut blocked: Vec<u32> = Vec::new();
+ let mut run = BufferRun { name, ..BufferRun::default() };
+
+ loop {
+ let runnable: Vec<usize> = (0..remaining.len())
+ .filter(|i| remaining[*i].2 > 0 && !blocked.contains(&remaining[*i].0))
+ .collect();
+ if runnable.is_empty() {
+ run.stalled = remaining.iter().any(|r| r.2 > 0);
+ break;
+ }
+ let idx = runnable[rng.next_range(runnable.len())];
+ let (pid, producer, left) = remaining[idx];
+ run.steps += 1;
+
+ let report = match &mut buffer {
+ AnyBuffer::Semaphores(pc) if producer => pc.produce(format!("P{}-{}", pid, left), pid),
+ AnyBuffer::Semaphores(pc) => pc.consume(pid),
+ AnyBuffer::Monitor(m) if m.is_pending(pid) => m.resume(pid),
+ AnyBuffer::Monitor(m) if producer => m.produce(format!("P{}-{}", pid, left), pid),
+ AnyBuffer::Monitor(m) => m.consume(pid),
+ };
+ let Ok(report) = report else {
+ run.stalled = true;
+ break;
+ };
+
+ // Quien completó su operación (el llamador o un despertado) descuenta un item
+ let mut done: Vec<u32> = report.resumed.iter().map(|(p, _)| *p).collect();
+ if report.result == BufferResult::Blocked {
+ run.waits += 1;
+ blocked.push(pid);
+ } else {
+ done.push(pid);
+ }
+ for p in done {
+ blocked.retain(|b| *b != p);
+ if let Some(r) = remaining.iter_mut().find(|r| r.0 == p) {
+ r.2 -= 1;
+ if r.1 { run.produced += 1 } else { run.consumed += 1 }
+ }
+ }
+ run.wakeups += report.resumed.len() as u64;
+ // Mesa: los despertados vuelven a ser planificables
+ blocked.retain(|b| !report.woken.contains(b));
+ }
+
+ // El monitor cuenta también las esperas repetidas y los despertares Mesa
+ if let AnyBuffer::Monitor(m) = &buffer {
+ run.waits = m.stats().waits;
+ run.wakeups = m.stats().wakeups;
+ run.spurious = m.stats().spurious;
+ }
+ run
+}
+
+/// Tabla comparativa de varias corridas
+pub fn display_comparison(workload: &BufferWorkload, runs: &[BufferRun]) {
+ println!("\n╔════════════════════════════════════════════════════════════════════════════════╗");
+ println!("║ BUFFER ACOTADO: SEMÁFOROS VS MONITOR ║");
+ println!("║ {} productores x {} items, {} consumidores, capacidad {}, semilla {}",
+ workload.producers, workload.items, workload.consumers, workload.capacity, workload.seed);
+ println!("╠═════════════════════╦══════════╦══════════╦═════════╦═════════════╦═══════════╦═══════╣");
+ println!("║ Variante ║ Producid.║ Consumid.║ Esperas ║ Despertares ║ Espurios ║ Pasos ║");
+ println!("╠═════════════════════╬══════════╬══════════╬═════════╬═════════════╬═══════════╬═══════╣");
+ for run in runs {
+ println!("║ {:19} ║ {:8} ║ {:8} ║ {:7} ║ {:11} ║ {:9} ║ {:5} ║",
+ run.name, run.produced, run.consumed, run.waits, run.wakeups, run.spurious, run.steps);
+ }
+ println!("╚═════════════════════╩══════════╩══════════╩═════════╩═════════════╩═══════════╩═══════╝");
+ for run in runs.iter().filter(|r| r.stalled) {
+ println!("[WARN] {}: quedaron procesos bloqueados sin completar", run.name);
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_mesa_wakeup_can_be_spurious() {
+ let mut mesa = MonitorBuffer::new(1, MonitorSemantics::Mesa, false);
+ assert_eq!(mesa.consume(2).unwrap().result, BufferResult::Blocked);
+
+ // El productor despierta a 2, pero 3 entra antes y se lleva el item
+ assert_eq!(mesa.produce("A".to_string(), 1).unwrap().woken, vec![2]);
+ assert_eq!(mesa.consume(3).unwrap().result, BufferResult::Consumed("A".to_string()));
+ assert_eq!(mesa.resume(2).unwrap().result, BufferResult::Blocked);
+ assert_eq!((mesa.stats().waits, mesa.stats().spurious), (2, 1));
+
+ // Con Hoare el despertado consume en el acto
+ let mut hoare = MonitorBuffer::new(1, MonitorSemantics::Hoare, false);
+ hoare.consume(2).unwrap();
+ let report = hoare.produce("A".to_string(), 1).unwrap();
+ assert_eq!(report.resumed, vec![(2, BufferResult::Consumed("A".to_string()))]);
+ assert_eq!(hoare.consume(3).unwrap().result, BufferResult::Blocked);
+ assert_eq!(hoare.stats().spurious, 0);
+ }
+
+ #[test]
+ fn test_workload_completes_with_every_variant() {
+ let workload = BufferWorkload { producers: 3, consumers: 2, items: 6, capacity: 2, seed: 7 };
+ for variant in [BufferVariant::Semaphores, BufferVariant::Monitor(MonitorSemantics::Hoare), BufferVariant::Monitor(MonitorSemantics::Mesa)] {
+ for broadcast in [false, true] {
+ let run = run_workload(workload, variant, broadcast);
+ assert!(!run.stalled, "{} se trabó", run.name);
+ assert_eq!((run.produced, run.consumed), (18, 18));
+ if variant != BufferVariant::Monitor(MonitorSemantics::Mesa) && !broadcast {
+ assert_eq!(run.spurious, 0);
+ }
+ }
+ }
+ }
+}
@@ -1,12 +1,49 @@
+//! Cena de los filósofos por ticks: comer y pensar duran varios ticks y cada
+//! filósofo toma un tenedor por tick, así que hay contención real entre vecinos
+
+use std::fmt;
 use super::sync::Semaphore;
+use crate::rng::XorShift;
+
+/// Ticks que dura cada comida
+pub const EAT_TICKS: u32 = 3;
+/// Máximo de ticks pensando entre comidas
+pub const MAX_THINK_TICKS: usize = 4;
+
+/// Estrategia para repartir los tenedores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+ Naive, // Izquierdo y luego derecho: puede interbloquearse
+ Asymmetric, // Pares izquierdo primero, impares derecho primero
+ Waiter, // Un camarero concede los dos tenedores a la vez, al más antiguo primero
+ Hierarchy, // Siempre el tenedor de menor número primero
+ ChandyMisra, // Tenedores limpios/sucios que se piden al vecino
+}
+
+impl Strategy {
+ pub fn name(self) -> &'static str {
+ match self {
+ Strategy::Naive => "Ingenua",
+ Strategy::Asymmetric => "Asimétrica",
+ Strategy::Waiter => "Camarero",
+ Strategy::Hierarchy => "Jerarquía",
+ Strategy::ChandyMisra => "Chandy-Misra",
+ }
+ }
+}
+
+impl fmt::Display for Strategy {
+ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+ write!(f, "{}", self.name())
+ }
+}
 
-/// Estado de un filósofo
+/// Estado de un filósofo (con los ticks que le quedan)
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PhilosopherState {
- Thinking,
- Hungry,
- Eating,
- Finished,
+ Thinking(u32),
+ Hungry { since: u64 },
+ Eating(u32),
 }
 
 /// Un filósofo
@@ -15,169 +52,338 @@ pub struct Philosopher {
  pub id: usize,
  pub state: PhilosopherState,
  pub times_eaten: u32,
+ pub holding: Vec<usize>, // Tenedores en la mano
+ pub blocked_on: Option<usize>, // Tenedor en cuya cola de semáforo espera
+ pub total_wait: u64,
+ pub max_wait: u64,
+}
+
+/// Tenedor de Chandy-Misra: siempre tiene dueño
+#[derive(Debug, Clone)]
+struct CmFork {
+ owner: usize,
+ dirty: bool,
+ requested: bool, // El vecino que no lo tiene lo ha pedido
 }
 
 /// Simulación de la Cena de los Filósofos
 pub struct DiningPhilosophers {
  philosophers: Vec<Philosopher>,
  forks: Vec<Semaphore>, // Un semáforo por tenedor
+ cm_forks: Vec<CmFork>,
  total_philosophers: usize,
+ strategy: Strategy,
+ rng: XorShift,
+ tick: u64,
+ deadlock: Option<u64>, // Tick en que se detectó
 }
 
 impl DiningPhilosophers {
- pub fn new(count: usize) -> Self {
+ pub fn new(count: usize, strategy: Strategy, seed: u64) -> Self {
+ let mut rng = XorShift::new(seed);
  let mut philosophers = Vec::with_capacity(count);
  let mut forks = Vec::with_capacity(count);
+ let mut cm_forks = Vec::with_capacity(count);
 
  for i in 0..count {
  philosophers.push(Philosopher {
  id: i,
- state: PhilosopherState::Thinking,
+ state: PhilosopherState::Thinking(1 + rng.next_range(MAX_THINK_TICKS) as u32),
  times_eaten: 0,
+ holding: Vec::new(),
+ blocked_on: None,
+ total_wait: 0,
+ max_wait: 0,
  });
  forks.push(Semaphore::new(1, &format!("fork_{}", i)));
+ // El tenedor i lo comparten i y su vecino anterior: empieza sucio en el de menor id
+ cm_forks.push(CmFork { owner: i.min((i + count - 1) % count), dirty: true, requested: false });
+ }
+ if strategy == Strategy::ChandyMisra {
+ for (fork, cm) in cm_forks.iter().enumerate() {
+ philosophers[cm.owner].holding.push(fork);
+ }
  }
 
  Self {
  philosophers,
  forks,
+ cm_forks,
  total_philosophers: count,
+ strategy,
+ rng,
+ tick: 0,
+ deadlock: None,
  }
  }
 
- /// Un filósofo intenta comer
- /// Solución: Orden asimétrico para evitar deadlock
- /// - Filósofos 0-(n-2): toman tenedor izquierdo, luego derecho
- /// - Filósofo (n-1): toma tenedor derecho, luego izquierdo
- pub fn try_eat(&mut self, philosopher_id: usize) -> Result<(), String> {
- if philosopher_id >= self.total_philosophers {
- return Err("Filósofo inválido".to_string());
+ /// Tenedores izquierdo y derecho de un filósofo
+ fn forks_of(&self, id: usize) -> (usize, usize) {
+ (id, (id + 1) % self.total_philosophers)
  }
 
- let left_fork = philosopher_id;
- let right_fork = (philosopher_id + 1) % self.total_philosophers;
+ /// Orden en que se toman los tenedores en las estrategias con semáforos
+ fn acquisition_order(&self, id: usize) -> (usize, usize) {
+ let (left, right) = self.forks_of(id);
+ match self.strategy {
+ Strategy::Asymmetric if id % 2 == 1 => (right, left),
+ Strategy::Hierarchy => (left.min(right), left.max(right)),
+ _ => (left, right),
+ }
+ }
 
- println!("\n[Filósofo {}] Tengo hambre...", philosopher_id);
- self.philosophers[philosopher_id].state = PhilosopherState::Hungry;
+ /// Avanzar un tick; los filósofos actúan en un orden aleatorio con semilla
+ pub fn step(&mut self) {
+ if self.deadlock.is_some() {
+ return;
+ }
+ self.tick += 1;
 
- // Orden asimétrico para evitar deadlock
- let (first_fork, second_fork) = if philosopher_id == self.total_philosophers - 1 {
- // Último filósofo: derecho primero
- (right_fork, left_fork)
- } else {
- // Otros: izquierdo primero
- (left_fork, right_fork)
- };
+ let mut order: Vec<usize> = (0..self.total_philosophers).collect();
+ for i in (1..order.len()).rev() {
+ let j = self.rng.next_range(i + 1);
+ order.swap(i, j);
+ }
 
- // Intentar tomar primer tenedor
- if !self.forks[first_fork].wait(philosopher_id as u32) {
- println!("[Filósofo {}] No pude tomar tenedor {}", philosopher_id, first_fork);
- return Err(format!("Filósofo {} bloqueado en tenedor {}", philosopher_id, first_fork));
+ for id in order {
+ match self.philosophers[id].state {
+ PhilosopherState::Thinking(left) if left > 1 => {
+ self.philosophers[id].state = PhilosopherState::Thinking(left - 1);
+ }
+ PhilosopherState::Thinking(_) => {
+ self.philosophers[id].state = PhilosopherState::Hungry { since: self.tick };
+ println!("[Filósofo {}] Tengo hambre...", id);
+ }
+ PhilosopherState::Eating(left) if left > 1 => {
+ self.philosophers[id].state = PhilosopherState::Eating(left - 1);
+ }
+ PhilosopherState::Eating(_) => self.finish_eating(id),
+ PhilosopherState::Hungry { since } => match self.strategy {
+ Strategy::Waiter => self.ask_waiter(id, since),
+ Strategy::ChandyMisra => self.request_forks(id, since),
+ _ => self.take_next_fork(id, since),
+ },
+ }
  }
 
- println!("[Filósofo {}] Tomé tenedor {}", philosopher_id, first_fork);
+ if self.is_deadlocked() {
+ self.deadlock = Some(self.tick);
+ println!("[DEADLOCK] Tick {}: cada filósofo tiene un tenedor y espera el de su vecino", self.tick);
+ for phil in &self.philosophers {
+ println!(" Filósofo {} tiene {:?} y espera el tenedor {}",
+ phil.id, phil.holding, phil.blocked_on.unwrap_or_default());
+ }
+ }
+ }
 
- // Intentar tomar segundo tenedor
- if !self.forks[second_fork].wait(philosopher_id as u32) {
- // Si no puede tomar el segundo, suelta el primero
- println!("[Filósofo {}] No pude tomar tenedor {}, soltando {}", 
- philosopher_id, second_fork, first_fork);
- self.forks[first_fork].signal();
- return Err(format!("Filósofo {} bloqueado en tenedor {}", philosopher_id, second_fork));
+ /// Espera circular: todos hambrientos y bloqueados en un semáforo
+ fn is_deadlocked(&self) -> bool {
+ self.philosophers.iter().all(|p| {
+ matches!(p.state, PhilosopherState::Hungry { .. }) && p.blocked_on.is_some()
+ })
  }
 
- println!("[Filósofo {}] Tomé tenedor {}", philosopher_id, second_fork);
+ /// Tomar un tenedor por tick en el orden de la estrategia (sin soltar el primero)
+ fn take_next_fork(&mut self, id: usize, since: u64) {
+ if self.philosophers[id].blocked_on.is_some() {
+ return;
+ }
+ let (first, second) = self.acquisition_order(id);
+ if self.philosophers[id].holding.len() < 2 {
+ let fork = if self.philosophers[id].holding.is_empty() { first } else { second };
+ if self.forks[fork].wait(id as u32) {
+ self.philosophers[id].holding.push(fork);
+ println!("[Filósofo {}] Tomé tenedor {}", id, fork);
+ } else {
+ self.philosophers[id].blocked_on = Some(fork);
+ println!("[Filósofo {}] Espero el tenedor {}", id, fork);
+ }
+ }
+ if self.philosophers[id].holding.len() == 2 {
+ self.start_eating(id, since);
+ }
+ }
 
- // ¡A comer!
- self.philosophers[philosopher_id].state = PhilosopherState::Eating;
- self.philosophers[philosopher_id].times_eaten += 1;
- 
- println!("[EATING] [Filósofo {}] COMIENDO (vez #{}) con tenedores {} y {}", 
- philosopher_id, self.philosophers[philosopher_id].times_eaten, 
- left_fork, right_fork);
+ /// El camarero da los dos tenedores si están libres y ningún vecino que los necesite lleva más esperando
+ fn ask_waiter(&mut self, id: usize, since: u64) {
+ let (left, right) = self.forks_of(id);
+ let n = self.total_philosophers;
+ let older_neighbor = [(id + n - 1) % n, (id + 1) % n].iter().any(|&other| {
+ matches!(self.philosophers[other].state, PhilosopherState::Hungry { since: s } if (s, other) < (since, id))
+ });
+ if older_neighbor || self.forks[left].count() <= 0 || self.forks[right].count() <= 0 {
+ return;
+ }
+ self.forks[left].wait(id as u32);
+ self.forks[right].wait(id as u32);
+ self.philosophers[id].holding = vec![left, right];
+ println!("[Camarero] Concede los tenedores {} y {} al filósofo {}", left, right, id);
+ self.start_eating(id, since);
+ }
 
- // Simular tiempo comiendo (en sistema real sería sleep)
- // En nuestra simulación, come instantáneamente
+ /// Chandy-Misra: pedir los tenedores que faltan; el dueño cede uno sucio si no está comiendo
+ fn request_forks(&mut self, id: usize, since: u64) {
+ let (left, right) = self.forks_of(id);
+ for fork in [left, right] {
+ if self.cm_forks[fork].owner == id {
+ continue;
+ }
+ if !self.cm_forks[fork].requested {
+ self.cm_forks[fork].requested = true;
+ println!("[Filósofo {}] Pido el tenedor {} al filósofo {}", id, fork, self.cm_forks[fork].owner);
+ }
+ self.try_hand_over(fork);
+ }
+ if self.cm_forks[left].owner == id && self.cm_forks[right].owner == id {
+ self.start_eating(id, since);
+ }
+ }
 
- // Soltar tenedores en orden inverso
- self.forks[second_fork].signal();
- println!("[Filósofo {}] Solté tenedor {}", philosopher_id, second_fork);
- 
- self.forks[first_fork].signal();
- println!("[Filósofo {}] Solté tenedor {}", philosopher_id, first_fork);
+ /// Ceder un tenedor pedido si está sucio y su dueño no come; llega limpio
+ fn try_hand_over(&mut self, fork: usize) {
+ let cm = &self.cm_forks[fork];
+ let owner = cm.owner;
+ if !cm.requested || !cm.dirty || matches!(self.philosophers[owner].state, PhilosopherState::Eating(_)) {
+ return;
+ }
+ // El tenedor i es el izquierdo de i y el derecho de i-1
+ let to = if owner == fork { (fork + self.total_philosophers - 1) % self.total_philosophers } else { fork };
+ self.cm_forks[fork] = CmFork { owner: to, dirty: false, requested: false };
+ self.philosophers[owner].holding.retain(|f| *f != fork);
+ self.philosophers[to].holding.push(fork);
+ println!("[Filósofo {}] Limpio el tenedor {} y se lo paso al filósofo {}", owner, fork, to);
+ }
 
- // Volver a pensar
- self.philosophers[philosopher_id].state = PhilosopherState::Thinking;
- println!(" [Filósofo {}] Pensando...", philosopher_id);
+ fn start_eating(&mut self, id: usize, since: u64) {
+ let wait = self.tick - since;
+ let phil = &mut self.philosophers[id];
+ phil.times_eaten += 1;
+ phil.total_wait += wait;
+ phil.max_wait = phil.max_wait.max(wait);
+ phil.state = PhilosopherState::Eating(EAT_TICKS);
+ println!("[EATING] [Filósofo {}] COMIENDO (vez #{}) con tenedores {:?} tras esperar {} ticks",
+ id, phil.times_eaten, phil.holding, wait);
+ }
 
- Ok(())
+ /// Soltar los tenedores: con semáforos pasan al primero de la cola; en Chandy-Misra quedan sucios
+ fn finish_eating(&mut self, id: usize) {
+ if self.strategy == Strategy::ChandyMisra {
+ let (left, right) = self.forks_of(id);
+ for fork in [left, right] {
+ self.cm_forks[fork].dirty = true;
+ }
+ self.philosophers[id].state = PhilosopherState::Thinking(0); // Ya no come: puede ceder
+ for fork in [left, right] {
+ self.try_hand_over(fork);
+ }
+ } else {
+ let holding = std::mem::take(&mut self.philosophers[id].holding);
+ for fork in holding.into_iter().rev() {
+ println!("[Filósofo {}] Solté tenedor {}", id, fork);
+ if let Some(pid) = self.forks[fork].signal() {
+ let woken = &mut self.philosophers[pid as usize];
+ woken.holding.push(fork);
+ woken.blocked_on = None;
+ }
+ }
+ }
+ let think = 1 + self.rng.next_range(MAX_THINK_TICKS) as u32;
+ self.philosophers[id].state = PhilosopherState::Thinking(think);
+ println!("[Filósofo {}] Pensando ({} ticks)...", id, think);
  }
 
- /// Simular varios pasos
+ /// Simular varios ticks (se detiene si hay interbloqueo)
  pub fn simulate(&mut self, steps: usize) {
  println!("\n╔═══════════════════════════════════════════════════╗");
  println!("║ SIMULACIÓN: CENA DE LOS FILÓSOFOS ║");
- println!("║ {} filósofos, {} tenedores ║", 
- self.total_philosophers, self.total_philosophers);
+ println!("║ {} filósofos, {} tenedores, estrategia {} ║",
+ self.total_philosophers, self.total_philosophers, self.strategy.name());
  println!("╚═══════════════════════════════════════════════════╝");
 
- for step in 0..steps {
- println!("\n--- Paso {} ---", step + 1);
- 
- // Cada filósofo intenta comer en secuencia
- for i in 0..self.total_philosophers {
- let _ = self.try_eat(i);
+ for _ in 0..steps {
+ println!("\n--- Tick {} ---", self.tick + 1);
+ self.step();
+ if self.deadlock.is_some() {
+ break;
+ }
  }
 
  self.display_status();
+ self.display_summary();
  }
 
- self.display_summary();
+ /// Espera más larga, contando la de quien sigue hambriento
+ fn worst_wait(&self, phil: &Philosopher) -> u64 {
+ match phil.state {
+ PhilosopherState::Hungry { since } => phil.max_wait.max(self.tick - since),
+ _ => phil.max_wait,
+ }
+ }
+
+ pub fn deadlock(&self) -> Option<u64> {
+ self.deadlock
+ }
+
+ pub fn philosophers(&self) -> &[Philosopher] {
+ &self.philosophers
  }
 
  /// Mostrar estado actual
  pub fn display_status(&self) {
- println!("\n┌─────┬───────────┬────────────┐");
- println!("│ ID │ Estado │ Veces Comió│");
- println!("├─────┼───────────┼────────────┤");
- 
+ println!("\n┌─────┬──────────────┬────────────┬─────────┬─────────────┐");
+ println!("│ ID │ Estado │ Tenedores │ Comidas │ Espera máx. │");
+ println!("├─────┼──────────────┼────────────┼─────────┼─────────────┤");
+
  for phil in &self.philosophers {
  let state_str = match phil.state {
- PhilosopherState::Thinking => " Pensando",
- PhilosopherState::Hungry => "[HUNGRY] Hambriento",
- PhilosopherState::Eating => "[EATING] Comiendo",
- PhilosopherState::Finished => "[OK] Terminó",
+ PhilosopherState::Thinking(_) => "Pensando",
+ PhilosopherState::Hungry { .. } => "[HUNGRY]",
+ PhilosopherState::Eating(_) => "[EATING]",
  };
- println!("│ {} │ {} │ {:3} │", phil.id, state_str, phil.times_eaten);
+ let forks: Vec<String> = phil.holding.iter().map(|f| f.to_string()).collect();
+ println!("│ {:3} │ {:12} │ {:10} │ {:7} │ {:11} │",
+ phil.id, state_str, forks.join(","), phil.times_eaten, self.worst_wait(phil));
  }
- 
- println!("└─────┴───────────┴────────────┘");
+
+ println!("└─────┴──────────────┴────────────┴─────────┴─────────────┘");
  }
 
- /// Resumen final
+ /// Resumen final con métricas de inanición por filósofo
  pub fn display_summary(&self) {
  println!("\n╔═══════════════════════════════════════════╗");
- println!("║ RESUMEN FINAL ║");
+ println!("║ RESUMEN FINAL ({}) ║", self.strategy.name());
  println!("╠═══════════════════════════════════════════╣");
- 
+
  let total_meals: u32 = self.philosophers.iter().map(|p| p.times_eaten).sum();
  let avg_meals = total_meals as f64 / self.total_philosophers as f64;
- 
+
+ println!("║ Ticks simulados: {:6} ║", self.tick);
  println!("║ Total de comidas: {:6} ║", total_meals);
  println!("║ Promedio por filósofo: {:.2} ║", avg_meals);
  println!("╠═══════════════════════════════════════════╣");
- 
+
+ for phil in &self.philosophers {
+ let avg_wait = if phil.times_eaten > 0 { phil.total_wait as f64 / phil.times_eaten as f64 } else { 0.0 };
+ println!("║ F{}: {:3} comidas, espera media {:5.2}, máx {:3} ║",
+ phil.id, phil.times_eaten, avg_wait, self.worst_wait(phil));
+ }
+ println!("╠═══════════════════════════════════════════╣");
+
  let min_meals = self.philosophers.iter().map(|p| p.times_eaten).min().unwrap_or(0);
  let max_meals = self.philosophers.iter().map(|p| p.times_eaten).max().unwrap_or(0);
- 
+
  println!("║ Mínimo: {} | Máximo: {} ║", min_meals, max_meals);
- 
- if min_meals > 0 {
+
+ if let Some(tick) = self.deadlock {
+ println!("║ [ERROR] Interbloqueo en el tick {:4} ║", tick);
+ }
+ let starved: Vec<usize> = self.philosophers.iter().filter(|p| p.times_eaten == 0).map(|p| p.id).collect();
+ if starved.is_empty() {
  println!("║ [OK] NO hubo inanición ║");
  } else {
- println!("║ [WARN] Posible inanición detectada ║");
+ println!("║ [WARN] Inanición: nunca comieron {:?} ║", starved);
  }
- 
+
  println!("╚═══════════════════════════════════════════╝");
  }
 }
@@ -186,20 +392,34 @@ impl DiningPhilosophers {
 mod tests {
  use super::*;
 
- #[test]
- fn test_philosophers_no_deadlock() {
- let mut dining = DiningPhilosophers::new(5);
- 
- // Simular varios intentos
- for _ in 0..10 {
- for i in 0..5 {
- let _ = dining.try_eat(i);
+ fn run(strategy: Strategy, seed: u64, ticks: usize) -> DiningPhilosophers {
+ let mut dining = DiningPhilosophers::new(5, strategy, seed);
+ for _ in 0..ticks {
+ dining.step();
+ // Dos vecinos nunca comen a la vez
+ for phil in &dining.philosophers {
+ let next = &dining.philosophers[(phil.id + 1) % 5];
+ assert!(!(matches!(phil.state, PhilosopherState::Eating(_)) && matches!(next.state, PhilosopherState::Eating(_))));
+ }
  }
+ dining
  }
 
+ #[test]
+ fn test_philosophers_no_deadlock() {
+ for strategy in [Strategy::Asymmetric, Strategy::Waiter, Strategy::Hierarchy, Strategy::ChandyMisra] {
+ let dining = run(strategy, 42, 300);
+ assert_eq!(dining.deadlock(), None, "{} se interbloqueó", strategy);
  // Verificar que todos comieron al menos una vez (no hay inanición)
- for phil in &dining.philosophers {
- assert!(phil.times_eaten > 0, "Filósofo {} no comió", phil.id);
+ for phil in dining.philosophers() {
+ assert!(phil.times_eaten > 0, "{}: filósofo {} no comió", strategy, phil.id);
+ }
+ }
  }
+
+ #[test]
+ fn test_naive_can_deadlock() {
+ let deadlocked = (0..20).filter_map(|seed| run(Strategy::Naive, seed, 300).deadlock()).count();
+ assert!(deadlocked > 0);
  }
 }
@@ -0,0 +1,331 @@
+//! Lectores-escritores: varios lectores pueden compartir el recurso, un escritor lo necesita solo
+//! La política de admisión decide quién puede pasar hambre
+
+use std::collections::VecDeque;
+use crate::rng::XorShift;
+
+/// Ticks que dura una lectura
+pub const READ_TICKS: u32 = 3;
+/// Ticks que dura una escritura
+pub const WRITE_TICKS: u32 = 2;
+/// Máximo de ticks pensando entre accesos
+pub const MAX_IDLE_TICKS: usize = 3;
+
+/// Quién tiene preferencia cuando hay lectores y escritores esperando
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RwPolicy {
+ ReaderPreference, // Un lector entra si no hay escritor activo (los escritores pueden esperar para siempre)
+ WriterPreference, // Un escritor esperando detiene a los lectores nuevos
+ Fair, // Turnos FIFO: se admite en orden de llegada, agrupando lectores consecutivos
+}
+
+impl RwPolicy {
+ pub const ALL: [RwPolicy; 3] = [RwPolicy::ReaderPreference, RwPolicy::WriterPreference, RwPolicy::Fair];
+
+ pub fn name(self) -> &'static str {
+ match self {
+ RwPolicy::ReaderPreference => "Pref. lectores",
+ RwPolicy::WriterPreference => "Pref. escritores",
+ RwPolicy::Fair => "Justa (FIFO)",
+ }
+ }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+ Reader,
+ Writer,
+}
+
+/// Estado de un actor
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActorState {
+ Idle(usize), // Ticks que faltan para pedir acceso
+ Waiting { since: u64, ticket: u64 },
+ Active(u32), // Ticks que faltan para soltar el recurso
+}
+
+/// Un lector o escritor
+#[derive(Debug)]
+pub struct Actor {
+ pub id: usize,
+ pub role: Role,
+ pub state: ActorState,
+ pub accesses: u32,
+ pub total_wait: u64,
+ pub max_wait: u64,
+}
+
+impl Actor {
+ fn label(&self) -> String {
+ match self.role {
+ Role::Reader => format!("Lector {}", self.id),
+ Role::Writer => format!("Escritor {}", self.id),
+ }
+ }
+}
+
+/// Métricas de un rol al final de la simulación
+#[derive(Debug, Clone, Default)]
+pub struct RoleSummary {
+ pub accesses: u32,
+ pub avg_wait: f64,
+ pub max_wait: u64, // Incluye la espera de quien sigue esperando al terminar
+ pub starved: usize, // Actores que nunca accedieron
+}
+
+/// Simulación de lectores-escritores por ticks
+pub struct ReadersWriters {
+ actors: Vec<Actor>,
+ policy: RwPolicy,
+ rng: XorShift,
+ tick: u64,
+ next_ticket: u64,
+ verbose: bool,
+}
+
+impl ReadersWriters {
+ pub fn new(readers: usize, writers: usize, policy: RwPolicy, seed: u64) -> Self {
+ let mut rng = XorShift::new(seed);
+ let actors = (0..readers).map(|i| (i, Role::Reader))
+ .chain((0..writers).map(|i| (i, Role::Writer)))
+ .map(|(id, role)| Actor {
+ id,
+ role,
+ state: ActorState::Idle(rng.next_range(MAX_IDLE_TICKS)),
+ accesses: 0,
+ total_wait: 0,
+ max_wait: 0,
+ })
+ .collect();
+ Self { actors, policy, rng, tick: 0, next_ticket: 0, verbose: true }
+ }
+
+ /// Mostrar o no los eventos de cada tick
+ pub fn set_verbose(&mut self, verbose: bool) {
+ self.verbose = verbose;
+ }
+
+ fn log(&self, message: String) {
+ if self.verbose {
+ println!("{}", message);
+ }
+ }
+
+ /// Avanzar un tick: terminar accesos, generar pedidos y admitir según la política
+ pub fn step(&mut self) {
+ self.tick += 1;
+
+ for i in 0..self.actors.len() {
+ match self.actors[i].state {
+ ActorState::Active(1) => {
+ let idle = 1 + self.rng.next_range(MAX_IDLE_TICKS);
+ self.actors[i].state = ActorState::Idle(idle);
+ let verb = if self.actors[i].role == Role::Reader { "lectura" } else { "escritura" };
+ self.log(format!("[{}] Termina su {}", self.actors[i].label(), verb));
+ }
+ ActorState::Active(left) => self.actors[i].state = ActorState::Active(left - 1),
+ ActorState::Idle(0) => {
+ self.actors[i].state = ActorState::Waiting { since: self.tick, ticket: self.next_ticket };
+ self.next_ticket += 1;
+ self.log(format!("[{}] Pide acceso", self.actors[i].label()));
+ }
+ ActorState::Idle(left) => self.actors[i].state = ActorState::Idle(left - 1),
+ ActorState::Waiting { .. } => {}
+ }
+ }
+
+ for i in self.admissions() {
+ let ActorState::Waiting { since, .. } = self.actors[i].state else {
+ continue;
+ };
+ let wait = self.tick - since;
+ let actor = &mut self.actors[i];
+ actor.accesses += 1;
+ actor.total_wait += wait;
+ actor.max_wait = actor.max_wait.max(wait);
+ actor.state = ActorState::Active(match actor.role {
+ Role::Reader => READ_TICKS,
+ Role::Writer => WRITE_TICKS,
+ });
+ let verb = if actor.role == Role::Reader { "leer" } else { "escribir" };
+ let message = format!("[{}] Empieza a {} (esperó {} ticks)", actor.label(), verb, wait);
+ self.log(message);
+ }
+ }
+
+ /// Índices de los actores que entran este tick
+ fn admissions(&self) -> Vec<usize> {
+ let active = |role: Role| self.actors.iter().filter(|a| a.role == role && matches!(a.state, ActorState::Active(_))).count();
+ let (readers_in, writer_in) = (active(Role::Reader), active(Role::Writer) > 0);
+
+ // Esperando, en orden de llegada
+ let mut queue: Vec<usize> = (0..self.actors.len())
+ .filter(|i| matches!(self.actors[*i].state, ActorState::Waiting { .. }))
+ .collect();
+ queue.sort_by_key(|i| match self.actors[*i].state {
+ ActorState::Waiting { ticket, .. } => ticket,
+ _ => u64::MAX,
+ });
+ let readers: Vec<usize> = queue.iter().copied().filter(|i| self.actors[*i].role == Role::Reader).collect();
+ let first_writer = queue.iter().copied().find(|i| self.actors[*i].role == Role::Writer);
+
+ if writer_in {
+ return Vec::new();
+ }
+ match self.policy {
+ RwPolicy::ReaderPreference => {
+ if !readers.is_empty() {
+ readers
+ } else if readers_in == 0 {
+ first_writer.into_iter().collect()
+ } else {
+ Vec::new()
+ }
+ }
+ RwPolicy::WriterPreference => match first_writer {
+ Some(writer) if readers_in == 0 => vec![writer],
+ Some(_) => Vec::new(),
+ None => readers,
+ },
+ RwPolicy::Fair => {
+ let mut queue: VecDeque<usize> = queue.into();
+ let mut admitted = Vec::new();
+ while let Some(&next) = queue.front() {
+ match self.actors[next].role {
+ Role::Reader => admitted.push(next),
+ Role::Writer if readers_in == 0 && admitted.is_empty() => {
+ admitted.push(next);
+ break;
+ }
+ Role::Writer => break,
+ }
+ queue.pop_front();
+ }
+ admitted
+ }
+ }
+ }
+
+ /// Simular varios ticks
+ pub fn simulate(&mut self, steps: usize) {
+ let readers = self.actors.iter().filter(|a| a.role == Role::Reader).count();
+ println!("\n╔═══════════════════════════════════════════════════╗");
+ println!("║ SIMULACIÓN: LECTORES-ESCRITORES ║");
+ println!("║ {} lectores, {} escritores, política: {}",
+ readers, self.actors.len() - readers, self.policy.name());
+ println!("╚═══════════════════════════════════════════════════╝");
+
+ for _ in 0..steps {
+ if self.verbose {
+ println!("\n--- Tick {} ---", self.tick + 1);
+ }
+ self.step();
+ }
+ }
+
+ /// Métricas de un rol; quien sigue esperando cuenta con su espera actual
+ pub fn summary(&self, role: Role) -> RoleSummary {
+ let actors: Vec<&Actor> = self.actors.iter().filter(|a| a.role == role).collect();
+ let accesses: u32 = actors.iter().map(|a| a.accesses).sum();
+ let total_wait: u64 = actors.iter().map(|a| a.total_wait).sum();
+ let max_wait = actors.iter()
+ .map(|a| match a.state {
+ ActorState::Waiting { since, .. } => a.max_wait.max(self.tick - since),
+ _ => a.max_wait,
+ })
+ .max()
+ .unwrap_or(0);
+ RoleSummary {
+ accesses,
+ avg_wait: if accesses == 0 { 0.0 } else { total_wait as f64 / accesses as f64 },
+ max_wait,
+ starved: actors.iter().filter(|a| a.accesses == 0).count(),
+ }
+ }
+
+ /// Estado y contadores de cada actor
+ pub fn display_status(&self) {
+ println!("\n┌──────────────┬──────────────┬──────────┬────────────┐");
+ println!("│ Actor │ Estado │ Accesos │ Espera máx │");
+ println!("├──────────────┼──────────────┼──────────┼────────────┤");
+ for actor in &self.actors {
+ let state = match actor.state {
+ ActorState::Idle(_) => "Pensando",
+ ActorState::Waiting { .. } => "Esperando",
+ ActorState::Active(_) if actor.role == Role::Reader => "Leyendo",
+ ActorState::Active(_) => "Escribiendo",
+ };
+ println!("│ {:12} │ {:12} │ {:8} │ {:10} │", actor.label(), state, actor.accesses, actor.max_wait);
+ }
+ println!("└──────────────┴──────────────┴──────────┴────────────┘");
+ }
+
+ /// Resumen final por rol
+ pub fn display_summary(&self) {
+ println!("\n╔═══════════════════════════════════════════╗");
+ println!("║ RESUMEN ({}, {} ticks)", self.policy.name(), self.tick);
+ println!("╠═══════════════════════════════════════════╣");
+ for (role, name) in [(Role::Reader, "Lectores"), (Role::Writer, "Escritores")] {
+ let s = self.summary(role);
+ println!("║ {}: {} accesos, espera media {:.2}, máxima {}", name, s.accesses, s.avg_wait, s.max_wait);
+ if s.starved > 0 {
+ println!("║ [WARN] {} {} sin acceder: inanición", s.starved, name.to_lowercase());
+ }
+ }
+ println!("╚═══════════════════════════════════════════╝");
+ }
+}
+
+/// Comparar las tres políticas con la misma carga y semilla
+pub fn compare_policies(readers: usize, writers: usize, steps: usize, seed: u64) {
+ println!("\n╔══════════════════╦═════════════════════════════╦═════════════════════════════╗");
+ println!("║ ║ Lectores ║ Escritores ║");
+ println!("║ Política ║ Accesos │ Media │ Máxima ║ Accesos │ Media │ Máxima ║");
+ println!("╠══════════════════╬═════════════════════════════╬═════════════════════════════╣");
+ for policy in RwPolicy::ALL {
+ let mut sim = ReadersWriters::new(readers, writers, policy, seed);
+ sim.set_verbose(false);
+ for _ in 0..steps {
+ sim.step();
+ }
+ let (r, w) = (sim.summary(Role::Reader), sim.summary(Role::Writer));
+ println!("║ {:16} ║ {:7} │ {:7.2} │ {:7} ║ {:7} │ {:7.2} │ {:7} ║",
+ policy.name(), r.accesses, r.avg_wait, r.max_wait, w.accesses, w.avg_wait, w.max_wait);
+ }
+ println!("╚══════════════════╩═════════════════════════════╩═════════════════════════════╝");
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ fn run(policy: RwPolicy) -> ReadersWriters {
+ let mut sim = ReadersWriters::new(5, 2, policy, 42);
+ sim.set_verbose(false);
+ for _ in 0..200 {
+ sim.step();
+ // Nunca un escritor junto a otro actor
+ let writers = sim.actors.iter().filter(|a| a.role == Role::Writer && matches!(a.state, ActorState::Active(_))).count();
+ let readers = sim.actors.iter().filter(|a| a.role == Role::Reader && matches!(a.state, ActorState::Active(_))).count();
+ assert!(writers == 0 || (writers == 1 && readers == 0));
+ }
+ sim
+ }
+
+ #[test]
+ fn test_policies_and_starvation() {
+ // Con lectores que se solapan, la preferencia de lectores hace esperar mucho a los escritores
+ let readers_first = run(RwPolicy::ReaderPreference);
+ let writers_first = run(RwPolicy::WriterPreference);
+ let fair = run(RwPolicy::Fair);
+ let writer_wait = |sim: &ReadersWriters| sim.summary(Role::Writer).max_wait;
+ assert!(writer_wait(&readers_first) > 2 * writer_wait(&fair));
+ assert!(writer_wait(&writers_first) <= writer_wait(&fair));
+
+ // Y al revés: dar paso a los escritores alarga la espera de los lectores
+ let reader_wait = |sim: &ReadersWriters| sim.summary(Role::Reader).max_wait;
+ assert!(reader_wait(&writers_first) > reader_wait(&readers_first));
+ assert!(fair.summary(Role::Writer).starved == 0 && fair.summary(Role::Reader).starved == 0);
+ }
+}
@@ -0,0 +1,247 @@
+//! Fumadores de cigarrillos con semáforos (solución con "pushers"): el agente deja
+//! dos ingredientes, un pusher por ingrediente anota lo que hay en la mesa y
+//! despierta solo al fumador que tiene el que falta
+
+use super::sync::Semaphore;
+use crate::rng::XorShift;
+
+/// Ticks que tarda un fumador en liar y fumar
+pub const SMOKE_TICKS: u32 = 2;
+
+pub const INGREDIENTS: [&str; 3] = ["tabaco", "papel", "cerillas"];
+
+/// pid del agente; los pushers son 1..=3 y los fumadores 4..=6
+const AGENT: u32 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ActorState {
+ Ready, // Pasó su wait o lo despertaron
+ Blocked,
+ Smoking(u32),
+}
+
+/// Simulación de los fumadores por ticks
+pub struct CigaretteSmokers {
+ agent_sem: Semaphore, // La mesa está libre
+ ingredients: Vec<Semaphore>, // El agente deja el ingrediente i
+ smoker_sems: Vec<Semaphore>, // Despierta al fumador que tiene el ingrediente i
+ on_table: [bool; 3], // Lo que los pushers ya vieron en la mesa
+ agent: ActorState,
+ pushers: [ActorState; 3],
+ smokers: [ActorState; 3],
+ rng: XorShift,
+ tick: u64,
+ rounds: u32,
+ missing: usize, // Ingrediente que faltó en la última ronda
+ placed_at: u64,
+ cigarettes: [u32; 3],
+ wrong_smoker: u32,
+ total_wait: u64,
+ max_wait: u64,
+}
+
+impl CigaretteSmokers {
+ pub fn new(seed: u64) -> Self {
+ let mut agent_sem = Semaphore::new(1, "agent");
+ let mut ingredients: Vec<Semaphore> = INGREDIENTS.iter().map(|name| Semaphore::new(0, name)).collect();
+ let mut smoker_sems: Vec<Semaphore> = INGREDIENTS.iter().map(|name| Semaphore::new(0, &format!("smoker_{}", name))).collect();
+
+ // Todos hacen su primer wait: el agente pasa y el resto se bloquea
+ agent_sem.wait(AGENT);
+ for i in 0..3 {
+ ingredients[i].wait(Self::pusher_pid(i));
+ smoker_sems[i].wait(Self::smoker_pid(i));
+ }
+
+ Self {
+ agent_sem,
+ ingredients,
+ smoker_sems,
+ on_table: [false; 3],
+ agent: ActorState::Ready,
+ pushers: [ActorState::Blocked; 3],
+ smokers: [ActorState::Blocked; 3],
+ rng: XorShift::new(seed),
+ tick: 0,
+ rounds: 0,
+ missing: 0,
+ placed_at: 0,
+ cigarettes: [0; 3],
+ wrong_smoker: 0,
+ total_wait: 0,
+ max_wait: 0,
+ }
+ }
+
+ fn pusher_pid(i: usize) -> u32 {
+ 1 + i as u32
+ }
+
+ fn smoker_pid(i: usize) -> u32 {
+ 4 + i as u32
+ }
+
+ /// Marcar como listo al actor que despertó un signal
+ fn wake(&mut self, pid: Option<u32>) {
+ match pid {
+ Some(AGENT) => self.agent = ActorState::Ready,
+ Some(pid @ 1..=3) => self.pushers[pid as usize - 1] = ActorState::Ready,
+ Some(pid) => self.smokers[pid as usize - 4] = ActorState::Ready,
+ None => {}
+ }
+ }
+
+ /// Avanzar un tick; los 7 actores actúan en un orden aleatorio con semilla
+ pub fn step(&mut self) {
+ self.tick += 1;
+ let mut order: Vec<u32> = (0..7).collect();
+ for i in (1..order.len()).rev() {
+ let j = self.rng.next_range(i + 1);
+ order.swap(i, j);
+ }
+ for pid in order {
+ match pid {
+ AGENT => self.agent_act(),
+ 1..=3 => self.pusher_act(pid as usize - 1),
+ _ => self.smoker_act(pid as usize - 4),
+ }
+ }
+ }
+
+ fn agent_act(&mut self) {
+ if self.agent != ActorState::Ready {
+ return;
+ }
+ self.missing = self.rng.next_range(3);
+ self.rounds += 1;
+ self.placed_at = self.tick;
+ let placed: Vec<usize> = (0..3).filter(|i| *i != self.missing).collect();
+ println!("[Agente] Ronda {}: deja {} y {}", self.rounds, INGREDIENTS[placed[0]], INGREDIENTS[placed[1]]);
+ for i in placed {
+ let woken = self.ingredients[i].signal();
+ self.wake(woken);
+ }
+ if !self.agent_sem.wait(AGENT) {
+ self.agent = ActorState::Blocked;
+ }
+ }
+
+ fn pusher_act(&mut self, i: usize) {
+ if self.pushers[i] != ActorState::Ready {
+ return;
+ }
+ // Si ya estaba el otro ingrediente, avisar al fumador que tiene el tercero
+ match (0..3).find(|j| *j != i && self.on_table[*j]) {
+ Some(j) => {
+ self.on_table[j] = false;
+ let smoker = 3 - i - j;
+ println!("[Pusher {}] Hay {} y {}: avisa al fumador con {}",
+ INGREDIENTS[i], INGREDIENTS[i], INGREDIENTS[j], INGREDIENTS[smoker]);
+ let woken = self.smoker_sems[smoker].signal();
+ self.wake(woken);
+ }
+ None => {
+ self.on_table[i] = true;
+ println!("[Pusher {}] Anota {} en la mesa", INGREDIENTS[i], INGREDIENTS[i]);
+ }
+ }
+ if !self.ingredients[i].wait(Self::pusher_pid(i)) {
+ self.pushers[i] = ActorState::Blocked;
+ }
+ }
+
+ fn smoker_act(&mut self, i: usize) {
+ match self.smokers[i] {
+ ActorState::Ready => {
+ let wait = self.tick - self.placed_at;
+ self.total_wait += wait;
+ self.max_wait = self.max_wait.max(wait);
+ self.cigarettes[i] += 1;
+ if i != self.missing {
+ self.wrong_smoker += 1;
+ }
+ self.smokers[i] = ActorState::Smoking(SMOKE_TICKS);
+ println!("[Fumador con {}] Lía y fuma (cigarrillo #{})", INGREDIENTS[i], self.cigarettes[i]);
+ }
+ ActorState::Smoking(left) if left > 1 => self.smokers[i] = ActorState::Smoking(left - 1),
+ ActorState::Smoking(_) => {
+ println!("[Fumador con {}] Termina y avisa al agente", INGREDIENTS[i]);
+ let woken = self.agent_sem.signal();
+ self.wake(woken);
+ self.smokers[i] = if self.smoker_sems[i].wait(Self::smoker_pid(i)) { ActorState::Ready } else { ActorState::Blocked };
+ }
+ ActorState::Blocked => {}
+ }
+ }
+
+ /// Simular varios ticks
+ pub fn simulate(&mut self, steps: usize) {
+ println!("\n╔═══════════════════════════════════════════════════╗");
+ println!("║ SIMULACIÓN: FUMADORES DE CIGARRILLOS ║");
+ println!("║ 1 agente, 3 pushers, 3 fumadores ║");
+ println!("╚═══════════════════════════════════════════════════╝");
+
+ for _ in 0..steps {
+ println!("\n--- Tick {} ---", self.tick + 1);
+ self.step();
+ }
+
+ self.display_summary();
+ }
+
+ pub fn rounds(&self) -> u32 {
+ self.rounds
+ }
+
+ pub fn cigarettes(&self) -> [u32; 3] {
+ self.cigarettes
+ }
+
+ /// Resumen final
+ pub fn display_summary(&self) {
+ let smoked: u32 = self.cigarettes.iter().sum();
+ let avg_wait = if smoked > 0 { self.total_wait as f64 / smoked as f64 } else { 0.0 };
+
+ println!("\n╔═══════════════════════════════════════════╗");
+ println!("║ RESUMEN FINAL ║");
+ println!("╠═══════════════════════════════════════════╣");
+ println!("║ Ticks simulados: {:6} ║", self.tick);
+ println!("║ Rondas del agente: {:6} ║", self.rounds);
+ println!("║ Cigarrillos fumados: {:6} ║", smoked);
+ println!("╠═══════════════════════════════════════════╣");
+ for (i, name) in INGREDIENTS.iter().enumerate() {
+ println!("║ Fumador con {}: {} cigarrillos ║", name, self.cigarettes[i]);
+ }
+ println!("╠═══════════════════════════════════════════╣");
+ println!("║ Espera desde que el agente deja: media {:.2}, máx {} ║", avg_wait, self.max_wait);
+
+ if self.wrong_smoker == 0 {
+ println!("║ [OK] Siempre fumó quien tenía lo que faltaba ║");
+ } else {
+ println!("║ [ERROR] {} rondas las tomó el fumador equivocado ║", self.wrong_smoker);
+ }
+ if self.cigarettes.contains(&0) {
+ println!("║ [WARN] Algún fumador nunca fumó ║");
+ }
+
+ println!("╚═══════════════════════════════════════════╝");
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_each_round_goes_to_the_right_smoker() {
+ let mut table = CigaretteSmokers::new(42);
+ for _ in 0..200 {
+ table.step();
+ // Nunca quedan más de una ronda sin fumar
+ let smoked: u32 = table.cigarettes().iter().sum();
+ assert!(table.rounds() - smoked <= 1);
+ }
+ assert_eq!(table.wrong_smoker, 0);
+ assert!(table.cigarettes().iter().all(|c| *c > 0));
+ }
+}
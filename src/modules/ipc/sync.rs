@@ -53,6 +53,224 @@ impl Semaphore {
  pub fn waiting_count(&self) -> usize {
  self.waiting_queue.len()
  }
+
+ pub fn name(&self) -> &str {
+ &self.name
+ }
+
+ pub fn waiting(&self) -> impl Iterator<Item = &u32> {
+ self.waiting_queue.iter()
+ }
+
+ pub fn is_waiting(&self, pid: u32) -> bool {
+ self.waiting_queue.contains(&pid)
+ }
+
+ /// Quitar un proceso de la cola devolviendo la unidad que descontó su wait
+ pub fn remove_waiter(&mut self, pid: u32) -> bool {
+ let before = self.waiting_queue.len();
+ self.waiting_queue.retain(|p| *p != pid);
+ let removed = self.waiting_queue.len() < before;
+ if removed {
+ self.count += 1;
+ }
+ removed
+ }
+}
+
+/// Protocolo contra la inversión de prioridades
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MutexProtocol {
+ None,
+ Inheritance, // El dueño hereda la prioridad del waiter más prioritario
+ Ceiling(u8), // El dueño sube al techo mientras lo tiene
+}
+
+/// Mutex con dueño: solo quien lo tomó puede liberarlo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mutex {
+ name: String,
+ owner: Option<u32>,
+ depth: u32, // Veces que el dueño lo tomó (modo recursivo)
+ recursive: bool,
+ protocol: MutexProtocol,
+ waiters: Vec<(u32, u8)>, // (pid, prioridad) en orden de llegada
+ contentions: u64,
+}
+
+impl Mutex {
+ pub fn new(name: &str, recursive: bool, protocol: MutexProtocol) -> Self {
+ Self {
+ name: name.to_string(),
+ owner: None,
+ depth: 0,
+ recursive,
+ protocol,
+ waiters: Vec::new(),
+ contentions: 0,
+ }
+ }
+
+ /// Tomar el mutex; devuelve false si el proceso queda esperando
+ pub fn lock(&mut self, pid: u32, priority: u8) -> Result<bool, String> {
+ if let MutexProtocol::Ceiling(ceiling) = self.protocol {
+ if priority > ceiling {
+ return Err(format!("Prioridad {} del proceso {} supera el techo {} de '{}'", priority, pid, ceiling, self.name));
+ }
+ }
+
+ match self.owner {
+ None => {
+ self.owner = Some(pid);
+ self.depth = 1;
+ println!("[Mutex {}] Proceso {} lo tomó", self.name, pid);
+ Ok(true)
+ }
+ Some(owner) if owner == pid => {
+ if !self.recursive {
+ return Err(format!("Proceso {} ya posee '{}' (no recursivo): se bloquearía a sí mismo", pid, self.name));
+ }
+ self.depth += 1;
+ println!("[Mutex {}] Proceso {} lo retomó (profundidad {})", self.name, pid, self.depth);
+ Ok(true)
+ }
+ Some(owner) => {
+ if self.waiters.iter().any(|(p, _)| *p == pid) {
+ return Err(format!("Proceso {} ya espera en '{}'", pid, self.name));
+ }
+ self.waiters.push((pid, priority));
+ self.contentions += 1;
+ println!("[Mutex {}] Proceso {} bloqueado (dueño: {})", self.name, pid, owner);
+ Ok(false)
+ }
+ }
+ }
+
+ /// Liberar; devuelve el nuevo dueño si se entregó a un waiter
+ pub fn unlock(&mut self, pid: u32) -> Result<Option<u32>, String> {
+ if self.owner != Some(pid) {
+ return Err(match self.owner {
+ Some(owner) => format!("Proceso {} no es dueño de '{}' (dueño: {})", pid, self.name, owner),
+ None => format!("'{}' no está tomado", self.name),
+ });
+ }
+
+ self.depth -= 1;
+ if self.depth > 0 {
+ println!("[Mutex {}] Proceso {} lo soltó una vez (profundidad {})", self.name, pid, self.depth);
+ return Ok(None);
+ }
+ Ok(self.hand_off())
+ }
+
+ /// Entregar el mutex al waiter de mayor prioridad (FIFO entre iguales)
+ fn hand_off(&mut self) -> Option<u32> {
+ let best = self.waiters.iter().map(|(_, prio)| *prio).max();
+ let next = best
+ .and_then(|best| self.waiters.iter().position(|(_, prio)| *prio == best))
+ .map(|idx| self.waiters.remove(idx).0);
+
+ self.owner = next;
+ self.depth = if next.is_some() { 1 } else { 0 };
+ match next {
+ Some(pid) => println!("[Mutex {}] Entregado al proceso {}", self.name, pid),
+ None => println!("[Mutex {}] Libre", self.name),
+ }
+ next
+ }
+
+ /// Liberar por completo el mutex de un dueño que terminó
+ pub fn abandon(&mut self, pid: u32) -> Option<u32> {
+ if self.owner != Some(pid) {
+ return None;
+ }
+ self.hand_off()
+ }
+
+ /// Prioridad que el mutex impone a su dueño según el protocolo
+ pub fn imposed_priority(&self) -> Option<u8> {
+ self.owner?;
+ match self.protocol {
+ MutexProtocol::None => None,
+ MutexProtocol::Inheritance => self.waiters.iter().map(|(_, prio)| *prio).max(),
+ MutexProtocol::Ceiling(ceiling) => Some(ceiling),
+ }
+ }
+
+ /// Actualizar la prioridad de un waiter (por herencia transitiva)
+ pub fn update_waiter(&mut self, pid: u32, priority: u8) {
+ if let Some(waiter) = self.waiters.iter_mut().find(|(p, _)| *p == pid) {
+ waiter.1 = priority;
+ }
+ }
+
+ pub fn remove_waiter(&mut self, pid: u32) -> bool {
+ let before = self.waiters.len();
+ self.waiters.retain(|(p, _)| *p != pid);
+ self.waiters.len() < before
+ }
+
+ pub fn name(&self) -> &str {
+ &self.name
+ }
+
+ pub fn owner(&self) -> Option<u32> {
+ self.owner
+ }
+
+ pub fn depth(&self) -> u32 {
+ self.depth
+ }
+
+ pub fn is_recursive(&self) -> bool {
+ self.recursive
+ }
+
+ pub fn protocol(&self) -> MutexProtocol {
+ self.protocol
+ }
+
+ pub fn waiters(&self) -> &[(u32, u8)] {
+ &self.waiters
+ }
+
+ pub fn is_waiting(&self, pid: u32) -> bool {
+ self.waiters.iter().any(|(p, _)| *p == pid)
+ }
+
+ pub fn contentions(&self) -> u64 {
+ self.contentions
+ }
+}
+
+/// Operación de un proceso sobre el buffer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BufferOp {
+ Produce(String),
+ Consume,
+}
+
+/// Operación detenida en `empty` o `full` hasta que la despierte un signal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(super) struct PendingOp {
+ pub(super) pid: u32,
+ pub(super) op: BufferOp,
+}
+
+/// Resultado de una operación para un proceso
+#[derive(Debug, Clone, PartialEq)]
+pub enum BufferResult {
+ Produced(String),
+ Consumed(String),
+ Blocked,
+}
+
+/// Resultado de produce/consume: el del llamador y los de los procesos que despertó
+#[derive(Debug, Clone)]
+pub struct BufferReport {
+ pub result: BufferResult,
+ pub resumed: Vec<(u32, BufferResult)>, // Procesos despertados que completaron su operación
+ pub woken: Vec<u32>, // Despertados que aún deben reintentar (monitor Mesa)
 }
 
 /// Buffer compartido para productor-consumidor
@@ -63,6 +281,8 @@ pub struct ProducerConsumerBuffer {
  mutex: Semaphore,
  empty: Semaphore,
  full: Semaphore,
+ #[serde(default)]
+ pending: Vec<PendingOp>,
  total_produced: u64,
  total_consumed: u64,
 }
@@ -75,81 +295,109 @@ impl ProducerConsumerBuffer {
  mutex: Semaphore::new(1, "mutex"),
  empty: Semaphore::new(capacity as i32, "empty"),
  full: Semaphore::new(0, "full"),
+ pending: Vec::new(),
  total_produced: 0,
  total_consumed: 0,
  }
  }
 
- /// Producir un item (bloquea si buffer lleno)
- pub fn produce(&mut self, item: String, producer_pid: u32) -> Result<(), String> {
+ /// Producir un item; si el buffer está lleno el productor queda bloqueado en `empty`
+ /// y el item se agrega cuando un consumidor lo despierta
+ pub fn produce(&mut self, item: String, producer_pid: u32) -> Result<BufferReport, String> {
  println!("\n[Productor {}] Intentando producir '{}'", producer_pid, item);
- 
- // wait(empty) - ¿Hay espacio?
- if !self.empty.wait(producer_pid) {
- return Err(format!("Productor {} bloqueado: buffer lleno", producer_pid));
+ self.start(producer_pid, BufferOp::Produce(item))
  }
 
- // wait(mutex) - Entrar a sección crítica
- if !self.mutex.wait(producer_pid) {
- return Err(format!("Productor {} bloqueado en mutex", producer_pid));
+ /// Consumir un item; si el buffer está vacío el consumidor queda bloqueado en `full`
+ pub fn consume(&mut self, consumer_pid: u32) -> Result<BufferReport, String> {
+ println!("\n[Consumidor {}] Intentando consumir", consumer_pid);
+ self.start(consumer_pid, BufferOp::Consume)
  }
 
- // SECCIÓN CRÍTICA: Agregar al buffer
- if self.buffer.len() >= self.capacity {
- self.mutex.signal();
- self.empty.signal();
- return Err("Buffer lleno (no debería pasar)".to_string());
+ fn start(&mut self, pid: u32, op: BufferOp) -> Result<BufferReport, String> {
+ if self.is_pending(pid) {
+ return Err(format!("Proceso {} ya está bloqueado en el buffer", pid));
  }
 
- self.buffer.push_back(item.clone());
- self.total_produced += 1;
- println!("[Productor {}] Item '{}' producido. Buffer: {}/{}", 
- producer_pid, item, self.buffer.len(), self.capacity);
+ // wait(empty) para producir, wait(full) para consumir
+ let passed = match op {
+ BufferOp::Produce(_) => self.empty.wait(pid),
+ BufferOp::Consume => self.full.wait(pid),
+ };
+ if !passed {
+ self.pending.push(PendingOp { pid, op });
+ return Ok(BufferReport { result: BufferResult::Blocked, resumed: Vec::new(), woken: Vec::new() });
+ }
 
- // signal(mutex) - Salir de sección crítica
- self.mutex.signal();
+ let (result, mut woken) = self.complete(pid, op)?;
 
- // signal(full) - Indicar que hay un item disponible
- if let Some(consumer) = self.full.signal() {
- println!("[Productor {}] Despertó al consumidor {}", producer_pid, consumer);
+ // Cada proceso despertado termina su operación, que puede despertar a otro
+ let mut resumed = Vec::new();
+ while let Some(next) = woken {
+ let Some(idx) = self.pending.iter().position(|p| p.pid == next) else {
+ break;
+ };
+ let pending = self.pending.remove(idx);
+ let (next_result, next_woken) = self.complete(pending.pid, pending.op)?;
+ resumed.push((pending.pid, next_result));
+ woken = next_woken;
  }
 
- Ok(())
+ Ok(BufferReport { result, resumed, woken: Vec::new() })
  }
 
- /// Consumir un item (bloquea si buffer vacío)
- pub fn consume(&mut self, consumer_pid: u32) -> Result<String, String> {
- println!("\n[Consumidor {}] Intentando consumir", consumer_pid);
+ /// Sección crítica de una operación que ya pasó empty/full; devuelve el pid despertado
+ fn complete(&mut self, pid: u32, op: BufferOp) -> Result<(BufferResult, Option<u32>), String> {
+ // wait(mutex) - Entrar a sección crítica (se libera dentro de la misma operación)
+ self.mutex.wait(pid);
 
- // wait(full) - ¿Hay items?
- if !self.full.wait(consumer_pid) {
- return Err(format!("Consumidor {} bloqueado: buffer vacío", consumer_pid));
- }
+ match op {
+ BufferOp::Produce(item) => {
+ self.buffer.push_back(item.clone());
+ self.total_produced += 1;
+ println!("[Productor {}] Item '{}' producido. Buffer: {}/{}",
+ pid, item, self.buffer.len(), self.capacity);
+ self.mutex.signal();
 
- // wait(mutex) - Entrar a sección crítica
- if !self.mutex.wait(consumer_pid) {
- return Err(format!("Consumidor {} bloqueado en mutex", consumer_pid));
+ // signal(full) - Indicar que hay un item disponible
+ let woken = self.full.signal();
+ if let Some(consumer) = woken {
+ println!("[Productor {}] Despertó al consumidor {}", pid, consumer);
  }
-
- // SECCIÓN CRÍTICA: Extraer del buffer
- if let Some(item) = self.buffer.pop_front() {
+ Ok((BufferResult::Produced(item), woken))
+ }
+ BufferOp::Consume => {
+ let Some(item) = self.buffer.pop_front() else {
+ self.mutex.signal();
+ return Err("Buffer vacío (no debería pasar)".to_string());
+ };
  self.total_consumed += 1;
- println!("[Consumidor {}] Item '{}' consumido. Buffer: {}/{}", 
- consumer_pid, item, self.buffer.len(), self.capacity);
-
- // signal(mutex)
+ println!("[Consumidor {}] Item '{}' consumido. Buffer: {}/{}",
+ pid, item, self.buffer.len(), self.capacity);
  self.mutex.signal();
 
  // signal(empty) - Indicar que hay espacio disponible
- if let Some(producer) = self.empty.signal() {
- println!("[Consumidor {}] Despertó al productor {}", consumer_pid, producer);
+ let woken = self.empty.signal();
+ if let Some(producer) = woken {
+ println!("[Consumidor {}] Despertó al productor {}", pid, producer);
+ }
+ Ok((BufferResult::Consumed(item), woken))
+ }
+ }
  }
 
- Ok(item)
- } else {
- self.mutex.signal();
- self.full.signal();
- Err("Buffer vacío (no debería pasar)".to_string())
+ /// ¿El proceso espera en el buffer?
+ pub fn is_pending(&self, pid: u32) -> bool {
+ self.pending.iter().any(|p| p.pid == pid)
+ }
+
+ /// Descartar la operación de un proceso que terminó mientras esperaba
+ pub fn cancel(&mut self, pid: u32) {
+ if let Some(idx) = self.pending.iter().position(|p| p.pid == pid) {
+ match self.pending.remove(idx).op {
+ BufferOp::Produce(_) => self.empty.remove_waiter(pid),
+ BufferOp::Consume => self.full.remove_waiter(pid),
+ };
  }
  }
 
@@ -166,6 +414,13 @@ impl ProducerConsumerBuffer {
  println!("║ mutex: count={:3} waiting={} ║", self.mutex.count(), self.mutex.waiting_count());
  println!("║ empty: count={:3} waiting={} ║", self.empty.count(), self.empty.waiting_count());
  println!("║ full: count={:3} waiting={} ║", self.full.count(), self.full.waiting_count());
+ for pending in &self.pending {
+ let op = match &pending.op {
+ BufferOp::Produce(item) => format!("producir '{}'", item),
+ BufferOp::Consume => "consumir".to_string(),
+ };
+ println!("║ PID {} bloqueado: {}", pending.pid, op);
+ }
  println!("╠══════════════════════════════════════════════╣");
  println!("║ Contenido del buffer: ║");
  
@@ -195,6 +450,29 @@ mod tests {
  assert_eq!(unblocked, Some(2));
  }
 
+ #[test]
+ fn test_mutex_ownership_and_inheritance() {
+ let mut mutex = Mutex::new("m", true, MutexProtocol::Inheritance);
+ assert!(mutex.lock(1, 1).unwrap());
+ assert!(mutex.lock(1, 1).unwrap()); // Recursivo
+ assert!(mutex.unlock(2).is_err()); // Solo el dueño libera
+
+ assert!(!mutex.lock(2, 3).unwrap());
+ assert!(!mutex.lock(3, 9).unwrap());
+ assert_eq!(mutex.imposed_priority(), Some(9));
+
+ assert_eq!(mutex.unlock(1).unwrap(), None);
+ // Se entrega al waiter más prioritario aunque llegó después
+ assert_eq!(mutex.unlock(1).unwrap(), Some(3));
+ assert_eq!(mutex.imposed_priority(), Some(3));
+
+ let mut ceiling = Mutex::new("c", false, MutexProtocol::Ceiling(5));
+ assert!(ceiling.lock(1, 7).is_err());
+ assert!(ceiling.lock(1, 2).unwrap());
+ assert!(ceiling.lock(1, 2).is_err()); // No recursivo
+ assert_eq!(ceiling.imposed_priority(), Some(5));
+ }
+
  #[test]
  fn test_producer_consumer() {
  let mut buffer = ProducerConsumerBuffer::new(2);
@@ -204,8 +482,8 @@ mod tests {
  assert!(buffer.produce("Item2".to_string(), 100).is_ok());
  
  // Consumir 1 item
- let item = buffer.consume(200).unwrap();
- assert_eq!(item, "Item1");
+ let report = buffer.consume(200).unwrap();
+ assert_eq!(report.result, BufferResult::Consumed("Item1".to_string()));
  
  // Producir otro (ahora hay espacio)
  assert!(buffer.produce("Item3".to_string(), 100).is_ok());
@@ -213,4 +491,29 @@ mod tests {
  assert_eq!(buffer.total_produced, 3);
  assert_eq!(buffer.total_consumed, 1);
  }
+
+ #[test]
+ fn test_blocked_operations_resume() {
+ let mut buffer = ProducerConsumerBuffer::new(1);
+
+ // Dos consumidores esperan en full sin que el contador se pierda
+ assert_eq!(buffer.consume(200).unwrap().result, BufferResult::Blocked);
+ assert_eq!(buffer.consume(201).unwrap().result, BufferResult::Blocked);
+ assert_eq!(buffer.full.count(), -2);
+
+ // Cada item despierta a un consumidor, que lo retira
+ let report = buffer.produce("A".to_string(), 100).unwrap();
+ assert_eq!(report.resumed, vec![(200, BufferResult::Consumed("A".to_string()))]);
+
+ // Con el buffer lleno, el productor espera y completa al liberarse espacio
+ buffer.cancel(201);
+ buffer.produce("B".to_string(), 100).unwrap();
+ assert_eq!(buffer.produce("C".to_string(), 101).unwrap().result, BufferResult::Blocked);
+ let report = buffer.consume(202).unwrap();
+ assert_eq!(report.result, BufferResult::Consumed("B".to_string()));
+ assert_eq!(report.resumed, vec![(101, BufferResult::Produced("C".to_string()))]);
+
+ assert_eq!((buffer.empty.count(), buffer.full.count(), buffer.mutex.count()), (0, 1, 1));
+ assert!(buffer.pending.is_empty());
+ }
 }
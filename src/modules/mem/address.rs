@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Estructura de la tabla de páginas usada para traducir direcciones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PageTableKind {
+ MultiLevel, // Jerárquica de 1, 2 o 3 niveles
+ Inverted, // Una entrada por marco físico (global)
+ Hashed, // Tabla hash con cadenas por bucket
+}
+
+/// Configuración del espacio de direcciones virtuales
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AddressingConfig {
+ pub page_size: usize, // Bytes por página (potencia de 2)
+ pub address_bits: u32, // Ancho de la dirección virtual
+ pub levels: u32, // Niveles de la tabla jerárquica (1-3)
+ pub pte_size: usize, // Bytes por entrada de tabla
+ pub kind: PageTableKind,
+ pub hash_buckets: usize, // Solo para tabla hash
+}
+
+impl Default for AddressingConfig {
+ fn default() -> Self {
+ Self {
+ page_size: 4096,
+ address_bits: 32,
+ levels: 1,
+ pte_size: 4,
+ kind: PageTableKind::MultiLevel,
+ hash_buckets: 64,
+ }
+ }
+}
+
+impl AddressingConfig {
+ /// Crear configuración validando sus parámetros
+ pub fn new(page_size: usize, address_bits: u32, levels: u32, kind: PageTableKind) -> Result<Self, String> {
+ if !page_size.is_power_of_two() {
+ return Err(format!("El tamaño de página {} no es potencia de 2", page_size));
+ }
+ if !(1..=3).contains(&levels) {
+ return Err(format!("Niveles de tabla inválidos: {} (usa 1, 2 o 3)", levels));
+ }
+
+ let config = Self {
+ page_size,
+ address_bits,
+ levels,
+ kind,
+ ..Self::default()
+ };
+
+ if address_bits > 64 || address_bits < config.offset_bits() + levels {
+ return Err(format!("Ancho de dirección inválido: {} bits para páginas de {} bytes", address_bits, page_size));
+ }
+ // Con una tabla de un nivel el índice ocupa todo el número de página: debe caber en usize
+ if config.page_number_bits() >= usize::BITS {
+ return Err(format!("Número de página de {} bits: no cabe en un índice de {} bits", config.page_number_bits(), usize::BITS));
+ }
+
+ Ok(config)
+ }
+
+ /// Bits de desplazamiento dentro de la página
+ pub fn offset_bits(&self) -> u32 {
+ self.page_size.trailing_zeros()
+ }
+
+ /// Bits del número de página
+ pub fn page_number_bits(&self) -> u32 {
+ self.address_bits - self.offset_bits()
+ }
+
+ /// Bits de índice por nivel (el primer nivel absorbe el resto)
+ pub fn level_bits(&self) -> Vec<u32> {
+ let bits = self.page_number_bits();
+ let base = bits / self.levels;
+ let rem = bits % self.levels;
+ (0..self.levels).map(|l| if l == 0 { base + rem } else { base }).collect()
+ }
+
+ /// Dividir dirección virtual en (número de página, desplazamiento)
+ pub fn split(&self, vaddr: u64) -> Result<(usize, usize), String> {
+ if self.address_bits < 64 && vaddr >> self.address_bits != 0 {
+ return Err(format!("Dirección {:#x} fuera del espacio de {} bits", vaddr, self.address_bits));
+ }
+ let page = (vaddr >> self.offset_bits()) as usize;
+ let offset = (vaddr & (self.page_size as u64 - 1)) as usize;
+ Ok((page, offset))
+ }
+
+ /// Índices de cada nivel para un número de página
+ pub fn level_indices(&self, page_num: usize) -> Vec<usize> {
+ let bits = self.level_bits();
+ let mut remaining: u32 = bits.iter().sum();
+ bits.iter()
+ .map(|b| {
+ remaining -= b;
+ (page_num >> remaining) & ((1usize << b) - 1)
+ })
+ .collect()
+ }
+
+ /// Bytes que ocupa una entrada de tabla invertida (PID + página + PTE)
+ pub fn inverted_entry_size(&self) -> usize {
+ 4 + self.pte_size * 2
+ }
+
+ /// Bytes de un nodo de tabla hash (etiqueta + PTE + siguiente)
+ pub fn hashed_entry_size(&self) -> usize {
+ self.pte_size * 3
+ }
+
+ /// Bucket de la tabla hash para (pid, página)
+ pub fn hash_bucket(&self, pid: u32, page_num: usize) -> usize {
+ (page_num ^ (pid as usize).wrapping_mul(31)) % self.hash_buckets
+ }
+}
+
+/// Resultado de traducir una dirección virtual
+#[derive(Debug, Clone)]
+pub struct Translation {
+ pub vaddr: u64,
+ pub page_num: usize,
+ pub offset: usize,
+ pub frame_num: usize,
+ pub physical_address: u64,
+ pub page_fault: bool,
+ pub steps: Vec<String>,
+}
+
+/// Memoria ocupada por las tablas de páginas
+#[derive(Debug, Clone)]
+pub struct TableOverhead {
+ pub label: String,
+ pub tables: usize,
+ pub bytes: usize,
+}
+
+/// Overhead de una tabla jerárquica con las páginas dadas
+/// Solo se reservan las tablas internas que cubren páginas referenciadas
+pub fn multilevel_overhead(config: &AddressingConfig, levels: u32, pages: &[usize]) -> TableOverhead {
+ let config = AddressingConfig { levels, ..*config };
+ let bits = config.level_bits();
+ let mut tables = 0;
+ let mut bytes = 0;
+
+ for (level, b) in bits.iter().enumerate() {
+ let count = if level == 0 {
+ 1
+ } else {
+ pages.iter()
+ .map(|p| config.level_indices(*p)[..level].to_vec())
+ .collect::<HashSet<_>>()
+ .len()
+ };
+ tables += count;
+ bytes += count.saturating_mul(1usize << b).saturating_mul(config.pte_size);
+ }
+
+ TableOverhead {
+ label: format!("Jerárquica {} nivel(es)", levels),
+ tables,
+ bytes,
+ }
+}
+
+/// Overhead de la tabla invertida (una sola para todo el sistema)
+pub fn inverted_overhead(config: &AddressingConfig, num_frames: usize) -> TableOverhead {
+ TableOverhead {
+ label: "Invertida (global)".to_string(),
+ tables: 1,
+ bytes: num_frames * config.inverted_entry_size(),
+ }
+}
+
+/// Overhead de la tabla hash (buckets + un nodo por página residente)
+pub fn hashed_overhead(config: &AddressingConfig, resident_pages: usize) -> TableOverhead {
+ TableOverhead {
+ label: format!("Hash ({} buckets)", config.hash_buckets),
+ tables: 1,
+ bytes: config.hash_buckets * config.pte_size + resident_pages * config.hashed_entry_size(),
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_split_and_level_indices() {
+ let config = AddressingConfig::new(4096, 32, 2, PageTableKind::MultiLevel).unwrap();
+ let (page, offset) = config.split(0x1a2b3).unwrap();
+ assert_eq!(page, 0x1a);
+ assert_eq!(offset, 0x2b3);
+ assert_eq!(config.level_bits(), vec![10, 10]);
+ assert_eq!(config.level_indices(0x00401), vec![1, 1]);
+
+ let three = AddressingConfig::new(4096, 32, 3, PageTableKind::MultiLevel).unwrap();
+ assert_eq!(three.level_bits(), vec![8, 6, 6]);
+ }
+
+ #[test]
+ fn test_invalid_config() {
+ assert!(AddressingConfig::new(3000, 32, 1, PageTableKind::MultiLevel).is_err());
+ assert!(AddressingConfig::new(4096, 32, 4, PageTableKind::MultiLevel).is_err());
+ assert!(AddressingConfig::new(4096, 12, 1, PageTableKind::MultiLevel).is_err());
+ // 64 bits de número de página desbordarían el desplazamiento de los índices
+ assert!(AddressingConfig::new(1, 64, 1, PageTableKind::MultiLevel).is_err());
+ let wide = AddressingConfig::new(2, 64, 1, PageTableKind::MultiLevel).unwrap();
+ assert_eq!(wide.level_indices(5), vec![5]);
+ assert_eq!(multilevel_overhead(&wide, 1, &[5]).bytes, usize::MAX);
+ }
+
+ #[test]
+ fn test_multilevel_overhead_smaller_than_flat() {
+ let config = AddressingConfig::default();
+ let pages = vec![0, 1, 2, 0x400];
+
+ let flat = multilevel_overhead(&config, 1, &pages);
+ let two = multilevel_overhead(&config, 2, &pages);
+
+ // Plana: 2^20 entradas de 4 bytes
+ assert_eq!(flat.bytes, 4 * 1024 * 1024);
+ // Dos niveles: directorio + 2 tablas internas de 1024 entradas
+ assert_eq!(two.tables, 3);
+ assert_eq!(two.bytes, 3 * 1024 * 4);
+ }
+}
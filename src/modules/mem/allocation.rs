@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Política de asignación de marcos por proceso
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameAllocationPolicy {
+ #[default]
+ Equal, // m / n marcos por proceso
+ Proportional, // Proporcional a memory_req
+}
+
+/// Alcance del reemplazo de páginas
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplacementScope {
+ #[default]
+ Global, // La víctima puede ser de cualquier proceso
+ Local, // La víctima sale de los marcos del propio proceso
+}
+
+/// Calcular cuántos marcos recibe cada proceso (pid, memory_req)
+/// Cada proceso recibe al menos un marco mientras alcancen
+pub fn compute_allocation(policy: FrameAllocationPolicy, total_frames: usize, processes: &[(u32, usize)]) -> HashMap<u32, usize> {
+ let mut quotas = HashMap::new();
+ if processes.is_empty() || total_frames == 0 {
+ return quotas;
+ }
+
+ let n = processes.len();
+ match policy {
+ FrameAllocationPolicy::Equal => {
+ for (i, (pid, _)) in processes.iter().enumerate() {
+ let extra = if i < total_frames % n { 1 } else { 0 };
+ quotas.insert(*pid, total_frames / n + extra);
+ }
+ }
+ FrameAllocationPolicy::Proportional => {
+ let total_req: usize = processes.iter().map(|(_, req)| (*req).max(1)).sum();
+ let mut assigned = 0;
+ for (pid, req) in processes {
+ let share = ((*req).max(1) * total_frames / total_req).max(1);
+ quotas.insert(*pid, share);
+ assigned += share;
+ }
+
+ // Repartir el sobrante a los procesos más grandes
+ let mut by_size: Vec<_> = processes.to_vec();
+ by_size.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+ for (pid, _) in by_size.iter().cycle().take(total_frames.saturating_sub(assigned)) {
+ *quotas.get_mut(pid).unwrap() += 1;
+ }
+
+ // El mínimo de un marco puede pasarse del total si hay más procesos que marcos:
+ // se devuelve desde la cuota más grande (a igualdad, la del proceso más pequeño)
+ while assigned > total_frames {
+ let Some((pid, _)) = processes.iter()
+ .filter(|(pid, _)| quotas[pid] > 0)
+ .max_by_key(|(pid, req)| (quotas[pid], std::cmp::Reverse(*req), *pid))
+ else {
+ break;
+ };
+ *quotas.get_mut(pid).unwrap() -= 1;
+ assigned -= 1;
+ }
+ }
+ }
+
+ quotas
+}
+
+/// Control de Page Fault Frequency (tasas entre 0 y 1)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PffConfig {
+ pub enabled: bool,
+ pub lower: f64, // Por debajo: se quita un marco
+ pub upper: f64, // Por encima: se agrega un marco
+ pub window: u64, // Ticks entre ajustes
+}
+
+impl Default for PffConfig {
+ fn default() -> Self {
+ Self {
+ enabled: false,
+ lower: 0.1,
+ upper: 0.5,
+ window: 10,
+ }
+ }
+}
+
+/// Contadores de accesos y fallos de un proceso
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProcessMemStats {
+ pub accesses: u64,
+ pub faults: u64,
+ pub window_accesses: u64, // Desde el último ajuste PFF
+ pub window_faults: u64,
+}
+
+impl ProcessMemStats {
+ pub fn record(&mut self, fault: bool) {
+ self.accesses += 1;
+ self.window_accesses += 1;
+ if fault {
+ self.faults += 1;
+ self.window_faults += 1;
+ }
+ }
+
+ pub fn fault_rate(&self) -> f64 {
+ if self.accesses > 0 {
+ self.faults as f64 / self.accesses as f64
+ } else {
+ 0.0
+ }
+ }
+
+ pub fn window_fault_rate(&self) -> f64 {
+ if self.window_accesses > 0 {
+ self.window_faults as f64 / self.window_accesses as f64
+ } else {
+ 0.0
+ }
+ }
+
+ pub fn reset_window(&mut self) {
+ self.window_accesses = 0;
+ self.window_faults = 0;
+ }
+}
+
+/// Umbrales para detectar hiperpaginación (thrashing)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ThrashingConfig {
+ pub enabled: bool,
+ pub window: u64, // Ticks por ventana de medición
+ pub fault_rate: f64, // Tasa de fallos mínima para sospechar
+ pub cpu_utilization: f64, // Uso de CPU máximo para confirmar
+}
+
+impl Default for ThrashingConfig {
+ fn default() -> Self {
+ Self {
+ enabled: false,
+ window: 10,
+ fault_rate: 0.5,
+ cpu_utilization: 0.5,
+ }
+ }
+}
+
+/// Resultado de evaluar una ventana de medición
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThrashingVerdict {
+ Thrashing { fault_rate: f64, cpu_utilization: f64 },
+ Recovered { fault_rate: f64, cpu_utilization: f64 },
+ Normal { fault_rate: f64, cpu_utilization: f64 },
+}
+
+/// Monitor de thrashing: mide tasa de fallos y uso de CPU por ventana
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThrashingMonitor {
+ pub config: ThrashingConfig,
+ ticks: u64,
+ busy_ticks: u64,
+ faults_mark: u64,
+ accesses_mark: u64,
+ pub suspended: Vec<u32>, // Procesos suspendidos por thrashing
+ pub detections: u64,
+}
+
+impl ThrashingMonitor {
+ pub fn new(config: ThrashingConfig) -> Self {
+ Self {
+ config,
+ ..Self::default()
+ }
+ }
+
+ /// Registrar un tick de CPU (ocupada o inactiva)
+ pub fn record_tick(&mut self, busy: bool) {
+ self.ticks += 1;
+ if busy {
+ self.busy_ticks += 1;
+ }
+ }
+
+ pub fn window_complete(&self) -> bool {
+ self.config.enabled && self.ticks >= self.config.window.max(1)
+ }
+
+ /// Evaluar la ventana actual con los contadores globales de memoria
+ pub fn evaluate(&mut self, total_faults: u64, total_accesses: u64) -> ThrashingVerdict {
+ let faults = total_faults.saturating_sub(self.faults_mark);
+ let accesses = total_accesses.saturating_sub(self.accesses_mark);
+ let fault_rate = if accesses > 0 { faults as f64 / accesses as f64 } else { 0.0 };
+ let cpu_utilization = if self.ticks > 0 { self.busy_ticks as f64 / self.ticks as f64 } else { 0.0 };
+
+ self.ticks = 0;
+ self.busy_ticks = 0;
+ self.faults_mark = total_faults;
+ self.accesses_mark = total_accesses;
+
+ if fault_rate >= self.config.fault_rate && cpu_utilization < self.config.cpu_utilization {
+ self.detections += 1;
+ ThrashingVerdict::Thrashing { fault_rate, cpu_utilization }
+ } else if !self.suspended.is_empty() && fault_rate < self.config.fault_rate / 2.0 {
+ ThrashingVerdict::Recovered { fault_rate, cpu_utilization }
+ } else {
+ ThrashingVerdict::Normal { fault_rate, cpu_utilization }
+ }
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_equal_and_proportional_allocation() {
+ let procs = vec![(1, 10), (2, 30), (3, 60)];
+
+ let equal = compute_allocation(FrameAllocationPolicy::Equal, 10, &procs);
+ assert_eq!(equal[&1], 4);
+ assert_eq!(equal[&2], 3);
+ assert_eq!(equal[&3], 3);
+
+ let prop = compute_allocation(FrameAllocationPolicy::Proportional, 10, &procs);
+ assert_eq!(prop[&1], 1);
+ assert_eq!(prop[&2], 3);
+ assert_eq!(prop[&3], 6);
+ assert_eq!(prop.values().sum::<usize>(), 10);
+ }
+
+ #[test]
+ fn test_proportional_with_more_processes_than_frames() {
+ let procs: Vec<(u32, usize)> = (1..=10).map(|pid| (pid, pid as usize * 10)).collect();
+ let prop = compute_allocation(FrameAllocationPolicy::Proportional, 4, &procs);
+ assert_eq!(prop.values().sum::<usize>(), 4);
+ // Se quedan sin marco los procesos más pequeños
+ assert!((7..=10).all(|pid| prop[&pid] == 1));
+ assert!((1..=6).all(|pid| prop[&pid] == 0));
+ }
+
+ #[test]
+ fn test_thrashing_detection() {
+ let mut monitor = ThrashingMonitor::new(ThrashingConfig {
+ enabled: true,
+ window: 4,
+ fault_rate: 0.5,
+ cpu_utilization: 0.5,
+ });
+
+ // CPU casi siempre inactiva esperando fallos
+ monitor.record_tick(true);
+ for _ in 0..3 {
+ monitor.record_tick(false);
+ }
+ assert!(monitor.window_complete());
+ assert!(matches!(monitor.evaluate(8, 10), ThrashingVerdict::Thrashing { .. }));
+
+ // Ventana siguiente: pocos fallos nuevos
+ monitor.suspended.push(1);
+ for _ in 0..4 {
+ monitor.record_tick(true);
+ }
+ assert!(matches!(monitor.evaluate(9, 30), ThrashingVerdict::Recovered { .. }));
+ }
+}
@@ -1,8 +1,12 @@
-/// Buddy System Allocator
-/// Sistema de asignación de memoria con bloques de tamaño potencia de 2
+//! Buddy System Allocator
+//! Sistema de asignación de memoria con bloques de tamaño potencia de 2
+//! Los bloques libres se guardan en una lista por orden (tamaño = min << orden)
+//! ordenada por dirección, así asignar y liberar cuesta O(log n)
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+use crate::rng::XorShift;
 
 /// Estado de un bloque
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -19,6 +23,10 @@ pub struct Block {
  pub size: usize,
  pub state: BlockState,
  pub pid: Option<u32>,
+ #[serde(default)]
+ pub requested: usize, // Bytes pedidos (antes de redondear)
+ #[serde(default)]
+ pub allocated_at: u64, // Tiempo de simulación de la asignación
 }
 
 /// Buddy System Allocator
@@ -26,8 +34,10 @@ pub struct Block {
 pub struct BuddyAllocator {
  total_size: usize,
  min_block_size: usize,
- blocks: Vec<Block>,
+ free_lists: Vec<BTreeSet<usize>>, // orden -> direcciones de bloques libres
  allocated_blocks: HashMap<usize, Block>, // address -> block
+ #[serde(default)]
+ clock: u64, // Tiempo que se anota en las nuevas asignaciones
  
  // Métricas
  total_allocations: u64,
@@ -37,161 +47,216 @@ pub struct BuddyAllocator {
 
 impl BuddyAllocator {
  /// Crear un nuevo allocador
- /// total_size debe ser potencia de 2
- pub fn new(total_size: usize, min_block_size: usize) -> Self {
- // Crear bloque inicial libre
- let initial_block = Block {
- address: 0,
- size: total_size,
- state: BlockState::Free,
- pid: None,
- };
+ /// total_size y min_block_size deben ser potencias de 2
+ pub fn new(total_size: usize, min_block_size: usize) -> Result<Self, String> {
+ if !total_size.is_power_of_two() {
+ return Err(format!("El tamaño del heap {} no es potencia de 2", total_size));
+ }
+ if !min_block_size.is_power_of_two() || min_block_size > total_size {
+ return Err(format!("Bloque mínimo inválido: {} (potencia de 2 y <= {})", min_block_size, total_size));
+ }
 
- Self {
+ // Un único bloque libre del orden máximo
+ let max_order = (total_size / min_block_size).trailing_zeros() as usize;
+ let mut free_lists = vec![BTreeSet::new(); max_order + 1];
+ free_lists[max_order].insert(0);
+
+ Ok(Self {
  total_size,
  min_block_size,
- blocks: vec![initial_block],
+ free_lists,
  allocated_blocks: HashMap::new(),
+ clock: 0,
  total_allocations: 0,
  total_deallocations: 0,
  internal_fragmentation: 0,
- }
+ })
  }
 
- /// Asignar memoria para un proceso
- pub fn alloc(&mut self, pid: u32, size: usize) -> Result<usize, String> {
- // Encontrar el tamaño de bloque más pequeño que pueda contener size
- let required_size = self.next_power_of_2(size.max(self.min_block_size));
+ pub fn total_size(&self) -> usize {
+ self.total_size
+ }
 
- // Buscar un bloque libre del tamaño adecuado
- if let Some(block_idx) = self.find_free_block(required_size) {
- let block = self.blocks[block_idx].clone();
- 
- // Si el bloque es más grande, dividirlo
- if block.size > required_size {
- self.split_block(block_idx, required_size);
+ pub fn min_block_size(&self) -> usize {
+ self.min_block_size
  }
 
- // Marcar como asignado
- self.blocks[block_idx].state = BlockState::Allocated;
- self.blocks[block_idx].pid = Some(pid);
- 
- let address = self.blocks[block_idx].address;
- self.allocated_blocks.insert(address, self.blocks[block_idx].clone());
- 
- self.total_allocations += 1;
- self.internal_fragmentation += required_size - size;
+ /// Fijar el tiempo de simulación para las próximas asignaciones
+ pub fn set_clock(&mut self, now: u64) {
+ self.clock = now;
+ }
 
- println!("[Buddy] Asignado {} bytes (redondeado a {}) en dirección {} para proceso {}", 
- size, required_size, address, pid);
+ /// Bloques asignados a un proceso, por dirección
+ pub fn blocks_of(&self, pid: u32) -> Vec<Block> {
+ let mut blocks: Vec<Block> = self.allocated_blocks.values()
+ .filter(|b| b.pid == Some(pid))
+ .cloned()
+ .collect();
+ blocks.sort_by_key(|b| b.address);
+ blocks
+ }
 
- Ok(address)
- } else {
- Err(format!("No hay bloques libres suficientemente grandes para {} bytes", size))
+ /// Liberar (sin imprimir) todos los bloques de un proceso; devuelve los liberados
+ pub fn free_pid(&mut self, pid: u32) -> Vec<Block> {
+ let blocks = self.blocks_of(pid);
+ for block in &blocks {
+ let _ = self.try_free(block.address);
  }
+ blocks
  }
 
- /// Liberar memoria
- pub fn free(&mut self, address: usize) -> Result<(), String> {
- if let Some(block) = self.allocated_blocks.remove(&address) {
- // Encontrar el bloque en la lista
- if let Some(idx) = self.blocks.iter().position(|b| b.address == address) {
- self.blocks[idx].state = BlockState::Free;
- self.blocks[idx].pid = None;
- 
- // Intentar fusionar con buddies
- self.coalesce(idx);
- 
- self.total_deallocations += 1;
- 
- println!("[Buddy] Liberado bloque en dirección {} (tamaño: {})", address, block.size);
- 
- Ok(())
- } else {
- Err(format!("Bloque en dirección {} no encontrado en lista", address))
- }
- } else {
- Err(format!("No hay bloque asignado en dirección {}", address))
+ /// Orden de un bloque de tamaño `size`
+ fn order(&self, size: usize) -> usize {
+ (size / self.min_block_size).trailing_zeros() as usize
  }
+
+ fn block_size(&self, order: usize) -> usize {
+ self.min_block_size << order
  }
 
- /// Encontrar un bloque libre del tamaño especificado
- fn find_free_block(&self, size: usize) -> Option<usize> {
- self.blocks.iter()
- .position(|b| b.state == BlockState::Free && b.size >= size)
+ /// Cambiar el tamaño de un bloque asignado
+ /// Encoge o crece en el lugar si los buddies necesarios están libres; si no, lo reubica
+ pub fn realloc(&mut self, address: usize, new_size: usize) -> Result<usize, String> {
+ let block = self.allocated_blocks.get(&address)
+ .ok_or_else(|| format!("No hay bloque asignado en dirección {}", address))?;
+ let pid = block.pid.unwrap_or(0);
+ let allocated_at = block.allocated_at;
+ let current = block.size;
+ let required = self.next_power_of_2(new_size.max(self.min_block_size));
+
+ if required <= current {
+ // Encoger: la mitad superior pasa a ser un buddy libre (mismo límite de 2·mínimo que alloc)
+ let mut size = current;
+ while size > required && size > self.min_block_size * 2 {
+ size /= 2;
+ let order = self.order(size);
+ self.free_lists[order].insert(address + size);
+ }
+ let block = self.allocated_blocks.get_mut(&address).unwrap();
+ block.size = size;
+ block.requested = new_size;
+ self.internal_fragmentation += required - new_size;
+ println!("[Buddy] Bloque {} reducido en el lugar: {} -> {} bytes", address, current, size);
+ return Ok(address);
  }
 
- /// Dividir un bloque en dos buddies
- fn split_block(&mut self, idx: usize, target_size: usize) {
- let block = self.blocks[idx].clone();
- 
- if block.size <= target_size || block.size <= self.min_block_size * 2 {
- return;
+ // Crecer en el lugar: el bloque debe ser la mitad inferior y su buddy estar libre
+ let mut size = current;
+ while size < required && address.is_multiple_of(size * 2) && self.free_lists[self.order(size)].contains(&(address + size)) {
+ size *= 2;
  }
 
- let half_size = block.size / 2;
- 
- // Marcar bloque original como dividido
- self.blocks[idx].state = BlockState::Split;
- self.blocks[idx].size = half_size;
-
- // Crear buddy
- let buddy = Block {
- address: block.address + half_size,
- size: half_size,
- state: BlockState::Free,
- pid: None,
- };
+ if size >= required {
+ let mut size = current;
+ while size < required {
+ let order = self.order(size);
+ self.free_lists[order].remove(&(address + size));
+ size *= 2;
+ }
+ let block = self.allocated_blocks.get_mut(&address).unwrap();
+ block.size = required;
+ block.requested = new_size;
+ self.internal_fragmentation += required - new_size;
+ println!("[Buddy] Bloque {} ampliado en el lugar: {} -> {} bytes", address, current, required);
+ return Ok(address);
+ }
 
- self.blocks.insert(idx + 1, buddy);
+ // Sin buddies libres: asignar un bloque nuevo y liberar el anterior
+ // El bloque reubicado conserva el momento de la asignación original
+ let new_address = self.alloc(pid, new_size)?;
+ self.free(address)?;
+ self.allocated_blocks.get_mut(&new_address).unwrap().allocated_at = allocated_at;
+ println!("[Buddy] Bloque reubicado: {} -> {}", address, new_address);
+ Ok(new_address)
+ }
 
- // Recursivamente dividir si es necesario
- if half_size > target_size {
- self.split_block(idx, target_size);
- } else {
- // Cambiar de Split a Free si ya es del tamaño correcto
- self.blocks[idx].state = BlockState::Free;
+ /// Bloque libre más grande
+ pub fn largest_free_block(&self) -> usize {
+ self.free_lists.iter()
+ .rposition(|list| !list.is_empty())
+ .map(|order| self.block_size(order))
+ .unwrap_or(0)
  }
+
+ /// Asignar memoria para un proceso
+ pub fn alloc(&mut self, pid: u32, size: usize) -> Result<usize, String> {
+ let (address, required_size) = self.try_alloc(pid, size)?;
+ println!("[Buddy] Asignado {} bytes (redondeado a {}) en dirección {} para proceso {}", 
+ size, required_size, address, pid);
+ Ok(address)
  }
 
- /// Fusionar bloques buddy libres
- fn coalesce(&mut self, idx: usize) {
- if idx >= self.blocks.len() {
- return;
+ /// Asignar sin imprimir; devuelve (dirección, tamaño redondeado)
+ pub fn try_alloc(&mut self, pid: u32, size: usize) -> Result<(usize, usize), String> {
+ // Encontrar el tamaño de bloque más pequeño que pueda contener size
+ let required_size = self.next_power_of_2(size.max(self.min_block_size));
+
+ // El bloque libre de menor dirección que alcance (igual que recorrer el heap en orden)
+ let (order, address) = self.find_free_block(required_size)
+ .ok_or_else(|| format!("No hay bloques libres suficientemente grandes para {} bytes", size))?;
+ self.free_lists[order].remove(&address);
+
+ // Dividir: la mitad superior vuelve a su lista; no se generan bloques menores a 2·mínimo
+ let mut block_size = self.block_size(order);
+ while block_size > required_size && block_size > self.min_block_size * 2 {
+ block_size /= 2;
+ let half_order = self.order(block_size);
+ self.free_lists[half_order].insert(address + block_size);
  }
 
- let block = self.blocks[idx].clone();
- 
- // Buscar el buddy
- let buddy_address = self.get_buddy_address(block.address, block.size);
- 
- if let Some(buddy_idx) = self.blocks.iter().position(|b| b.address == buddy_address && b.size == block.size) {
- if self.blocks[buddy_idx].state == BlockState::Free {
- // Ambos buddies están libres, fusionar
- let merged_address = block.address.min(buddy_address);
- let merged_size = block.size * 2;
-
- // Remover ambos bloques
- let remove_first = idx.min(buddy_idx);
- let remove_second = idx.max(buddy_idx);
- 
- self.blocks.remove(remove_second);
- self.blocks.remove(remove_first);
+ self.allocated_blocks.insert(address, Block {
+ address,
+ size: block_size,
+ state: BlockState::Allocated,
+ pid: Some(pid),
+ requested: size,
+ allocated_at: self.clock,
+ });
+ self.total_allocations += 1;
+ self.internal_fragmentation += required_size - size;
 
- // Crear bloque fusionado
- let merged_block = Block {
- address: merged_address,
- size: merged_size,
- state: BlockState::Free,
- pid: None,
- };
+ Ok((address, required_size))
+ }
 
- self.blocks.insert(remove_first, merged_block);
+ /// Liberar memoria
+ pub fn free(&mut self, address: usize) -> Result<(), String> {
+ let size = self.try_free(address)?;
+ println!("[Buddy] Liberado bloque en dirección {} (tamaño: {})", address, size);
+ Ok(())
+ }
 
- // Recursivamente intentar fusionar más
- self.coalesce(remove_first);
+ /// Liberar sin imprimir; devuelve el tamaño del bloque
+ pub fn try_free(&mut self, address: usize) -> Result<usize, String> {
+ let block = self.allocated_blocks.remove(&address)
+ .ok_or_else(|| format!("No hay bloque asignado en dirección {}", address))?;
+
+ // Fusionar con buddies libres mientras se pueda
+ let mut merged_address = block.address;
+ let mut merged_size = block.size;
+ while merged_size < self.total_size {
+ let buddy = self.get_buddy_address(merged_address, merged_size);
+ let order = self.order(merged_size);
+ if !self.free_lists[order].remove(&buddy) {
+ break;
  }
+ merged_address = merged_address.min(buddy);
+ merged_size *= 2;
  }
+ let order = self.order(merged_size);
+ self.free_lists[order].insert(merged_address);
+
+ self.total_deallocations += 1;
+ Ok(block.size)
+ }
+
+ /// Bloque libre de menor dirección con tamaño >= size: (orden, dirección)
+ fn find_free_block(&self, size: usize) -> Option<(usize, usize)> {
+ let first_order = self.order(size);
+ self.free_lists.iter()
+ .enumerate()
+ .skip(first_order)
+ .filter_map(|(order, list)| list.first().map(|addr| (order, *addr)))
+ .min_by_key(|(_, addr)| *addr)
  }
 
  /// Calcular dirección del buddy
@@ -208,63 +273,73 @@ impl BuddyAllocator {
  power
  }
 
+ /// Todos los bloques (libres y asignados) en orden de dirección
+ fn blocks(&self) -> Vec<Block> {
+ let mut blocks: Vec<Block> = self.free_lists.iter()
+ .enumerate()
+ .flat_map(|(order, list)| list.iter().map(move |addr| (order, *addr)))
+ .map(|(order, address)| Block {
+ address,
+ size: self.block_size(order),
+ state: BlockState::Free,
+ pid: None,
+ requested: 0,
+ allocated_at: 0,
+ })
+ .chain(self.allocated_blocks.values().cloned())
+ .collect();
+ blocks.sort_by_key(|b| b.address);
+ blocks
+ }
+
  /// Obtener métricas
  pub fn stats(&self) -> BuddyStats {
- let free_blocks = self.blocks.iter().filter(|b| b.state == BlockState::Free).count();
- let allocated_blocks = self.blocks.iter().filter(|b| b.state == BlockState::Allocated).count();
- let total_free = self.blocks.iter()
- .filter(|b| b.state == BlockState::Free)
- .map(|b| b.size)
- .sum();
- let total_allocated = self.blocks.iter()
- .filter(|b| b.state == BlockState::Allocated)
- .map(|b| b.size)
+ let free_blocks = self.free_lists.iter().map(|l| l.len()).sum();
+ let total_free = self.free_lists.iter()
+ .enumerate()
+ .map(|(order, list)| list.len() * self.block_size(order))
  .sum();
+ let total_allocated = self.allocated_blocks.values().map(|b| b.size).sum();
 
  BuddyStats {
  total_size: self.total_size,
  free_memory: total_free,
  allocated_memory: total_allocated,
  free_blocks,
- allocated_blocks,
+ allocated_blocks: self.allocated_blocks.len(),
  total_allocations: self.total_allocations,
  total_deallocations: self.total_deallocations,
  internal_fragmentation: self.internal_fragmentation,
- external_fragmentation: self.calculate_external_fragmentation(),
+ external_fragmentation: self.calculate_external_fragmentation(total_free),
  }
  }
 
  /// Calcular fragmentación externa
- fn calculate_external_fragmentation(&self) -> f64 {
- let free_blocks: Vec<_> = self.blocks.iter()
- .filter(|b| b.state == BlockState::Free)
- .collect();
- 
- if free_blocks.is_empty() {
- return 0.0;
- }
-
- let total_free: usize = free_blocks.iter().map(|b| b.size).sum();
- let largest_free = free_blocks.iter().map(|b| b.size).max().unwrap_or(0);
- 
+ fn calculate_external_fragmentation(&self, total_free: usize) -> f64 {
  if total_free == 0 {
  0.0
  } else {
- (1.0 - (largest_free as f64 / total_free as f64)) * 100.0
+ (1.0 - (self.largest_free_block() as f64 / total_free as f64)) * 100.0
  }
  }
 
  /// Visualizar estado del allocador
  pub fn display(&self) {
+ self.display_zone("BUDDY ALLOCATOR - ESTADO ACTUAL", 0);
+ }
+
+ /// Visualizar con direcciones desplazadas a la base de la zona
+ pub fn display_zone(&self, title: &str, base: usize) {
+ let blocks = self.blocks();
  println!("\n╔════════════════════════════════════════════════════╗");
- println!("║ BUDDY ALLOCATOR - ESTADO ACTUAL ║");
+ println!("║ {} ", title);
  println!("╠════════════════════════════════════════════════════╣");
  println!("║ Tamaño total: {} bytes ", self.total_size);
  println!("║ Tamaño mínimo de bloque: {} bytes ", self.min_block_size);
- println!("║ Bloques totales: {} ", self.blocks.len());
+ println!("║ Bloques totales: {} ", blocks.len());
  println!("╠════════════════════════════════════════════════════╣");
  
- for block in &self.blocks {
+ for block in &blocks {
  let state_str = match block.state {
  BlockState::Free => "Libre ",
  BlockState::Allocated => "Asignado ",
@@ -278,13 +353,66 @@ impl BuddyAllocator {
  };
  
  println!("║ [{:#06x}] {} bytes | {} | {} ", 
- block.address, block.size, state_str, pid_str);
+ base + block.address, block.size, state_str, pid_str);
  }
  
  println!("╚════════════════════════════════════════════════════╝");
  }
 }
 
+/// Resultado del modo benchmark
+#[derive(Debug, Clone)]
+pub struct BuddyBench {
+ pub operations: u64,
+ pub allocations: u64,
+ pub frees: u64,
+ pub failures: u64,
+ pub elapsed: Duration,
+ pub final_stats: BuddyStats,
+}
+
+impl BuddyBench {
+ pub fn ops_per_second(&self) -> f64 {
+ self.operations as f64 / self.elapsed.as_secs_f64().max(1e-9)
+ }
+}
+
+/// Ejecutar `operations` alloc/free aleatorios (sin imprimir) y medir el throughput
+/// Los tamaños van de 1 byte a `max_request`; se libera con probabilidad ~1/2
+pub fn bench(total_size: usize, min_block_size: usize, max_request: usize, operations: u64, seed: u64) -> Result<BuddyBench, String> {
+ let mut allocator = BuddyAllocator::new(total_size, min_block_size)?;
+ let mut rng = XorShift::new(seed);
+ let mut live: Vec<usize> = Vec::new();
+ let (mut allocations, mut frees, mut failures) = (0, 0, 0);
+
+ let start = Instant::now();
+ for _ in 0..operations {
+ if !live.is_empty() && rng.next_range(2) == 0 {
+ let address = live.swap_remove(rng.next_range(live.len()));
+ allocator.try_free(address)?;
+ frees += 1;
+ } else {
+ match allocator.try_alloc(1, 1 + rng.next_range(max_request)) {
+ Ok((address, _)) => {
+ live.push(address);
+ allocations += 1;
+ }
+ Err(_) => failures += 1,
+ }
+ }
+ }
+ let elapsed = start.elapsed();
+
+ Ok(BuddyBench {
+ operations,
+ allocations,
+ frees,
+ failures,
+ elapsed,
+ final_stats: allocator.stats(),
+ })
+}
+
 #[derive(Debug, Clone)]
 pub struct BuddyStats {
  pub total_size: usize,
@@ -304,7 +432,7 @@ mod tests {
 
  #[test]
  fn test_buddy_alloc_free() {
- let mut allocator = BuddyAllocator::new(1024, 64);
+ let mut allocator = BuddyAllocator::new(1024, 64).unwrap();
  
  // Asignar 100 bytes (redondeado a 128)
  let addr1 = allocator.alloc(1, 100).unwrap();
@@ -322,7 +450,7 @@ mod tests {
 
  #[test]
  fn test_buddy_coalescing() {
- let mut allocator = BuddyAllocator::new(1024, 64);
+ let mut allocator = BuddyAllocator::new(1024, 64).unwrap();
  
  let addr1 = allocator.alloc(1, 64).unwrap();
  let addr2 = allocator.alloc(2, 64).unwrap();
@@ -335,4 +463,68 @@ mod tests {
  // Después de fusionar, debería haber menos bloques
  assert!(stats.free_blocks < 3);
  }
+
+ #[test]
+ fn test_buddy_validation_and_realloc() {
+ assert!(BuddyAllocator::new(3000, 64).is_err());
+ assert!(BuddyAllocator::new(1024, 48).is_err());
+
+ let mut allocator = BuddyAllocator::new(1024, 64).unwrap();
+ let addr = allocator.alloc(1, 100).unwrap(); // 128 en [0, 128)
+
+ // El buddy [128, 256) y luego [256, 512) están libres: crece en el lugar
+ assert_eq!(allocator.realloc(addr, 400).unwrap(), addr);
+ assert_eq!(allocator.stats().allocated_memory, 512);
+
+ // Encoger deja libres las mitades superiores, sin bajar de 2·mínimo como alloc
+ assert_eq!(allocator.realloc(addr, 50).unwrap(), addr);
+ assert_eq!(allocator.stats().allocated_memory, 128);
+ assert_eq!(allocator.stats().internal_fragmentation, 28 + 112 + 14);
+
+ // Con el buddy ocupado hay que reubicar; se conserva el momento de la asignación
+ let other = allocator.alloc(2, 64).unwrap();
+ assert_eq!(other, 128);
+ allocator.set_clock(9);
+ let moved = allocator.realloc(addr, 200).unwrap();
+ assert_ne!(moved, addr);
+ assert_eq!(allocator.blocks_of(1)[0].allocated_at, 0);
+ }
+
+ #[test]
+ fn test_random_trace_keeps_invariants() {
+ let mut allocator = BuddyAllocator::new(1 << 16, 64).unwrap();
+ let mut rng = XorShift::new(7);
+ let mut live = Vec::new();
+
+ for _ in 0..5000 {
+ if !live.is_empty() && rng.next_range(2) == 0 {
+ allocator.try_free(live.swap_remove(rng.next_range(live.len()))).unwrap();
+ } else if let Ok((address, _)) = allocator.try_alloc(1, 1 + rng.next_range(2000)) {
+ live.push(address);
+ }
+
+ // Los bloques cubren el heap sin solaparse
+ let blocks = allocator.blocks();
+ let mut next = 0;
+ for block in &blocks {
+ assert_eq!(block.address, next);
+ assert_eq!(block.address % block.size, 0);
+ next += block.size;
+ }
+ assert_eq!(next, 1 << 16);
+ }
+
+ for address in live {
+ allocator.try_free(address).unwrap();
+ }
+ assert_eq!(allocator.stats().free_blocks, 1);
+ }
+
+ #[test]
+ fn test_bench_mode() {
+ let result = bench(1 << 20, 64, 4096, 10_000, 42).unwrap();
+ assert_eq!(result.operations, 10_000);
+ assert_eq!(result.allocations + result.failures + result.frees, 10_000);
+ assert!(result.ops_per_second() > 0.0);
+ }
 }
@@ -0,0 +1,284 @@
+//! Simulador de caché de CPU asociativa por conjuntos (L1 y L2 opcional)
+//! Clasifica los fallos en obligatorios, de capacidad y de conflicto (las 3C)
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use crate::rng::XorShift;
+
+/// Política de reemplazo dentro de un conjunto
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CacheReplacement {
+ Lru,
+ Fifo,
+ Random,
+}
+
+/// Geometría de un nivel de caché
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CacheConfig {
+ pub size: usize, // Bytes totales
+ pub line_size: usize, // Bytes por línea
+ pub associativity: usize, // Vías por conjunto
+ pub replacement: CacheReplacement,
+}
+
+impl CacheConfig {
+ pub fn lines(&self) -> usize {
+ self.size / self.line_size
+ }
+
+ pub fn sets(&self) -> usize {
+ self.lines() / self.associativity
+ }
+
+ fn validate(&self) -> Result<(), String> {
+ if !self.size.is_power_of_two() || !self.line_size.is_power_of_two() {
+ return Err(format!("Tamaño de caché ({}) y de línea ({}) deben ser potencias de 2", self.size, self.line_size));
+ }
+ if self.line_size > self.size {
+ return Err(format!("La línea de {} B no cabe en una caché de {} B", self.line_size, self.size));
+ }
+ if self.associativity == 0 || !self.lines().is_multiple_of(self.associativity) {
+ return Err(format!("Asociatividad {} inválida para {} líneas", self.associativity, self.lines()));
+ }
+ Ok(())
+ }
+}
+
+/// Contadores de un nivel
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+ pub accesses: u64,
+ pub hits: u64,
+ pub compulsory: u64, // Primera referencia a la línea
+ pub capacity: u64, // También fallaría en una caché totalmente asociativa
+ pub conflict: u64, // Fallo causado solo por la asociatividad
+}
+
+impl CacheStats {
+ pub fn misses(&self) -> u64 {
+ self.accesses - self.hits
+ }
+
+ pub fn hit_rate(&self) -> f64 {
+ if self.accesses == 0 {
+ 0.0
+ } else {
+ self.hits as f64 / self.accesses as f64 * 100.0
+ }
+ }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheLine {
+ tag: u64,
+ loaded_at: u64,
+ last_used: u64,
+}
+
+/// Caché LRU totalmente asociativa del mismo número de líneas (separa capacidad de conflicto)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ShadowCache {
+ capacity: usize,
+ stamps: HashMap<u64, u64>, // línea -> último uso
+ by_stamp: BTreeMap<u64, u64>, // último uso -> línea
+}
+
+impl ShadowCache {
+ /// Acceder a una línea; devuelve si estaba presente
+ fn access(&mut self, line: u64, now: u64) -> bool {
+ let hit = match self.stamps.insert(line, now) {
+ Some(old) => {
+ self.by_stamp.remove(&old);
+ true
+ }
+ None => false,
+ };
+ self.by_stamp.insert(now, line);
+ if self.stamps.len() > self.capacity {
+ if let Some((_, victim)) = self.by_stamp.pop_first() {
+ self.stamps.remove(&victim);
+ }
+ }
+ hit
+ }
+}
+
+/// Un nivel de caché asociativa por conjuntos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheLevel {
+ pub name: String,
+ pub config: CacheConfig,
+ sets: Vec<Vec<CacheLine>>,
+ clock: u64,
+ rng: XorShift,
+ seen: HashSet<u64>, // Líneas referenciadas alguna vez (acotado por memoria física / line_size)
+ shadow: ShadowCache,
+ stats: CacheStats,
+}
+
+impl CacheLevel {
+ pub fn new(name: &str, config: CacheConfig) -> Result<Self, String> {
+ config.validate().map_err(|e| format!("{}: {}", name, e))?;
+ Ok(Self {
+ name: name.to_string(),
+ config,
+ sets: vec![Vec::new(); config.sets()],
+ clock: 0,
+ rng: XorShift::new(42),
+ seen: HashSet::new(),
+ shadow: ShadowCache { capacity: config.lines(), ..ShadowCache::default() },
+ stats: CacheStats::default(),
+ })
+ }
+
+ pub fn stats(&self) -> &CacheStats {
+ &self.stats
+ }
+
+ /// Acceder a una dirección física; devuelve true si fue acierto
+ pub fn access(&mut self, address: u64) -> bool {
+ self.clock += 1;
+ self.stats.accesses += 1;
+
+ let line = address / self.config.line_size as u64;
+ let set_idx = (line % self.sets.len() as u64) as usize;
+ let tag = line / self.sets.len() as u64;
+ let in_shadow = self.shadow.access(line, self.clock);
+
+ let set = &mut self.sets[set_idx];
+ if let Some(entry) = set.iter_mut().find(|l| l.tag == tag) {
+ entry.last_used = self.clock;
+ self.stats.hits += 1;
+ return true;
+ }
+
+ if self.seen.insert(line) {
+ self.stats.compulsory += 1;
+ } else if !in_shadow {
+ self.stats.capacity += 1;
+ } else {
+ self.stats.conflict += 1;
+ }
+
+ let new_line = CacheLine { tag, loaded_at: self.clock, last_used: self.clock };
+ if set.len() < self.config.associativity {
+ set.push(new_line);
+ } else {
+ let victim = match self.config.replacement {
+ CacheReplacement::Lru => (0..set.len()).min_by_key(|i| set[*i].last_used).unwrap(),
+ CacheReplacement::Fifo => (0..set.len()).min_by_key(|i| set[*i].loaded_at).unwrap(),
+ CacheReplacement::Random => self.rng.next_range(set.len()),
+ };
+ set[victim] = new_line;
+ }
+ false
+ }
+
+ pub fn describe(&self) -> String {
+ format!("{}: {} B, líneas de {} B, {} vía(s), {} conjunto(s), {:?}",
+ self.name, self.config.size, self.config.line_size, self.config.associativity,
+ self.config.sets(), self.config.replacement)
+ }
+}
+
+/// Jerarquía L1 + L2 opcional; L2 solo ve los fallos de L1
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheHierarchy {
+ l1: CacheLevel,
+ l2: Option<CacheLevel>,
+ memory_accesses: u64,
+}
+
+impl CacheHierarchy {
+ pub fn new(l1: CacheConfig, l2: Option<CacheConfig>) -> Result<Self, String> {
+ Ok(Self {
+ l1: CacheLevel::new("L1", l1)?,
+ l2: l2.map(|config| CacheLevel::new("L2", config)).transpose()?,
+ memory_accesses: 0,
+ })
+ }
+
+ /// Acceder a una dirección; devuelve el nivel que la sirvió (1, 2 o 0 = memoria)
+ pub fn access(&mut self, address: u64) -> u8 {
+ if self.l1.access(address) {
+ return 1;
+ }
+ if let Some(l2) = self.l2.as_mut() {
+ if l2.access(address) {
+ return 2;
+ }
+ }
+ self.memory_accesses += 1;
+ 0
+ }
+
+ pub fn levels(&self) -> Vec<&CacheLevel> {
+ std::iter::once(&self.l1).chain(self.l2.as_ref()).collect()
+ }
+
+ pub fn display(&self) {
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ JERARQUÍA DE CACHÉ ║");
+ println!("╠════════════════════════════════════════════════════╣");
+ for level in self.levels() {
+ println!("║ {}", level.describe());
+ }
+ println!("╚════════════════════════════════════════════════════╝");
+
+ println!("╔═══════╦══════════╦══════════╦══════════╦══════════╦══════════╦══════════╗");
+ println!("║ Nivel ║ Accesos ║ Aciertos ║ % Acierto║ Oblig. ║ Capacidad║ Conflicto║");
+ println!("╠═══════╬══════════╬══════════╬══════════╬══════════╬══════════╬══════════╣");
+ for level in self.levels() {
+ let s = level.stats();
+ println!("║ {:5} ║ {:8} ║ {:8} ║ {:7.2}% ║ {:8} ║ {:8} ║ {:8} ║",
+ level.name, s.accesses, s.hits, s.hit_rate(), s.compulsory, s.capacity, s.conflict);
+ }
+ println!("╚═══════╩══════════╩══════════╩══════════╩══════════╩══════════╩══════════╝");
+ println!("Accesos que llegaron a memoria principal: {}", self.memory_accesses);
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ fn direct_mapped() -> CacheConfig {
+ CacheConfig { size: 64, line_size: 16, associativity: 1, replacement: CacheReplacement::Lru }
+ }
+
+ #[test]
+ fn test_miss_classification() {
+ let mut cache = CacheLevel::new("L1", direct_mapped()).unwrap();
+
+ // 0 y 64 compiten por el conjunto 0 aunque la caché tiene sitio
+ for address in [0, 64, 0, 64] {
+ cache.access(address);
+ }
+ assert_eq!((cache.stats().compulsory, cache.stats().conflict), (2, 2));
+
+ // La clasificación sobrevive al guardar el estado entre comandos
+ let mut cache: CacheLevel = serde_json::from_str(&serde_json::to_string(&cache).unwrap()).unwrap();
+ cache.access(0);
+ assert_eq!((cache.stats().compulsory, cache.stats().conflict), (2, 3));
+
+ // Recorrer 5 líneas en una caché de 4: el reuso falla por capacidad
+ let mut cache = CacheLevel::new("L1", direct_mapped()).unwrap();
+ for address in [0, 16, 32, 48, 64, 0] {
+ cache.access(address);
+ }
+ assert_eq!((cache.stats().compulsory, cache.stats().capacity), (5, 1));
+ }
+
+ #[test]
+ fn test_l2_serves_l1_misses() {
+ let l2 = CacheConfig { size: 256, line_size: 16, associativity: 4, replacement: CacheReplacement::Fifo };
+ let mut caches = CacheHierarchy::new(direct_mapped(), Some(l2)).unwrap();
+
+ assert_eq!(caches.access(0), 0);
+ assert_eq!(caches.access(64), 0);
+ assert_eq!(caches.access(0), 2);
+ assert_eq!(caches.access(0), 1);
+ assert!(CacheHierarchy::new(CacheConfig { associativity: 3, ..direct_mapped() }, None).is_err());
+ }
+}
@@ -0,0 +1,286 @@
+//! Asignador de particiones variables (memoria contigua)
+//! Estrategias first-fit, best-fit, worst-fit y next-fit con fusión de huecos y compactación
+
+use serde::{Deserialize, Serialize};
+use super::buddy::BuddyStats;
+
+/// Estrategia de ubicación de un bloque en los huecos libres
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FitStrategy {
+ First, // Primer hueco suficiente
+ Best, // Hueco más chico suficiente
+ Worst, // Hueco más grande
+ Next, // Como first-fit pero desde la última asignación
+}
+
+/// Partición: hueco libre o bloque asignado
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Partition {
+ pub address: usize,
+ pub size: usize,
+ pub pid: Option<u32>, // None = hueco
+ #[serde(default)]
+ pub allocated_at: u64,
+}
+
+/// Asignador contiguo con particiones ordenadas por dirección
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContiguousAllocator {
+ total_size: usize,
+ partitions: Vec<Partition>,
+ next_fit_cursor: usize, // Dirección donde retoma next-fit
+ #[serde(default)]
+ clock: u64,
+
+ // Métricas
+ total_allocations: u64,
+ total_deallocations: u64,
+ failed_allocations: u64,
+ compactions: u64,
+ bytes_moved: usize,
+}
+
+impl ContiguousAllocator {
+ pub fn new(total_size: usize) -> Self {
+ Self {
+ total_size,
+ partitions: vec![Partition { address: 0, size: total_size, pid: None, allocated_at: 0 }],
+ next_fit_cursor: 0,
+ clock: 0,
+ total_allocations: 0,
+ total_deallocations: 0,
+ failed_allocations: 0,
+ compactions: 0,
+ bytes_moved: 0,
+ }
+ }
+
+ pub fn set_clock(&mut self, now: u64) {
+ self.clock = now;
+ }
+
+ /// Liberar (sin imprimir) todos los bloques de un proceso; devuelve los liberados
+ pub fn free_pid(&mut self, pid: u32) -> Vec<Partition> {
+ let owned: Vec<Partition> = self.partitions.iter()
+ .filter(|p| p.pid == Some(pid))
+ .cloned()
+ .collect();
+ for partition in &owned {
+ if let Some(idx) = self.partitions.iter().position(|p| p.address == partition.address) {
+ self.partitions[idx].pid = None;
+ self.coalesce(idx);
+ self.total_deallocations += 1;
+ }
+ }
+ owned
+ }
+
+ /// Asignar `size` bytes con la estrategia indicada
+ pub fn alloc(&mut self, pid: u32, size: usize, strategy: FitStrategy) -> Result<usize, String> {
+ if size == 0 {
+ return Err("No se pueden asignar 0 bytes".to_string());
+ }
+
+ let Some(idx) = self.find_hole(size, strategy) else {
+ self.failed_allocations += 1;
+ let free: usize = self.holes().map(|p| p.size).sum();
+ return Err(if free >= size {
+ format!("No hay hueco contiguo de {} bytes ({} libres en total: usa heap-compact)", size, free)
+ } else {
+ format!("Memoria insuficiente para {} bytes", size)
+ });
+ };
+
+ let address = self.partitions[idx].address;
+ let remaining = self.partitions[idx].size - size;
+ self.partitions[idx] = Partition { address, size, pid: Some(pid), allocated_at: self.clock };
+ if remaining > 0 {
+ self.partitions.insert(idx + 1, Partition { address: address + size, size: remaining, pid: None, allocated_at: 0 });
+ }
+
+ self.next_fit_cursor = address + size;
+ self.total_allocations += 1;
+ println!("[Contiguo] Asignado {} bytes en dirección {} para proceso {} ({:?}-fit)",
+ size, address, pid, strategy);
+ Ok(address)
+ }
+
+ /// Índice del hueco elegido por la estrategia
+ fn find_hole(&self, size: usize, strategy: FitStrategy) -> Option<usize> {
+ let mut fits = self.partitions.iter()
+ .enumerate()
+ .filter(|(_, p)| p.pid.is_none() && p.size >= size);
+
+ match strategy {
+ FitStrategy::First => fits.next().map(|(i, _)| i),
+ FitStrategy::Best => fits.min_by_key(|(_, p)| p.size).map(|(i, _)| i),
+ FitStrategy::Worst => fits.max_by_key(|(_, p)| (p.size, usize::MAX - p.address)).map(|(i, _)| i),
+ FitStrategy::Next => {
+ // Primer hueco a partir del cursor; si no hay, se da la vuelta
+ let candidates: Vec<(usize, &Partition)> = fits.collect();
+ candidates.iter()
+ .find(|(_, p)| p.address + p.size > self.next_fit_cursor)
+ .or_else(|| candidates.first())
+ .map(|(i, _)| *i)
+ }
+ }
+ }
+
+ /// Liberar un bloque y fusionarlo con los huecos vecinos
+ pub fn free(&mut self, address: usize) -> Result<(), String> {
+ let idx = self.partitions.iter()
+ .position(|p| p.address == address && p.pid.is_some())
+ .ok_or_else(|| format!("No hay bloque asignado en dirección {}", address))?;
+
+ let size = self.partitions[idx].size;
+ self.partitions[idx].pid = None;
+ self.coalesce(idx);
+ self.total_deallocations += 1;
+
+ println!("[Contiguo] Liberado bloque en dirección {} (tamaño: {})", address, size);
+ Ok(())
+ }
+
+ /// Fusionar el hueco con el siguiente y el anterior si también están libres
+ fn coalesce(&mut self, mut idx: usize) {
+ if idx + 1 < self.partitions.len() && self.partitions[idx + 1].pid.is_none() {
+ let next = self.partitions.remove(idx + 1);
+ self.partitions[idx].size += next.size;
+ }
+ if idx > 0 && self.partitions[idx - 1].pid.is_none() {
+ let hole = self.partitions.remove(idx);
+ idx -= 1;
+ self.partitions[idx].size += hole.size;
+ }
+ }
+
+ /// Compactar: mover los bloques al inicio y dejar un único hueco al final
+ /// Devuelve las reubicaciones (dirección anterior, nueva, pid)
+ pub fn compact(&mut self) -> Vec<(usize, usize, u32)> {
+ let mut relocations = Vec::new();
+ let mut compacted = Vec::new();
+ let mut address = 0;
+
+ for part in self.partitions.iter().filter(|p| p.pid.is_some()) {
+ if part.address != address {
+ relocations.push((part.address, address, part.pid.unwrap()));
+ self.bytes_moved += part.size;
+ }
+ compacted.push(Partition { address, size: part.size, pid: part.pid, allocated_at: part.allocated_at });
+ address += part.size;
+ }
+ if address < self.total_size {
+ compacted.push(Partition { address, size: self.total_size - address, pid: None, allocated_at: 0 });
+ }
+
+ self.partitions = compacted;
+ self.next_fit_cursor = address;
+ self.compactions += 1;
+ relocations
+ }
+
+ fn holes(&self) -> impl Iterator<Item = &Partition> {
+ self.partitions.iter().filter(|p| p.pid.is_none())
+ }
+
+ pub fn partitions(&self) -> &[Partition] {
+ &self.partitions
+ }
+
+ pub fn failed_allocations(&self) -> u64 {
+ self.failed_allocations
+ }
+
+ /// Métricas con el mismo formato que el buddy allocator
+ pub fn stats(&self) -> BuddyStats {
+ let free_memory: usize = self.holes().map(|p| p.size).sum();
+ let largest_hole = self.holes().map(|p| p.size).max().unwrap_or(0);
+ let allocated_blocks = self.partitions.iter().filter(|p| p.pid.is_some()).count();
+
+ BuddyStats {
+ total_size: self.total_size,
+ free_memory,
+ allocated_memory: self.total_size - free_memory,
+ free_blocks: self.partitions.len() - allocated_blocks,
+ allocated_blocks,
+ total_allocations: self.total_allocations,
+ total_deallocations: self.total_deallocations,
+ internal_fragmentation: 0, // Los bloques tienen el tamaño exacto pedido
+ external_fragmentation: if free_memory == 0 {
+ 0.0
+ } else {
+ (1.0 - largest_hole as f64 / free_memory as f64) * 100.0
+ },
+ }
+ }
+
+ /// Visualizar particiones
+ pub fn display(&self) {
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ ASIGNADOR CONTIGUO - ESTADO ACTUAL ║");
+ println!("╠════════════════════════════════════════════════════╣");
+ println!("║ Tamaño total: {} bytes ", self.total_size);
+ println!("║ Particiones: {} | Cursor next-fit: {} ", self.partitions.len(), self.next_fit_cursor);
+ println!("║ Compactaciones: {} ({} bytes movidos) ", self.compactions, self.bytes_moved);
+ println!("╠════════════════════════════════════════════════════╣");
+
+ for part in &self.partitions {
+ let (state_str, pid_str) = match part.pid {
+ Some(pid) => ("Asignado ", format!("PID {}", pid)),
+ None => ("Hueco ", "-----".to_string()),
+ };
+ println!("║ [{:#06x}] {} bytes | {} | {} ", part.address, part.size, state_str, pid_str);
+ }
+
+ println!("╚════════════════════════════════════════════════════╝");
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ /// Memoria con huecos de 100, 300 y 200 bytes (en ese orden)
+ fn fragmented() -> ContiguousAllocator {
+ let mut heap = ContiguousAllocator::new(1000);
+ let sizes = [100, 50, 300, 50, 200, 300];
+ let addrs: Vec<usize> = sizes.iter().map(|s| heap.alloc(1, *s, FitStrategy::First).unwrap()).collect();
+ for idx in [0, 2, 4] {
+ heap.free(addrs[idx]).unwrap();
+ }
+ heap
+ }
+
+ #[test]
+ fn test_fit_strategies() {
+ assert_eq!(fragmented().alloc(2, 150, FitStrategy::First).unwrap(), 150);
+ assert_eq!(fragmented().alloc(2, 150, FitStrategy::Best).unwrap(), 500);
+ assert_eq!(fragmented().alloc(2, 150, FitStrategy::Worst).unwrap(), 150);
+
+ // Next-fit retoma desde la última asignación
+ let mut heap = fragmented();
+ heap.alloc(2, 80, FitStrategy::Next).unwrap();
+ assert_eq!(heap.alloc(2, 80, FitStrategy::Next).unwrap(), 150);
+ assert_eq!(heap.alloc(2, 80, FitStrategy::Next).unwrap(), 230);
+ }
+
+ #[test]
+ fn test_coalesce_and_compact() {
+ let mut heap = fragmented();
+ assert!(heap.alloc(2, 400, FitStrategy::First).is_err());
+ assert!(heap.stats().external_fragmentation > 0.0);
+
+ let moved = heap.compact();
+ assert_eq!(moved.len(), 3);
+ assert_eq!(heap.stats().external_fragmentation, 0.0);
+ let addr = heap.alloc(2, 400, FitStrategy::First).unwrap();
+ assert_eq!(addr, 400);
+
+ // Liberar todo deja un único hueco
+ let allocated: Vec<usize> = heap.partitions().iter().filter(|p| p.pid.is_some()).map(|p| p.address).collect();
+ for a in allocated {
+ heap.free(a).unwrap();
+ }
+ assert_eq!(heap.partitions().len(), 1);
+ }
+}
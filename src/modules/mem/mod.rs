@@ -2,7 +2,19 @@
 
 pub mod paging;
 pub mod buddy;
+pub mod tlb;
+pub mod address;
+pub mod allocation;
+pub mod swap;
+pub mod shm;
+pub mod contiguous;
+pub mod slab;
+pub mod zone;
+pub mod segmentation;
+pub mod cache;
+pub mod trace;
 
+#[derive(Default)]
 pub struct MemoryManager {}
 
 impl MemoryManager {
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use super::tlb::{AccessLatencies, Tlb, TlbPolicy};
+use super::address::{self, AddressingConfig, PageTableKind, TableOverhead, Translation};
+use super::allocation::{self, FrameAllocationPolicy, PffConfig, ProcessMemStats, ReplacementScope};
+use super::swap::{SwapConfig, SwapSpace};
+use super::shm::{ShmRegistry, SHM_SWAP_OWNER};
 
 /// Entrada en la tabla de páginas
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +12,10 @@ pub struct PageTableEntry {
  pub frame_number: Option<usize>, // None = no está en memoria
  pub valid: bool,
  pub last_access: u64, // Timestamp para LRU
+ #[serde(default)]
+ pub swap_slot: Option<usize>, // Copia en el área de swap
+ #[serde(default)]
+ pub cow: bool, // Compartida tras fork: solo lectura hasta la primera escritura
 }
 
 /// Tabla de páginas por proceso
@@ -37,26 +46,69 @@ impl PageTable {
  frame_number: None,
  valid: false,
  last_access: time,
+ swap_slot: None,
+ cow: false,
  });
  }
  None
  }
 
- /// Mapear página a marco
+ /// Mapear página a marco (conserva la copia en swap si existe)
  pub fn map(&mut self, page_num: usize, frame_num: usize, time: u64) {
+ let swap_slot = self.swap_slot(page_num);
  self.entries.insert(page_num, PageTableEntry {
  frame_number: Some(frame_num),
  valid: true,
  last_access: time,
+ swap_slot,
+ cow: false,
  });
  }
 
+ /// Mapear página a un marco compartido en modo copy-on-write
+ pub fn map_shared(&mut self, page_num: usize, frame_num: usize, time: u64) {
+ self.map(page_num, frame_num, time);
+ self.set_cow(page_num, true);
+ }
+
+ pub fn is_cow(&self, page_num: usize) -> bool {
+ self.entries.get(&page_num).is_some_and(|e| e.valid && e.cow)
+ }
+
+ pub fn set_cow(&mut self, page_num: usize, cow: bool) {
+ if let Some(entry) = self.entries.get_mut(&page_num) {
+ entry.cow = cow;
+ }
+ }
+
+ /// Slot de swap que guarda la página
+ pub fn swap_slot(&self, page_num: usize) -> Option<usize> {
+ self.entries.get(&page_num).and_then(|e| e.swap_slot)
+ }
+
+ pub fn set_swap_slot(&mut self, page_num: usize, slot: usize) {
+ if let Some(entry) = self.entries.get_mut(&page_num) {
+ entry.swap_slot = Some(slot);
+ }
+ }
+
  /// Invalidar entrada (cuando se reemplaza)
  pub fn invalidate(&mut self, page_num: usize) {
  if let Some(entry) = self.entries.get_mut(&page_num) {
  entry.valid = false;
  entry.frame_number = None;
+ entry.cow = false;
+ }
+ }
+
+ /// Página con contenido propio (en memoria o con copia en swap)
+ pub fn holds(&self, page_num: usize) -> bool {
+ self.entries.get(&page_num).is_some_and(|e| e.valid || e.swap_slot.is_some())
  }
+
+ /// Páginas referenciadas alguna vez (válidas o no)
+ pub fn pages(&self) -> Vec<usize> {
+ self.entries.keys().copied().collect()
  }
 
  /// Obtener todas las páginas válidas
@@ -73,9 +125,57 @@ impl PageTable {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Frame {
  pub frame_num: usize,
- pub pid: Option<u32>,
+ pub pid: Option<u32>, // Dueño principal (a quien se carga el marco)
  pub page_num: Option<usize>,
  pub load_time: u64, // Para FIFO
+ #[serde(default)]
+ pub sharers: Vec<(u32, usize)>, // Otros mapeos (pid, página) del mismo marco
+ #[serde(default)]
+ pub shm: Option<(u32, usize)>, // (segmento, página) si pertenece a memoria compartida
+}
+
+impl Frame {
+ fn empty(frame_num: usize) -> Self {
+ Self {
+ frame_num,
+ pid: None,
+ page_num: None,
+ load_time: 0,
+ sharers: Vec::new(),
+ shm: None,
+ }
+ }
+
+ /// Cantidad de páginas que mapean este marco
+ pub fn ref_count(&self) -> usize {
+ self.pid.map_or(0, |_| 1) + self.sharers.len()
+ }
+}
+
+/// Algoritmo de reemplazo para los accesos que generan los procesos al ejecutar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplacementAlgorithm {
+ Fifo,
+ #[default]
+ Lru,
+ WorkingSet(usize), // Ventana Δ
+}
+
+/// Métricas de copy-on-write
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CowStats {
+ pub forks: u64,
+ pub shared_pages: u64, // Páginas compartidas al hacer fork
+ pub cow_faults: u64, // Escrituras sobre páginas COW
+ pub copies: u64, // Marcos copiados
+ pub reclaimed: u64, // Fallos COW resueltos sin copiar (único dueño)
+}
+
+impl CowStats {
+ /// Copias que el fork se ahorró frente a duplicar todo el espacio
+ pub fn copies_avoided(&self) -> u64 {
+ self.shared_pages.saturating_sub(self.copies)
+ }
 }
 
 /// Gestor de marcos de memoria con algoritmos de reemplazo
@@ -85,6 +185,34 @@ pub struct FrameManager {
  fifo_queue: VecDeque<usize>, // Índices de marcos para FIFO
  page_tables: HashMap<u32, PageTable>,
  
+ // TLB consultada antes de las tablas de páginas
+ #[serde(default)]
+ tlb: Tlb,
+ #[serde(default)]
+ latencies: AccessLatencies,
+ #[serde(default)]
+ addressing: AddressingConfig,
+
+ // Asignación de marcos por proceso
+ #[serde(default)]
+ allocation_policy: FrameAllocationPolicy,
+ #[serde(default)]
+ scope: ReplacementScope,
+ #[serde(default)]
+ quotas: HashMap<u32, usize>, // pid -> marcos asignados
+ #[serde(default)]
+ pff: PffConfig,
+ #[serde(default)]
+ process_stats: HashMap<u32, ProcessMemStats>,
+
+ // Área de swap en el disco simulado
+ #[serde(default)]
+ swap: SwapSpace,
+ #[serde(default)]
+ cow: CowStats,
+ #[serde(default)]
+ shm: ShmRegistry,
+
  // Métricas
  page_faults: u64,
  page_hits: u64,
@@ -95,18 +223,24 @@ impl FrameManager {
  pub fn new(total_frames: usize) -> Self {
  let mut frames = Vec::with_capacity(total_frames);
  for i in 0..total_frames {
- frames.push(Frame {
- frame_num: i,
- pid: None,
- page_num: None,
- load_time: 0,
- });
+ frames.push(Frame::empty(i));
  }
 
  Self {
  frames,
  fifo_queue: VecDeque::new(),
  page_tables: HashMap::new(),
+ tlb: Tlb::default(),
+ latencies: AccessLatencies::default(),
+ addressing: AddressingConfig::default(),
+ allocation_policy: FrameAllocationPolicy::default(),
+ scope: ReplacementScope::default(),
+ quotas: HashMap::new(),
+ pff: PffConfig::default(),
+ process_stats: HashMap::new(),
+ swap: SwapSpace::default(),
+ cow: CowStats::default(),
+ shm: ShmRegistry::default(),
  page_faults: 0,
  page_hits: 0,
  current_time: 0,
@@ -118,36 +252,340 @@ impl FrameManager {
  self.page_tables.insert(pid, PageTable::new(pid));
  }
 
- /// Acceder a una página (devuelve marco o provoca fallo)
- pub fn access_page_fifo(&mut self, pid: u32, page_num: usize) -> Result<usize, String> {
- self.current_time += 1;
+ /// Duplicar el espacio del padre en el hijo compartiendo los marcos residentes (COW)
+ /// Devuelve cuántas páginas quedaron compartidas
+ pub fn fork(&mut self, parent: u32, child: u32) -> usize {
+ let pages = self.page_tables.get(&parent).map(|t| t.valid_pages()).unwrap_or_default();
+ let mut child_table = PageTable::new(child);
+
+ // Los segmentos compartidos se heredan conectados y siguen siendo escribibles
+ for name in self.shm.attached_to(parent) {
+ let base = self.shm.find(&name).and_then(|s| s.base_of(parent));
+ let _ = self.shm.attach(child, &name, base);
+ }
+ let pages: Vec<(usize, usize, u64)> = pages.into_iter()
+ .filter(|(page_num, frame_idx, _)| {
+ if self.frames[*frame_idx].shm.is_some() {
+ child_table.map(*page_num, *frame_idx, self.current_time);
+ self.frames[*frame_idx].sharers.push((child, *page_num));
+ false
+ } else {
+ true
+ }
+ })
+ .collect();
+
+ for (page_num, frame_idx, _) in &pages {
+ child_table.map_shared(*page_num, *frame_idx, self.current_time);
+ self.frames[*frame_idx].sharers.push((child, *page_num));
+ }
+ if let Some(parent_table) = self.page_tables.get_mut(&parent) {
+ for (page_num, _, _) in &pages {
+ parent_table.set_cow(*page_num, true);
+ }
+ }
+
+ // Las páginas expulsadas se copian a un slot propio del hijo: los slots
+ // se liberan por pid y el del padre desaparece cuando este termina
+ let swapped: Vec<(usize, u64)> = self.page_tables.get(&parent)
+ .map(|t| t.entries.iter()
+ .filter(|(_, e)| !e.valid && e.swap_slot.is_some())
+ .map(|(page, e)| (*page, e.last_access))
+ .collect())
+ .unwrap_or_default();
+ for (page_num, last_access) in swapped {
+ if let Some(slot) = self.swap.queue_page_out(child, page_num, None) {
+ child_table.entries.insert(page_num, PageTableEntry {
+ frame_number: None,
+ valid: false,
+ last_access,
+ swap_slot: Some(slot),
+ cow: false,
+ });
+ }
+ }
+ self.swap.service_pending(self.current_time);
+ self.page_tables.insert(child, child_table);
+
+ self.cow.forks += 1;
+ self.cow.shared_pages += pages.len() as u64;
+ pages.len()
+ }
+
+ /// Reemplazar el espacio de direcciones (exec): se sueltan todas sus páginas
+ pub fn exec(&mut self, pid: u32) {
+ for name in self.shm.attached_to(pid) {
+ let _ = self.shm_detach(pid, &name);
+ }
+ self.drop_frames(pid, false);
+ self.swap.free_slots(pid);
+ self.page_tables.insert(pid, PageTable::new(pid));
+ }
+
+ /// Escribir en una página: si está compartida COW se copia a un marco propio
+ pub fn write_page(&mut self, pid: u32, page_num: usize, algorithm: ReplacementAlgorithm) -> Result<usize, String> {
+ let frame_idx = self.access_page(pid, page_num, algorithm)?;
+ if !self.page_tables[&pid].is_cow(page_num) {
+ return Ok(frame_idx);
+ }
+
+ self.cow.cow_faults += 1;
+ if self.frames[frame_idx].ref_count() == 1 {
+ // Los demás ya copiaron o terminaron: basta con habilitar escritura
+ self.page_tables.get_mut(&pid).unwrap().set_cow(page_num, false);
+ self.cow.reclaimed += 1;
+ return Ok(frame_idx);
+ }
+
+ self.unshare(frame_idx, pid, page_num);
+ let new_idx = self.claim_frame(pid, algorithm)?;
+ self.frames[new_idx] = Frame {
+ frame_num: new_idx,
+ pid: Some(pid),
+ page_num: Some(page_num),
+ load_time: self.current_time,
+ sharers: Vec::new(),
+ shm: None,
+ };
+ if algorithm == ReplacementAlgorithm::Fifo {
+ self.fifo_queue.push_back(new_idx);
+ }
+ self.page_tables.get_mut(&pid).unwrap().map(page_num, new_idx, self.current_time);
+ self.tlb.insert(pid, page_num, new_idx, self.current_time);
+ self.swap.service_pending(self.current_time);
+
+ self.cow.copies += 1;
+ Ok(new_idx)
+ }
+
+ /// Quitar el mapeo (pid, página) de un marco compartido
+ /// Si era el dueño principal, el primer proceso que lo comparte pasa a serlo
+ fn unshare(&mut self, frame_idx: usize, pid: u32, page_num: usize) {
+ let frame = &mut self.frames[frame_idx];
+ if frame.pid == Some(pid) && frame.page_num == Some(page_num) {
+ let (new_pid, new_page) = frame.sharers.remove(0);
+ frame.pid = Some(new_pid);
+ frame.page_num = Some(new_page);
+ } else {
+ frame.sharers.retain(|m| *m != (pid, page_num));
+ }
+
+ if let Some(table) = self.page_tables.get_mut(&pid) {
+ table.invalidate(page_num);
+ }
+ self.tlb.invalidate(pid, page_num);
+ }
+
+ /// Marco para una página nueva: uno libre o el de una víctima expulsada
+ fn claim_frame(&mut self, pid: u32, algorithm: ReplacementAlgorithm) -> Result<usize, String> {
+ if let Some(free_idx) = self.free_frame_for(pid) {
+ return Ok(free_idx);
+ }
+ let owner = self.victim_owner(pid)?;
+ let victim_idx = match algorithm {
+ ReplacementAlgorithm::Fifo => self.find_fifo_victim(owner),
+ ReplacementAlgorithm::Lru => self.find_lru_victim(owner),
+ ReplacementAlgorithm::WorkingSet(window) => self.find_working_set_victim(window, owner),
+ };
+ self.evict_frame(victim_idx, true);
+ Ok(victim_idx)
+ }
+
+ pub fn cow_stats(&self) -> &CowStats {
+ &self.cow
+ }
+
+ /// Crear un segmento de memoria compartida
+ pub fn shm_create(&mut self, name: &str, pages: usize) -> Result<u32, String> {
+ self.shm.create(name, pages)
+ }
+
+ /// Conectar un proceso a un segmento; devuelve la página base
+ pub fn shm_attach(&mut self, pid: u32, name: &str, base: Option<usize>) -> Result<usize, String> {
+ if !self.page_tables.contains_key(&pid) {
+ self.create_page_table(pid);
+ }
+ let (id, base) = self.shm.attach(pid, name, base)?;
+
+ // No se puede tapar una página privada que ya tiene contenido (en memoria o en swap)
+ let pages = self.shm.get(id).map(|s| s.pages).unwrap_or(0);
+ if let Some(page) = self.first_held_page(pid, base..base + pages) {
+ let _ = self.shm.detach(pid, name);
+ return Err(format!("La página {:#x} del proceso {} ya está mapeada", page, pid));
+ }
+ Ok(base)
+ }
 
+ /// Primera página del rango que el proceso ya tiene mapeada
+ pub fn first_held_page(&self, pid: u32, mut pages: std::ops::Range<usize>) -> Option<usize> {
+ let table = self.page_tables.get(&pid)?;
+ pages.find(|p| table.holds(*p))
+ }
+
+ /// Desconectar un proceso: sus páginas del segmento dejan de estar mapeadas
+ pub fn shm_detach(&mut self, pid: u32, name: &str) -> Result<(), String> {
+ let (id, base, pages) = self.shm.detach(pid, name)?;
+ for page_num in base..base + pages {
+ let frame_idx = self.page_tables.get(&pid)
+ .and_then(|t| t.entries.get(&page_num))
+ .filter(|e| e.valid)
+ .and_then(|e| e.frame_number);
+ if let Some(frame_idx) = frame_idx {
+ if self.frames[frame_idx].ref_count() > 1 {
+ self.unshare(frame_idx, pid, page_num);
+ } else {
+ self.evict_frame(frame_idx, true);
+ }
+ }
+ if let Some(table) = self.page_tables.get_mut(&pid) {
+ table.entries.remove(&page_num);
+ }
+ }
+ self.swap.service_pending(self.current_time);
+ self.shm_reap(id);
+ Ok(())
+ }
+
+ /// Marcar un segmento para destrucción; se elimina al desconectarse el último proceso
+ /// Devuelve true si se eliminó en el acto
+ pub fn shm_destroy(&mut self, name: &str) -> Result<bool, String> {
+ let (id, unused) = self.shm.mark_destroy(name)?;
+ if unused {
+ self.shm_reap(id);
+ }
+ Ok(unused)
+ }
+
+ /// Liberar marcos y slots de un segmento marcado sin procesos conectados
+ fn shm_reap(&mut self, id: u32) {
+ if let Some(seg) = self.shm.remove_if_unused(id) {
+ let (frames, slots) = seg.resources();
+ for frame_idx in frames {
+ self.evict_frame(frame_idx, false);
+ }
+ for slot in slots {
+ self.swap.free_slot(slot);
+ }
+ }
+ }
+
+ pub fn shm(&self) -> &ShmRegistry {
+ &self.shm
+ }
+
+ /// Fallo sobre una página de segmento ya residente: basta con mapear el marco
+ fn map_resident_shm(&mut self, pid: u32, page_num: usize) -> Option<usize> {
+ let (id, index) = self.shm.lookup(pid, page_num)?;
+ let frame_idx = self.shm.get(id)?.frame(index)?;
+
+ self.frames[frame_idx].sharers.push((pid, page_num));
+ self.page_tables.get_mut(&pid).unwrap().map(page_num, frame_idx, self.current_time);
+ self.tlb.insert(pid, page_num, frame_idx, self.current_time);
+ Some(frame_idx)
+ }
+
+ /// Configurar la TLB (capacidad 0 la desactiva)
+ pub fn configure_tlb(&mut self, capacity: usize, policy: TlbPolicy, flush_on_switch: bool) {
+ self.tlb = Tlb::new(capacity, policy, flush_on_switch);
+ }
+
+ /// Configurar latencias usadas para el tiempo efectivo de acceso
+ pub fn set_latencies(&mut self, latencies: AccessLatencies) {
+ self.latencies = latencies;
+ }
+
+ pub fn tlb(&self) -> &Tlb {
+ &self.tlb
+ }
+
+ /// Configurar tamaño de página y estructura de la tabla de páginas
+ pub fn configure_addressing(&mut self, config: AddressingConfig) {
+ self.addressing = config;
+ }
+
+ pub fn addressing(&self) -> &AddressingConfig {
+ &self.addressing
+ }
+
+ /// Configurar el área de swap (0 slots la desactiva)
+ pub fn configure_swap(&mut self, config: SwapConfig) {
+ self.swap = SwapSpace::new(config);
+ }
+
+ pub fn swap(&self) -> &SwapSpace {
+ &self.swap
+ }
+
+ /// Notificar cambio de contexto a la TLB
+ pub fn context_switch(&mut self, pid: u32) {
+ self.tlb.context_switch(pid);
+ }
+
+ /// Traducir página consultando primero la TLB y luego la tabla de páginas
+ /// Devuelve el marco si la página está en memoria (hit)
+ fn translate(&mut self, pid: u32, page_num: usize) -> Option<usize> {
  // Verificar si la página tabla existe
  if !self.page_tables.contains_key(&pid) {
  self.create_page_table(pid);
  }
 
- // Intentar acceder a la página
+ self.tlb.context_switch(pid);
+ let tlb_frame = self.tlb.lookup(pid, page_num, self.current_time);
+
+ // La tabla de páginas se consulta igual para mantener el timestamp LRU
  let page_table = self.page_tables.get_mut(&pid).unwrap();
- 
- if let Some(frame_num) = page_table.access(page_num, self.current_time) {
- // HIT: La página ya está en memoria
+ let frame = page_table.access(page_num, self.current_time);
+
+ self.process_stats.entry(pid).or_default().record(frame.is_none());
+
+ if let Some(frame_num) = frame {
  self.page_hits += 1;
+ if tlb_frame.is_none() {
+ self.tlb.insert(pid, page_num, frame_num, self.current_time);
+ }
+ }
+
+ frame
+ }
+
+ /// Acceder a una página con el algoritmo indicado
+ pub fn access_page(&mut self, pid: u32, page_num: usize, algorithm: ReplacementAlgorithm) -> Result<usize, String> {
+ match algorithm {
+ ReplacementAlgorithm::Fifo => self.access_page_fifo(pid, page_num),
+ ReplacementAlgorithm::Lru => self.access_page_lru(pid, page_num),
+ ReplacementAlgorithm::WorkingSet(window) => self.access_page_working_set(pid, page_num, window),
+ }
+ }
+
+ /// Total de fallos de página
+ pub fn page_faults(&self) -> u64 {
+ self.page_faults
+ }
+
+ /// Acceder a una página (devuelve marco o provoca fallo)
+ pub fn access_page_fifo(&mut self, pid: u32, page_num: usize) -> Result<usize, String> {
+ self.current_time += 1;
+
+ if let Some(frame_num) = self.translate(pid, page_num) {
+ // HIT: La página ya está en memoria
  return Ok(frame_num);
  }
 
  // MISS: Fallo de página
  self.page_faults += 1;
+ if let Some(frame_idx) = self.map_resident_shm(pid, page_num) {
+ return Ok(frame_idx);
+ }
 
  // Buscar marco libre
- if let Some(free_idx) = self.frames.iter().position(|f| f.pid.is_none()) {
+ if let Some(free_idx) = self.free_frame_for(pid) {
  // Hay marco libre
  self.allocate_frame_fifo(free_idx, pid, page_num);
  return Ok(free_idx);
  }
 
  // No hay marcos libres - reemplazar con FIFO
- let victim_idx = self.fifo_queue.pop_front().unwrap();
+ let victim_idx = self.find_fifo_victim(self.victim_owner(pid)?);
  self.replace_frame_fifo(victim_idx, pid, page_num);
  Ok(victim_idx)
  }
@@ -156,90 +594,184 @@ impl FrameManager {
  pub fn access_page_lru(&mut self, pid: u32, page_num: usize) -> Result<usize, String> {
  self.current_time += 1;
 
- if !self.page_tables.contains_key(&pid) {
- self.create_page_table(pid);
- }
-
- let page_table = self.page_tables.get_mut(&pid).unwrap();
- 
- if let Some(frame_num) = page_table.access(page_num, self.current_time) {
+ if let Some(frame_num) = self.translate(pid, page_num) {
  // HIT
- self.page_hits += 1;
  return Ok(frame_num);
  }
 
  // MISS
  self.page_faults += 1;
+ if let Some(frame_idx) = self.map_resident_shm(pid, page_num) {
+ return Ok(frame_idx);
+ }
 
  // Buscar marco libre
- if let Some(free_idx) = self.frames.iter().position(|f| f.pid.is_none()) {
+ if let Some(free_idx) = self.free_frame_for(pid) {
  self.allocate_frame_lru(free_idx, pid, page_num);
  return Ok(free_idx);
  }
 
  // Reemplazar con LRU
- let victim_idx = self.find_lru_victim();
+ let victim_idx = self.find_lru_victim(self.victim_owner(pid)?);
  self.replace_frame_lru(victim_idx, pid, page_num);
  Ok(victim_idx)
  }
 
  /// Asignar marco libre (FIFO)
  fn allocate_frame_fifo(&mut self, frame_idx: usize, pid: u32, page_num: usize) {
- self.frames[frame_idx] = Frame {
- frame_num: frame_idx,
- pid: Some(pid),
- page_num: Some(page_num),
- load_time: self.current_time,
- };
+ self.map_page(frame_idx, pid, page_num);
  self.fifo_queue.push_back(frame_idx);
- 
- let page_table = self.page_tables.get_mut(&pid).unwrap();
- page_table.map(page_num, frame_idx, self.current_time);
  }
 
  /// Reemplazar marco (FIFO)
  fn replace_frame_fifo(&mut self, frame_idx: usize, new_pid: u32, new_page: usize) {
  // Invalidar entrada antigua
- if let Some(old_pid) = self.frames[frame_idx].pid {
- if let Some(old_page) = self.frames[frame_idx].page_num {
- if let Some(old_table) = self.page_tables.get_mut(&old_pid) {
- old_table.invalidate(old_page);
- }
- }
- }
+ self.unmap_frame(frame_idx, true);
 
  // Asignar nuevo
- self.frames[frame_idx] = Frame {
- frame_num: frame_idx,
- pid: Some(new_pid),
- page_num: Some(new_page),
- load_time: self.current_time,
- };
+ self.map_page(frame_idx, new_pid, new_page);
  self.fifo_queue.push_back(frame_idx);
-
- let page_table = self.page_tables.get_mut(&new_pid).unwrap();
- page_table.map(new_page, frame_idx, self.current_time);
  }
 
  /// Asignar marco libre (LRU)
  fn allocate_frame_lru(&mut self, frame_idx: usize, pid: u32, page_num: usize) {
+ self.map_page(frame_idx, pid, page_num);
+ }
+
+ /// Cargar una página en un marco (se lee de swap si tiene copia)
+ fn map_page(&mut self, frame_idx: usize, pid: u32, page_num: usize) {
  self.frames[frame_idx] = Frame {
  frame_num: frame_idx,
  pid: Some(pid),
  page_num: Some(page_num),
  load_time: self.current_time,
+ sharers: Vec::new(),
+ shm: None,
  };
- 
+
+ // Las páginas de un segmento se leen de la copia en swap del segmento
+ let shm = self.shm.lookup(pid, page_num);
+ if let Some((id, index)) = shm {
+ self.frames[frame_idx].shm = shm;
+ self.shm.set_frame(id, index, Some(frame_idx));
+ if let Some(slot) = self.shm.get(id).and_then(|s| s.swap_slot(index)) {
+ self.swap.queue_page_in(SHM_SWAP_OWNER, index, slot);
+ }
+ }
+
  let page_table = self.page_tables.get_mut(&pid).unwrap();
+ if let Some(slot) = page_table.swap_slot(page_num).filter(|_| shm.is_none()) {
+ self.swap.queue_page_in(pid, page_num, slot);
+ }
  page_table.map(page_num, frame_idx, self.current_time);
+ self.tlb.insert(pid, page_num, frame_idx, self.current_time);
+
+ // Page-out de la víctima (si hubo) y page-in van juntos al disco
+ self.swap.service_pending(self.current_time);
+ }
+
+ /// Retirar la página que ocupa un marco (se escribe en swap si `write_back`)
+ /// Los procesos que compartían el marco pierden su mapeo; si era COW cada uno
+ /// guarda su propia copia en swap y al volver a tocarla la recibe privada
+ fn unmap_frame(&mut self, frame_idx: usize, write_back: bool) {
+ let cow_copies = write_back && self.swap.is_enabled() && self.frames[frame_idx].shm.is_none();
+ for (pid, page_num) in std::mem::take(&mut self.frames[frame_idx].sharers) {
+ if let Some(table) = self.page_tables.get_mut(&pid) {
+ table.invalidate(page_num);
+ if cow_copies {
+ if let Some(slot) = self.swap.queue_page_out(pid, page_num, table.swap_slot(page_num)) {
+ table.set_swap_slot(page_num, slot);
+ }
+ }
+ }
+ self.tlb.invalidate(pid, page_num);
+ }
+
+ // Página de un segmento: su copia en swap pertenece al segmento y siempre se guarda
+ if let Some((id, index)) = self.frames[frame_idx].shm.take() {
+ if let (Some(pid), Some(page_num)) = (self.frames[frame_idx].pid, self.frames[frame_idx].page_num) {
+ if let Some(table) = self.page_tables.get_mut(&pid) {
+ table.invalidate(page_num);
+ }
+ self.tlb.invalidate(pid, page_num);
+ }
+ if let Some(seg) = self.shm.get(id) {
+ if self.swap.is_enabled() {
+ if let Some(slot) = self.swap.queue_page_out(SHM_SWAP_OWNER, index, seg.swap_slot(index)) {
+ self.shm.set_swap_slot(id, index, slot);
+ }
+ }
+ self.shm.set_frame(id, index, None);
+ }
+ return;
+ }
+
+ if let (Some(old_pid), Some(old_page)) = (self.frames[frame_idx].pid, self.frames[frame_idx].page_num) {
+ if let Some(old_table) = self.page_tables.get_mut(&old_pid) {
+ old_table.invalidate(old_page);
+ if write_back && self.swap.is_enabled() {
+ let existing = old_table.swap_slot(old_page);
+ if let Some(slot) = self.swap.queue_page_out(old_pid, old_page, existing) {
+ old_table.set_swap_slot(old_page, slot);
+ }
+ }
+ }
+ self.tlb.invalidate(old_pid, old_page);
+ }
+ }
+
+ /// Marco libre que puede usar el proceso (respeta la cuota en reemplazo local)
+ fn free_frame_for(&self, pid: u32) -> Option<usize> {
+ if self.scope == ReplacementScope::Local {
+ if let Some(&quota) = self.quotas.get(&pid) {
+ if self.resident_frames(pid) >= quota {
+ return None;
+ }
+ }
+ }
+ self.frames.iter().position(|f| f.pid.is_none())
+ }
+
+ /// Proceso del que debe salir la víctima (None = cualquiera)
+ /// Con reemplazo local, un proceso sin cuota ni marcos propios no puede cargar páginas
+ fn victim_owner(&self, pid: u32) -> Result<Option<u32>, String> {
+ if self.scope != ReplacementScope::Local {
+ return Ok(None);
+ }
+ if self.resident_frames(pid) > 0 {
+ return Ok(Some(pid));
+ }
+ if self.quotas.get(&pid) == Some(&0) {
+ return Err(format!("Proceso {} sin cuota de marcos con reemplazo local", pid));
+ }
+ Ok(None)
+ }
+
+ /// Encontrar víctima FIFO (el marco cargado hace más tiempo)
+ fn find_fifo_victim(&mut self, owner: Option<u32>) -> usize {
+ let candidate = |f: &Frame| f.pid.is_some() && (owner.is_none() || f.pid == owner);
+
+ if let Some(pos) = self.fifo_queue.iter().position(|&idx| candidate(&self.frames[idx])) {
+ return self.fifo_queue.remove(pos).unwrap();
+ }
+
+ // Marcos cargados por otros algoritmos no están en la cola
+ self.frames.iter()
+ .filter(|f| candidate(f))
+ .min_by_key(|f| f.load_time)
+ .map(|f| f.frame_num)
+ .unwrap_or(0)
  }
 
  /// Encontrar víctima LRU
- fn find_lru_victim(&self) -> usize {
+ fn find_lru_victim(&self, owner: Option<u32>) -> usize {
  let mut min_time = u64::MAX;
  let mut victim_idx = 0;
 
  for (idx, frame) in self.frames.iter().enumerate() {
+ if owner.is_some() && frame.pid != owner {
+ continue;
+ }
  if let Some(pid) = frame.pid {
  if let Some(page_num) = frame.page_num {
  if let Some(table) = self.page_tables.get(&pid) {
@@ -260,24 +792,257 @@ impl FrameManager {
  /// Reemplazar marco (LRU)
  fn replace_frame_lru(&mut self, frame_idx: usize, new_pid: u32, new_page: usize) {
  // Invalidar entrada antigua
- if let Some(old_pid) = self.frames[frame_idx].pid {
- if let Some(old_page) = self.frames[frame_idx].page_num {
- if let Some(old_table) = self.page_tables.get_mut(&old_pid) {
- old_table.invalidate(old_page);
+ self.unmap_frame(frame_idx, true);
+
+ // Asignar nuevo
+ self.map_page(frame_idx, new_pid, new_page);
  }
+
+ /// Configurar asignación de marcos, alcance del reemplazo y control PFF
+ pub fn configure_allocation(&mut self, policy: FrameAllocationPolicy, scope: ReplacementScope, pff: PffConfig) {
+ self.allocation_policy = policy;
+ self.scope = scope;
+ self.pff = pff;
  }
+
+ pub fn pff(&self) -> &PffConfig {
+ &self.pff
  }
 
- // Asignar nuevo
- self.frames[frame_idx] = Frame {
- frame_num: frame_idx,
- pid: Some(new_pid),
- page_num: Some(new_page),
- load_time: self.current_time,
+ /// Recalcular las cuotas de marcos para los procesos activos (pid, memory_req)
+ pub fn rebalance(&mut self, processes: &[(u32, usize)]) {
+ self.quotas = allocation::compute_allocation(self.allocation_policy, self.frames.len(), processes);
+ let pids: Vec<u32> = self.quotas.keys().copied().collect();
+ for pid in pids {
+ self.enforce_quota(pid);
+ }
+ }
+
+ /// Con reemplazo local, expulsar los marcos que excedan la cuota
+ fn enforce_quota(&mut self, pid: u32) {
+ if self.scope != ReplacementScope::Local {
+ return;
+ }
+ let quota = self.quotas.get(&pid).copied().unwrap_or(0);
+ while self.resident_frames(pid) > quota {
+ let victim_idx = self.find_lru_victim(Some(pid));
+ self.evict_frame(victim_idx, true);
+ }
+ self.swap.service_pending(self.current_time);
+ }
+
+ /// Ajustar cuotas según Page Fault Frequency
+ /// Devuelve los cambios (pid, cuota anterior, cuota nueva)
+ pub fn adjust_pff(&mut self) -> Vec<(u32, usize, usize)> {
+ let mut changes = Vec::new();
+ let mut pids: Vec<u32> = self.quotas.keys().copied().collect();
+ pids.sort();
+
+ for pid in pids {
+ let (rate, accesses) = match self.process_stats.get(&pid) {
+ Some(stats) => (stats.window_fault_rate(), stats.window_accesses),
+ None => continue,
  };
+ if accesses == 0 {
+ continue;
+ }
+
+ let old = self.quotas[&pid];
+ let assigned: usize = self.quotas.values().sum();
+ let new = if rate > self.pff.upper && assigned < self.frames.len() {
+ old + 1
+ } else if rate < self.pff.lower && old > 1 {
+ old - 1
+ } else {
+ old
+ };
+
+ if new != old {
+ self.quotas.insert(pid, new);
+ self.enforce_quota(pid);
+ changes.push((pid, old, new));
+ }
+ if let Some(stats) = self.process_stats.get_mut(&pid) {
+ stats.reset_window();
+ }
+ }
+
+ changes
+ }
+
+ pub fn resident_frames(&self, pid: u32) -> usize {
+ self.frames.iter().filter(|f| f.pid == Some(pid)).count()
+ }
+
+ pub fn quota(&self, pid: u32) -> Option<usize> {
+ self.quotas.get(&pid).copied()
+ }
+
+ pub fn process_stats(&self, pid: u32) -> Option<&ProcessMemStats> {
+ self.process_stats.get(&pid)
+ }
+
+ /// Liberar un marco (la página deja de estar en memoria)
+ fn evict_frame(&mut self, frame_idx: usize, write_back: bool) {
+ self.unmap_frame(frame_idx, write_back);
+ self.frames[frame_idx] = Frame::empty(frame_idx);
+ self.fifo_queue.retain(|&idx| idx != frame_idx);
+ }
+
+ /// Sacar de memoria todos los marcos de un proceso (devuelve cuántos)
+ /// Las páginas se escriben en swap si está activo
+ pub fn release_frames(&mut self, pid: u32) -> usize {
+ let freed = self.drop_frames(pid, true);
+ self.swap.service_pending(self.current_time);
+ freed
+ }
+
+ /// Los marcos compartidos no se liberan: solo se quita el mapeo del proceso
+ fn drop_frames(&mut self, pid: u32, write_back: bool) -> usize {
+ let mapped: Vec<(usize, usize, bool)> = self.frames.iter()
+ .flat_map(|f| {
+ let own = f.pid.filter(|p| *p == pid).map(|_| (f.frame_num, f.page_num.unwrap(), f.sharers.is_empty()));
+ let shared = f.sharers.iter().filter(|(p, _)| *p == pid).map(|(_, page)| (f.frame_num, *page, false));
+ own.into_iter().chain(shared).collect::<Vec<_>>()
+ })
+ .collect();
+
+ let mut freed = 0;
+ for (idx, page_num, exclusive) in mapped {
+ if exclusive {
+ self.evict_frame(idx, write_back);
+ freed += 1;
+ } else {
+ self.unshare(idx, pid, page_num);
+ }
+ }
+ freed
+ }
+
+ /// Eliminar un proceso terminado: marcos, slots de swap, tabla de páginas y cuota
+ pub fn release_process(&mut self, pid: u32) {
+ for name in self.shm.attached_to(pid) {
+ let _ = self.shm_detach(pid, &name);
+ }
+ self.drop_frames(pid, false);
+ self.swap.free_slots(pid);
+ self.page_tables.remove(&pid);
+ self.quotas.remove(&pid);
+ self.process_stats.remove(&pid);
+ }
+
+ /// Mostrar asignación de marcos por proceso
+ pub fn display_allocation(&self) {
+ println!("\n╔═══════════════════════════════════════════════════════════╗");
+ println!("║ ASIGNACIÓN DE MARCOS ({:?}, reemplazo {:?}) ", self.allocation_policy, self.scope);
+ println!("╠═════╦════════╦════════════╦═════════╦═════════╦═══════════╣");
+ println!("║ PID ║ Cuota ║ Residentes ║ Accesos ║ Fallos ║ Tasa ║");
+ println!("╠═════╬════════╬════════════╬═════════╬═════════╬═══════════╣");
+
+ let mut pids: Vec<u32> = self.quotas.keys().chain(self.process_stats.keys()).copied().collect();
+ pids.sort();
+ pids.dedup();
+
+ for pid in pids {
+ let quota = self.quota(pid).map(|q| format!("{:6}", q)).unwrap_or_else(|| " -- ".to_string());
+ let stats = self.process_stats.get(&pid).cloned().unwrap_or_default();
+ println!("║ {:3} ║ {} ║ {:10} ║ {:7} ║ {:7} ║ {:8.2}% ║",
+ pid, quota, self.resident_frames(pid), stats.accesses, stats.faults, stats.fault_rate() * 100.0);
+ }
+
+ println!("╚═════╩════════╩════════════╩═════════╩═════════╩═══════════╝");
+ if self.pff.enabled {
+ println!("PFF activo: umbral inferior {:.2}, superior {:.2}", self.pff.lower, self.pff.upper);
+ }
+ }
+
+ /// Traducir una dirección virtual recorriendo la tabla configurada
+ /// Si la página no está en memoria el fallo se atiende con LRU
+ pub fn translate_address(&mut self, pid: u32, vaddr: u64) -> Result<Translation, String> {
+ let config = self.addressing;
+ let (page_num, offset) = config.split(vaddr)?;
+
+ let mut steps = vec![format!("VA {:#x} -> página {:#x}, desplazamiento {:#x} ({} bits de offset)",
+ vaddr, page_num, offset, config.offset_bits())];
+
+ // Recorrido de la tabla (antes del acceso, para reflejar el estado previo)
+ let mut walk = Vec::new();
+ match config.kind {
+ PageTableKind::MultiLevel => {
+ let indices = config.level_indices(page_num);
+ let bits = config.level_bits();
+ for (level, (idx, b)) in indices.iter().zip(&bits).enumerate() {
+ let target = if level + 1 < indices.len() {
+ format!("tabla de nivel {}", level + 2)
+ } else {
+ "PTE".to_string()
+ };
+ walk.push(format!("Nivel {}: índice {:#x} ({} bits) -> {}", level + 1, idx, b, target));
+ }
+ }
+ PageTableKind::Inverted => {
+ match self.frames.iter().position(|f| f.pid == Some(pid) && f.page_num == Some(page_num)) {
+ Some(idx) => walk.push(format!("Tabla invertida: (pid={}, pág={:#x}) en entrada {} tras {} comparación(es)",
+ pid, page_num, idx, idx + 1)),
+ None => walk.push(format!("Tabla invertida: (pid={}, pág={:#x}) no encontrada tras {} comparación(es)",
+ pid, page_num, self.frames.len())),
+ }
+ }
+ PageTableKind::Hashed => {
+ let bucket = config.hash_bucket(pid, page_num);
+ let chain = self.frames.iter()
+ .filter_map(|f| f.pid.zip(f.page_num))
+ .filter(|(p, pg)| config.hash_bucket(*p, *pg) == bucket)
+ .count();
+ walk.push(format!("Hash(pid={}, pág={:#x}) = bucket {} (cadena de {} nodo(s))",
+ pid, page_num, bucket, chain));
+ }
+ }
+
+ let faults_before = self.page_faults;
+ let tlb_hits_before = self.tlb.hits();
+ let frame_num = self.access_page_lru(pid, page_num)?;
+ let page_fault = self.page_faults > faults_before;
+
+ if self.tlb.hits() > tlb_hits_before {
+ steps.push(format!("TLB hit -> marco {} (no se recorre la tabla)", frame_num));
+ } else {
+ if self.tlb.is_enabled() {
+ steps.push("TLB miss".to_string());
+ }
+ steps.extend(walk);
+ if page_fault {
+ steps.push(format!("PTE inválida -> fallo de página, cargada en marco {}", frame_num));
+ } else {
+ steps.push(format!("PTE válida -> marco {}", frame_num));
+ }
+ }
+
+ let physical_address = (frame_num * config.page_size + offset) as u64;
+ steps.push(format!("PA = {} × {} + {:#x} = {:#x}", frame_num, config.page_size, offset, physical_address));
 
- let page_table = self.page_tables.get_mut(&new_pid).unwrap();
- page_table.map(new_page, frame_idx, self.current_time);
+ Ok(Translation {
+ vaddr,
+ page_num,
+ offset,
+ frame_num,
+ physical_address,
+ page_fault,
+ steps,
+ })
+ }
+
+ /// Memoria ocupada por las tablas de páginas de un proceso en cada variante
+ pub fn table_overheads(&self, pid: u32) -> Vec<TableOverhead> {
+ let pages = self.page_tables.get(&pid).map(|t| t.pages()).unwrap_or_default();
+ let resident = self.frames.iter().filter(|f| f.pid == Some(pid)).count();
+
+ let mut overheads: Vec<_> = (1..=3)
+ .map(|levels| address::multilevel_overhead(&self.addressing, levels, &pages))
+ .collect();
+ overheads.push(address::inverted_overhead(&self.addressing, self.frames.len()));
+ overheads.push(address::hashed_overhead(&self.addressing, resident));
+ overheads
  }
 
  /// Obtener métricas
@@ -291,7 +1056,37 @@ impl FrameManager {
  0.0
  },
  total_accesses: self.page_hits + self.page_faults,
+ tlb_hits: self.tlb.hits(),
+ tlb_misses: self.tlb.misses(),
+ tlb_hit_rate: self.tlb.hit_ratio(),
+ effective_access_time: self.effective_access_time(),
+ }
  }
+
+ /// Calcular tiempo efectivo de acceso (ns) a partir de los contadores
+ /// - TLB hit: t_tlb + t_mem
+ /// - TLB miss: t_tlb + 2·t_mem (tabla de páginas + dato)
+ /// - Fallo de página: se suma t_fallo (+ tiempo de disco del swap)
+ fn effective_access_time(&self) -> f64 {
+ let accesses = self.page_hits + self.page_faults;
+ if accesses == 0 {
+ return 0.0;
+ }
+
+ let lat = self.latencies;
+ // Con swap, el tiempo de disco medido se suma al costo fijo del fallo
+ let mut total = self.page_faults * lat.page_fault_ns + self.swap.total_service_ns();
+
+ if self.tlb.is_enabled() {
+ let tlb_hits = self.tlb.hits().min(accesses);
+ total += accesses * lat.tlb_ns;
+ total += tlb_hits * lat.memory_ns;
+ total += (accesses - tlb_hits) * 2 * lat.memory_ns;
+ } else {
+ total += accesses * 2 * lat.memory_ns;
+ }
+
+ total as f64 / accesses as f64
  }
 
  /// Visualizar marcos
@@ -313,6 +1108,9 @@ impl FrameManager {
  
  println!("║ {:3} ║ {} ║ {} ║ {} ║", 
  frame.frame_num, pid_str, page_str, time_str);
+ for (pid, page) in &frame.sharers {
+ println!("║ ║ {:4} ║ {:5} ║ (compartido, refs={}) ║", pid, page, frame.ref_count());
+ }
  }
  
  println!("╚═══════╩══════╩═══════╩══════════════════════╝");
@@ -322,6 +1120,9 @@ impl FrameManager {
  pub fn reset_stats(&mut self) {
  self.page_faults = 0;
  self.page_hits = 0;
+ self.tlb.reset_stats();
+ self.swap.reset_stats();
+ self.cow = CowStats::default();
  }
 
  /// Acceder a una página con Working Set
@@ -329,41 +1130,40 @@ impl FrameManager {
  pub fn access_page_working_set(&mut self, pid: u32, page_num: usize, window_size: usize) -> Result<usize, String> {
  self.current_time += 1;
 
- if !self.page_tables.contains_key(&pid) {
- self.create_page_table(pid);
- }
-
- let page_table = self.page_tables.get_mut(&pid).unwrap();
- 
- if let Some(frame_num) = page_table.access(page_num, self.current_time) {
+ if let Some(frame_num) = self.translate(pid, page_num) {
  // HIT
- self.page_hits += 1;
  return Ok(frame_num);
  }
 
  // MISS
  self.page_faults += 1;
+ if let Some(frame_idx) = self.map_resident_shm(pid, page_num) {
+ return Ok(frame_idx);
+ }
 
  // Buscar marco libre
- if let Some(free_idx) = self.frames.iter().position(|f| f.pid.is_none()) {
+ if let Some(free_idx) = self.free_frame_for(pid) {
  self.allocate_frame_lru(free_idx, pid, page_num);
  return Ok(free_idx);
  }
 
  // Reemplazar usando Working Set: página no accedida en ventana Δ
- let victim_idx = self.find_working_set_victim(window_size);
+ let victim_idx = self.find_working_set_victim(window_size, self.victim_owner(pid)?);
  self.replace_frame_lru(victim_idx, pid, page_num);
  Ok(victim_idx)
  }
 
  /// Encontrar víctima según Working Set
  /// Una página está fuera del working set si no fue accedida en las últimas Δ referencias
- fn find_working_set_victim(&self, window_size: usize) -> usize {
+ fn find_working_set_victim(&self, window_size: usize, owner: Option<u32>) -> usize {
  let threshold = self.current_time.saturating_sub(window_size as u64);
  let mut oldest_time = u64::MAX;
  let mut victim_idx = 0;
 
  for (idx, frame) in self.frames.iter().enumerate() {
+ if owner.is_some() && frame.pid != owner {
+ continue;
+ }
  if let Some(pid) = frame.pid {
  if let Some(page_num) = frame.page_num {
  if let Some(table) = self.page_tables.get(&pid) {
@@ -395,6 +1195,10 @@ pub struct MemoryStats {
  pub page_hits: u64,
  pub hit_rate: f64,
  pub total_accesses: u64,
+ pub tlb_hits: u64,
+ pub tlb_misses: u64,
+ pub tlb_hit_rate: f64,
+ pub effective_access_time: f64, // ns
 }
 
 #[cfg(test)]
@@ -452,4 +1256,223 @@ mod tests {
  assert!(stats.page_hits > 0);
  // Working Set debería tener mejor rendimiento que FIFO en esta secuencia
  }
+
+ #[test]
+ fn test_tlb_checked_before_page_table() {
+ let mut fm = FrameManager::new(3);
+ fm.configure_tlb(2, TlbPolicy::Lru, false);
+ fm.set_latencies(AccessLatencies { tlb_ns: 10, memory_ns: 100, page_fault_ns: 1000 });
+
+ // 1 y 2 fallan; luego 1, 2 aciertan en TLB; 3 falla y expulsa 1 de la TLB
+ // (sigue en memoria); 1 acierta en tabla de páginas pero falla en TLB
+ for page in [1, 2, 1, 2, 3, 1] {
+ let _ = fm.access_page_lru(1, page);
+ }
+
+ let stats = fm.stats();
+ assert_eq!(stats.page_faults, 3);
+ assert_eq!(stats.page_hits, 3);
+ assert_eq!(stats.tlb_hits, 2);
+ assert_eq!(stats.tlb_misses, 4);
+
+ // (6·10 + 2·100 + 4·200 + 3·1000) / 6
+ assert!((stats.effective_access_time - 4060.0 / 6.0).abs() < 1e-9);
+ }
+
+ #[test]
+ fn test_local_replacement_keeps_other_process_frames() {
+ let mut fm = FrameManager::new(4);
+ fm.configure_allocation(FrameAllocationPolicy::Equal, ReplacementScope::Local, PffConfig::default());
+ fm.rebalance(&[(1, 10), (2, 10)]);
+
+ for page in [1, 2] {
+ let _ = fm.access_page_lru(2, page);
+ }
+ for page in [1, 2, 3, 4, 5] {
+ let _ = fm.access_page_lru(1, page);
+ }
+
+ // El proceso 1 solo puede usar sus 2 marcos; el proceso 2 conserva los suyos
+ assert_eq!(fm.resident_frames(1), 2);
+ assert_eq!(fm.resident_frames(2), 2);
+
+ // Más procesos que marcos: el que se queda sin cuota no roba marcos ajenos
+ let mut fm = FrameManager::new(2);
+ fm.configure_allocation(FrameAllocationPolicy::Equal, ReplacementScope::Local, PffConfig::default());
+ fm.rebalance(&[(1, 10), (2, 10), (3, 10)]);
+ assert_eq!(fm.quota(3), Some(0));
+ fm.access_page_lru(1, 1).unwrap();
+ assert!(fm.access_page_lru(3, 1).is_err());
+ assert!(fm.access_page_fifo(3, 1).is_err());
+ assert_eq!(fm.resident_frames(1), 1);
+ assert_eq!(fm.resident_frames(3), 0);
+ }
+
+ #[test]
+ fn test_pff_grows_and_shrinks_quota() {
+ let mut fm = FrameManager::new(6);
+ let pff = PffConfig { enabled: true, lower: 0.2, upper: 0.5, window: 10 };
+ fm.configure_allocation(FrameAllocationPolicy::Equal, ReplacementScope::Local, pff);
+ fm.rebalance(&[(1, 10), (2, 10), (3, 10)]);
+
+ // Proceso 1: solo fallos; proceso 2: solo aciertos
+ for page in [1, 2, 3, 4] {
+ let _ = fm.access_page_lru(1, page);
+ }
+ let _ = fm.access_page_lru(2, 1);
+ for _ in 0..9 {
+ let _ = fm.access_page_lru(2, 1);
+ }
+ fm.release_process(3);
+
+ let changes = fm.adjust_pff();
+ assert!(changes.contains(&(1, 2, 3)));
+ assert!(changes.contains(&(2, 2, 1)));
+ }
+
+ #[test]
+ fn test_evicted_pages_go_to_swap() {
+ let mut fm = FrameManager::new(2);
+ fm.configure_swap(SwapConfig { slots: 8, ..SwapConfig::default() });
+
+ // 3 expulsa a 1 (page-out); volver a 1 la trae de swap (page-in)
+ for page in [1, 2, 3, 1] {
+ let _ = fm.access_page_fifo(1, page);
+ }
+
+ assert_eq!(fm.swap().page_outs(), 2);
+ assert_eq!(fm.swap().page_ins(), 1);
+ assert!(fm.swap().total_service_ns() > 0);
+
+ fm.release_process(1);
+ assert_eq!(fm.swap().used_slots(), 0);
+ }
+
+ #[test]
+ fn test_fork_copies_swapped_out_pages() {
+ let mut fm = FrameManager::new(2);
+ fm.configure_swap(SwapConfig { slots: 8, ..SwapConfig::default() });
+ fm.create_page_table(1);
+ for page in [0, 1, 2] {
+ let _ = fm.access_page_lru(1, page);
+ }
+ let parent_slot = fm.page_tables[&1].swap_slot(0);
+ assert!(parent_slot.is_some());
+
+ // El hijo hereda la página 0 en un slot propio que sobrevive al padre
+ fm.fork(1, 2);
+ let child_slot = fm.page_tables[&2].swap_slot(0);
+ assert!(child_slot.is_some());
+ assert_ne!(child_slot, parent_slot);
+ fm.release_process(1);
+ assert_eq!(fm.swap().used_slots(), 1);
+
+ let ins = fm.swap().page_ins();
+ let _ = fm.access_page_lru(2, 0);
+ assert_eq!(fm.swap().page_ins(), ins + 1);
+ }
+
+ #[test]
+ fn test_evicting_shared_cow_frame_keeps_child_copy() {
+ let mut fm = FrameManager::new(2);
+ fm.configure_swap(SwapConfig { slots: 8, ..SwapConfig::default() });
+ fm.create_page_table(1);
+ let _ = fm.access_page_lru(1, 0);
+ fm.fork(1, 2);
+
+ // El marco compartido es la víctima LRU: padre e hijo guardan su copia
+ let _ = fm.access_page_lru(1, 1);
+ let _ = fm.access_page_lru(1, 2);
+ let child_slot = fm.page_tables[&2].swap_slot(0);
+ assert!(child_slot.is_some());
+ assert_ne!(child_slot, fm.page_tables[&1].swap_slot(0));
+
+ // El fallo del hijo trae su página desde swap
+ let ins = fm.swap().page_ins();
+ let _ = fm.access_page_lru(2, 0);
+ fm.swap.service_pending(fm.current_time);
+ assert_eq!(fm.swap().page_ins(), ins + 1);
+ assert!(!fm.page_tables[&2].is_cow(0));
+ }
+
+ #[test]
+ fn test_translate_address() {
+ let mut fm = FrameManager::new(4);
+ fm.configure_addressing(AddressingConfig::new(4096, 32, 2, PageTableKind::MultiLevel).unwrap());
+
+ let first = fm.translate_address(1, 0x1a2b3).unwrap();
+ assert_eq!(first.page_num, 0x1a);
+ assert_eq!(first.offset, 0x2b3);
+ assert!(first.page_fault);
+ assert_eq!(first.physical_address, 0x2b3); // Marco 0
+
+ let _ = fm.translate_address(1, 0x5000).unwrap();
+ let again = fm.translate_address(1, 0x1a000).unwrap();
+ assert!(!again.page_fault);
+ assert_eq!(again.frame_num, 0);
+ }
+ 
+ #[test]
+ fn test_copy_on_write_fork() {
+ let mut fm = FrameManager::new(8);
+ fm.create_page_table(1);
+ for page in 0..4 {
+ let _ = fm.access_page_lru(1, page);
+ }
+
+ assert_eq!(fm.fork(1, 2), 4);
+ assert_eq!(fm.frames[0].ref_count(), 2);
+ assert_eq!(fm.resident_frames(2), 0); // El hijo no consume marcos propios
+
+ // El hijo lee sin fallos y escribe copiando solo una página
+ assert_eq!(fm.access_page_lru(2, 1).unwrap(), 1);
+ let copy = fm.write_page(2, 1, ReplacementAlgorithm::Lru).unwrap();
+ assert_ne!(copy, 1);
+ assert_eq!(fm.frames[1].ref_count(), 1);
+
+ // El padre quedó como único dueño: su escritura no copia
+ assert_eq!(fm.write_page(1, 1, ReplacementAlgorithm::Lru).unwrap(), 1);
+
+ let cow = fm.cow_stats();
+ assert_eq!((cow.cow_faults, cow.copies, cow.reclaimed), (2, 1, 1));
+ assert_eq!(cow.copies_avoided(), 3);
+
+ // Al terminar el padre, el hijo hereda los marcos compartidos
+ fm.release_process(1);
+ assert_eq!(fm.resident_frames(2), 4);
+ }
+ 
+ #[test]
+ fn test_shared_memory_segment() {
+ let mut fm = FrameManager::new(2);
+ fm.configure_swap(SwapConfig { slots: 8, ..SwapConfig::default() });
+ fm.shm_create("buf", 2).unwrap();
+ let base = fm.shm_attach(1, "buf", None).unwrap();
+ assert_eq!(fm.shm_attach(2, "buf", Some(40)).unwrap(), 40);
+
+ // Ambos procesos ven el mismo marco
+ let frame = fm.access_page_lru(1, base).unwrap();
+ assert_eq!(fm.access_page_lru(2, 40).unwrap(), frame);
+ assert_eq!(fm.frames[frame].ref_count(), 2);
+
+ // Expulsar el marco invalida los dos mapeos y guarda la página del segmento
+ let _ = fm.access_page_lru(1, 0);
+ let _ = fm.access_page_lru(1, 1);
+ assert_eq!(fm.shm().find("buf").unwrap().resident_pages(), 0);
+ assert!(fm.page_tables[&2].valid_pages().is_empty());
+ let _ = fm.access_page_lru(2, 40);
+ assert_eq!(fm.swap().page_ins(), 1);
+
+ // No se conecta encima de páginas privadas ya mapeadas
+ fm.shm_create("log", 1).unwrap();
+ assert!(fm.shm_attach(1, "log", Some(1)).is_err());
+ assert_eq!(fm.shm().find("log").unwrap().attach_count(), 0);
+
+ // Se destruye cuando se desconecta el último proceso
+ assert!(!fm.shm_destroy("buf").unwrap());
+ fm.shm_detach(2, "buf").unwrap();
+ fm.release_process(1);
+ assert!(fm.shm().find("buf").is_none());
+ assert_eq!(fm.swap().used_slots(), 0);
+ }
 }
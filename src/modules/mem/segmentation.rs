@@ -0,0 +1,283 @@
+//! Segmentación: tablas de segmentos con base, límite y permisos
+//! En modo puro los segmentos ocupan memoria contigua (first-fit);
+//! en modo paginado cada segmento es un rango de páginas virtuales gestionado por FrameManager
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use super::contiguous::{ContiguousAllocator, FitStrategy};
+
+/// Memoria física reservada para segmentación pura
+pub const SEGMENT_MEMORY: usize = 16384;
+/// Páginas virtuales reservadas por segmento en modo paginado
+pub const SEGMENT_SPAN_PAGES: usize = 0x100;
+/// Región del final del espacio virtual donde se ubican los segmentos paginados
+pub const SEGMENT_REGION_PAGES: usize = 4 * SEGMENT_SPAN_PAGES;
+
+/// Segmentos de un espacio de direcciones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SegmentKind {
+ Code,
+ Data,
+ Heap,
+ Stack,
+}
+
+impl SegmentKind {
+ pub const ALL: [SegmentKind; 4] = [SegmentKind::Code, SegmentKind::Data, SegmentKind::Heap, SegmentKind::Stack];
+
+ /// Número de segmento (selector)
+ pub fn index(self) -> usize {
+ match self {
+ SegmentKind::Code => 0,
+ SegmentKind::Data => 1,
+ SegmentKind::Heap => 2,
+ SegmentKind::Stack => 3,
+ }
+ }
+
+ /// Permisos por defecto: código ejecutable y de solo lectura, el resto lectura/escritura
+ pub fn default_permissions(self) -> Permissions {
+ match self {
+ SegmentKind::Code => Permissions { read: true, write: false, execute: true },
+ _ => Permissions { read: true, write: true, execute: false },
+ }
+ }
+}
+
+/// Tipo de acceso a memoria
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+ Read,
+ Write,
+ Execute,
+}
+
+/// Permisos rwx de un segmento
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permissions {
+ pub read: bool,
+ pub write: bool,
+ pub execute: bool,
+}
+
+impl Permissions {
+ pub fn allows(&self, access: AccessKind) -> bool {
+ match access {
+ AccessKind::Read => self.read,
+ AccessKind::Write => self.write,
+ AccessKind::Execute => self.execute,
+ }
+ }
+}
+
+impl fmt::Display for Permissions {
+ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+ write!(f, "{}{}{}",
+ if self.read { 'r' } else { '-' },
+ if self.write { 'w' } else { '-' },
+ if self.execute { 'x' } else { '-' })
+ }
+}
+
+/// Entrada de la tabla de segmentos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+ pub kind: SegmentKind,
+ pub base: usize, // Dirección física (puro) o primera página virtual (paginado)
+ pub limit: usize, // Tamaño en bytes
+ pub perms: Permissions,
+}
+
+/// Modelo de traducción de una tabla
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SegmentationMode {
+ Pure,
+ Paged,
+}
+
+/// Tabla de segmentos de un proceso
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentTable {
+ pub mode: SegmentationMode,
+ pub page_size: usize,
+ pub segments: Vec<Segment>,
+}
+
+impl SegmentTable {
+ pub fn segment(&self, kind: SegmentKind) -> Option<&Segment> {
+ self.segments.iter().find(|s| s.kind == kind)
+ }
+}
+
+/// Resultado de una traducción válida
+#[derive(Debug, Clone, PartialEq)]
+pub enum SegmentTarget {
+ Physical(usize),
+ Page { page: usize, offset: usize }, // Falta pasar por la tabla de páginas
+}
+
+/// Tablas de segmentos de todos los procesos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentationManager {
+ memory: ContiguousAllocator,
+ tables: HashMap<u32, SegmentTable>,
+ translations: u64,
+ faults: u64,
+}
+
+impl Default for SegmentationManager {
+ fn default() -> Self {
+ Self {
+ memory: ContiguousAllocator::new(SEGMENT_MEMORY),
+ tables: HashMap::new(),
+ translations: 0,
+ faults: 0,
+ }
+ }
+}
+
+impl SegmentationManager {
+ /// Crear la tabla de un proceso con los tamaños de código, datos, heap y pila
+ /// `virtual_pages` es el tamaño del espacio virtual; en modo paginado los segmentos
+ /// van en sus últimas SEGMENT_REGION_PAGES páginas, lejos de las páginas planas
+ pub fn create(&mut self, pid: u32, sizes: [usize; 4], mode: SegmentationMode, page_size: usize, virtual_pages: usize) -> Result<&SegmentTable, String> {
+ if self.tables.contains_key(&pid) {
+ return Err(format!("El proceso {} ya tiene tabla de segmentos", pid));
+ }
+ if mode == SegmentationMode::Paged && virtual_pages < SEGMENT_REGION_PAGES {
+ return Err(format!("El espacio virtual ({} páginas) no alcanza para los segmentos paginados ({} páginas)", virtual_pages, SEGMENT_REGION_PAGES));
+ }
+
+ let mut segments = Vec::new();
+ for (kind, limit) in SegmentKind::ALL.into_iter().zip(sizes) {
+ let base = match mode {
+ SegmentationMode::Pure if limit > 0 => match self.memory.alloc(pid, limit, FitStrategy::First) {
+ Ok(address) => address,
+ Err(e) => {
+ // Deshacer los segmentos ya ubicados
+ self.memory.free_pid(pid);
+ return Err(format!("Segmento {:?}: {}", kind, e));
+ }
+ },
+ SegmentationMode::Pure => 0,
+ SegmentationMode::Paged => {
+ if limit.div_ceil(page_size) > SEGMENT_SPAN_PAGES {
+ return Err(format!("Segmento {:?} supera {} páginas", kind, SEGMENT_SPAN_PAGES));
+ }
+ Self::paged_base(kind, virtual_pages)
+ }
+ };
+ segments.push(Segment { kind, base, limit, perms: kind.default_permissions() });
+ }
+
+ self.tables.insert(pid, SegmentTable { mode, page_size, segments });
+ Ok(&self.tables[&pid])
+ }
+
+ /// Primera página virtual de un segmento paginado
+ pub fn paged_base(kind: SegmentKind, virtual_pages: usize) -> usize {
+ virtual_pages - SEGMENT_REGION_PAGES + kind.index() * SEGMENT_SPAN_PAGES
+ }
+
+ pub fn table(&self, pid: u32) -> Option<&SegmentTable> {
+ self.tables.get(&pid)
+ }
+
+ /// Traducir (segmento, desplazamiento); todo error es una violación de segmento
+ pub fn translate(&mut self, pid: u32, kind: SegmentKind, offset: usize, access: AccessKind) -> Result<SegmentTarget, String> {
+ let table = self.tables.get(&pid)
+ .ok_or_else(|| format!("El proceso {} no tiene tabla de segmentos", pid))?;
+ self.translations += 1;
+
+ let checked = match table.segment(kind) {
+ None => Err(format!("segmento {:?} inexistente", kind)),
+ Some(seg) if offset >= seg.limit => Err(format!("desplazamiento {:#x} fuera del límite {:#x} de {:?}", offset, seg.limit, kind)),
+ Some(seg) if !seg.perms.allows(access) => Err(format!("acceso {:?} no permitido en {:?} ({})", access, kind, seg.perms)),
+ Some(seg) => Ok(match table.mode {
+ SegmentationMode::Pure => SegmentTarget::Physical(seg.base + offset),
+ SegmentationMode::Paged => SegmentTarget::Page {
+ page: seg.base + offset / table.page_size,
+ offset: offset % table.page_size,
+ },
+ }),
+ };
+ if checked.is_err() {
+ self.faults += 1;
+ }
+ checked
+ }
+
+ /// Liberar la tabla y la memoria de un proceso
+ pub fn release(&mut self, pid: u32) {
+ if let Some(table) = self.tables.remove(&pid) {
+ if table.mode == SegmentationMode::Pure {
+ self.memory.free_pid(pid);
+ }
+ }
+ }
+
+ pub fn faults(&self) -> u64 {
+ self.faults
+ }
+
+ pub fn display(&self) {
+ let mut pids: Vec<&u32> = self.tables.keys().collect();
+ pids.sort();
+
+ println!("\n╔═════╦══════════╦════════╦═════════════════╦══════════╦══════╗");
+ println!("║ PID ║ Modo ║ Seg ║ Base ║ Límite ║ Perm ║");
+ println!("╠═════╬══════════╬════════╬═════════════════╬══════════╬══════╣");
+ for pid in pids {
+ let table = &self.tables[pid];
+ let mode = match table.mode {
+ SegmentationMode::Pure => "puro",
+ SegmentationMode::Paged => "paginado",
+ };
+ for seg in &table.segments {
+ let base = match table.mode {
+ SegmentationMode::Pure => format!("{:#08x}", seg.base),
+ SegmentationMode::Paged => format!("pág {:#x}", seg.base),
+ };
+ println!("║ {:3} ║ {:8} ║ {:6} ║ {:>15} ║ {:8} ║ {}  ║",
+ pid, mode, format!("{:?}", seg.kind), base, seg.limit, seg.perms);
+ }
+ }
+ println!("╚═════╩══════════╩════════╩═════════════════╩══════════╩══════╝");
+ let free: usize = self.memory.stats().free_memory;
+ println!("Traducciones: {} | Violaciones de segmento: {} | Memoria libre (puro): {} B",
+ self.translations, self.faults, free);
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_pure_segmentation_faults() {
+ let mut manager = SegmentationManager::default();
+ manager.create(1, [1024, 512, 256, 256], SegmentationMode::Pure, 4096, 1 << 20).unwrap();
+
+ assert_eq!(manager.translate(1, SegmentKind::Data, 0x10, AccessKind::Write), Ok(SegmentTarget::Physical(1024 + 0x10)));
+ // Fuera del límite y escritura en código
+ assert!(manager.translate(1, SegmentKind::Data, 512, AccessKind::Read).is_err());
+ assert!(manager.translate(1, SegmentKind::Code, 0, AccessKind::Write).is_err());
+ assert_eq!(manager.faults(), 2);
+
+ manager.release(1);
+ assert_eq!(manager.memory.stats().free_memory, SEGMENT_MEMORY);
+ }
+
+ #[test]
+ fn test_paged_segment_maps_to_pages() {
+ let mut manager = SegmentationManager::default();
+ assert!(manager.create(2, [1024, 8192, 0, 4096], SegmentationMode::Paged, 4096, 0x100).is_err());
+ manager.create(2, [1024, 8192, 0, 4096], SegmentationMode::Paged, 4096, 1 << 20).unwrap();
+
+ // Los segmentos quedan al final del espacio virtual de 2^20 páginas
+ let target = manager.translate(2, SegmentKind::Data, 4096 + 12, AccessKind::Read).unwrap();
+ assert_eq!(target, SegmentTarget::Page { page: 0xffc00 + SEGMENT_SPAN_PAGES + 1, offset: 12 });
+ assert!(manager.translate(2, SegmentKind::Heap, 0, AccessKind::Read).is_err());
+ }
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Primera página virtual usada para conectar segmentos (si no se indica otra)
+pub const SHM_BASE_PAGE: usize = 0x1000;
+/// Dueño de las copias en swap de los segmentos (ningún proceso usa el PID 0)
+pub const SHM_SWAP_OWNER: u32 = 0;
+
+/// Segmento de memoria compartida estilo System V
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShmSegment {
+ pub id: u32,
+ pub name: String,
+ pub pages: usize,
+ frames: Vec<Option<usize>>, // Marco residente de cada página
+ swap_slots: Vec<Option<usize>>, // Copia de cada página en swap
+ attachments: Vec<(u32, usize)>, // (pid, página base)
+ pub marked_for_destroy: bool, // Se elimina al desconectarse el último
+}
+
+impl ShmSegment {
+ pub fn attach_count(&self) -> usize {
+ self.attachments.len()
+ }
+
+ pub fn resident_pages(&self) -> usize {
+ self.frames.iter().filter(|f| f.is_some()).count()
+ }
+
+ pub fn attachments(&self) -> &[(u32, usize)] {
+ &self.attachments
+ }
+
+ /// Página base donde está conectado el proceso
+ pub fn base_of(&self, pid: u32) -> Option<usize> {
+ self.attachments.iter().find(|(p, _)| *p == pid).map(|(_, base)| *base)
+ }
+
+ pub fn frame(&self, index: usize) -> Option<usize> {
+ self.frames[index]
+ }
+
+ pub fn swap_slot(&self, index: usize) -> Option<usize> {
+ self.swap_slots[index]
+ }
+
+ /// Marcos y slots que ocupa (para liberarlos al destruirlo)
+ pub fn resources(&self) -> (Vec<usize>, Vec<usize>) {
+ (self.frames.iter().flatten().copied().collect(), self.swap_slots.iter().flatten().copied().collect())
+ }
+}
+
+/// Registro de segmentos compartidos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShmRegistry {
+ segments: HashMap<u32, ShmSegment>,
+ next_id: u32,
+}
+
+impl Default for ShmRegistry {
+ fn default() -> Self {
+ Self {
+ segments: HashMap::new(),
+ next_id: 1,
+ }
+ }
+}
+
+impl ShmRegistry {
+ /// Crear segmento (shmget)
+ pub fn create(&mut self, name: &str, pages: usize) -> Result<u32, String> {
+ if pages == 0 {
+ return Err("El segmento debe tener al menos una página".to_string());
+ }
+ if self.find(name).is_some() {
+ return Err(format!("El segmento '{}' ya existe", name));
+ }
+
+ let id = self.next_id;
+ self.next_id += 1;
+ self.segments.insert(id, ShmSegment {
+ id,
+ name: name.to_string(),
+ pages,
+ frames: vec![None; pages],
+ swap_slots: vec![None; pages],
+ attachments: Vec::new(),
+ marked_for_destroy: false,
+ });
+ Ok(id)
+ }
+
+ pub fn find(&self, name: &str) -> Option<&ShmSegment> {
+ self.segments.values().find(|s| s.name == name)
+ }
+
+ pub fn get(&self, id: u32) -> Option<&ShmSegment> {
+ self.segments.get(&id)
+ }
+
+ /// Conectar un proceso (shmat) en la página base indicada o en la del segmento
+ pub fn attach(&mut self, pid: u32, name: &str, base: Option<usize>) -> Result<(u32, usize), String> {
+ let seg = self.find(name).ok_or_else(|| format!("Segmento '{}' no encontrado", name))?;
+ if seg.marked_for_destroy {
+ return Err(format!("El segmento '{}' está marcado para destrucción", name));
+ }
+ if seg.base_of(pid).is_some() {
+ return Err(format!("El proceso {} ya está conectado a '{}'", pid, name));
+ }
+
+ let (id, pages) = (seg.id, seg.pages);
+ let base = base.unwrap_or(SHM_BASE_PAGE + (id as usize - 1) * 0x100);
+ let overlap = self.segments.values().any(|s| {
+ s.base_of(pid).is_some_and(|b| base < b + s.pages && b < base + pages)
+ });
+ if overlap {
+ return Err(format!("Las páginas {:#x}-{:#x} ya están ocupadas por otro segmento", base, base + pages - 1));
+ }
+
+ self.segments.get_mut(&id).unwrap().attachments.push((pid, base));
+ Ok((id, base))
+ }
+
+ /// Desconectar un proceso (shmdt); devuelve (id, base, páginas)
+ pub fn detach(&mut self, pid: u32, name: &str) -> Result<(u32, usize, usize), String> {
+ let seg = self.segments.values_mut()
+ .find(|s| s.name == name)
+ .ok_or_else(|| format!("Segmento '{}' no encontrado", name))?;
+ let base = seg.base_of(pid).ok_or_else(|| format!("El proceso {} no está conectado a '{}'", pid, name))?;
+ seg.attachments.retain(|(p, _)| *p != pid);
+ Ok((seg.id, base, seg.pages))
+ }
+
+ /// Marcar para destrucción (IPC_RMID); devuelve true si ya puede eliminarse
+ pub fn mark_destroy(&mut self, name: &str) -> Result<(u32, bool), String> {
+ let seg = self.segments.values_mut()
+ .find(|s| s.name == name)
+ .ok_or_else(|| format!("Segmento '{}' no encontrado", name))?;
+ seg.marked_for_destroy = true;
+ Ok((seg.id, seg.attachments.is_empty()))
+ }
+
+ /// Eliminar un segmento marcado sin procesos conectados
+ pub fn remove_if_unused(&mut self, id: u32) -> Option<ShmSegment> {
+ match self.segments.get(&id) {
+ Some(seg) if seg.marked_for_destroy && seg.attachments.is_empty() => self.segments.remove(&id),
+ _ => None,
+ }
+ }
+
+ /// Segmento y página del segmento que corresponden a (pid, página virtual)
+ pub fn lookup(&self, pid: u32, page_num: usize) -> Option<(u32, usize)> {
+ self.segments.values().find_map(|s| {
+ s.base_of(pid)
+ .filter(|base| page_num >= *base && page_num < base + s.pages)
+ .map(|base| (s.id, page_num - base))
+ })
+ }
+
+ /// Segmentos a los que está conectado un proceso
+ pub fn attached_to(&self, pid: u32) -> Vec<String> {
+ let mut names: Vec<String> = self.segments.values()
+ .filter(|s| s.base_of(pid).is_some())
+ .map(|s| s.name.clone())
+ .collect();
+ names.sort();
+ names
+ }
+
+ /// Registrar que una página quedó residente en un marco
+ pub fn set_frame(&mut self, id: u32, index: usize, frame: Option<usize>) {
+ if let Some(seg) = self.segments.get_mut(&id) {
+ seg.frames[index] = frame;
+ }
+ }
+
+ pub fn set_swap_slot(&mut self, id: u32, index: usize, slot: usize) {
+ if let Some(seg) = self.segments.get_mut(&id) {
+ seg.swap_slots[index] = Some(slot);
+ }
+ }
+
+ pub fn segments(&self) -> Vec<&ShmSegment> {
+ let mut segs: Vec<&ShmSegment> = self.segments.values().collect();
+ segs.sort_by_key(|s| s.id);
+ segs
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_attach_lookup_detach() {
+ let mut shm = ShmRegistry::default();
+ shm.create("buf", 4).unwrap();
+ assert!(shm.create("buf", 2).is_err());
+
+ let (id, base) = shm.attach(1, "buf", None).unwrap();
+ assert_eq!(base, SHM_BASE_PAGE);
+ shm.attach(2, "buf", Some(10)).unwrap();
+ assert_eq!(shm.lookup(2, 12), Some((id, 2)));
+ assert_eq!(shm.lookup(2, 14), None);
+
+ // IPC_RMID con procesos conectados: se elimina al salir el último
+ assert_eq!(shm.mark_destroy("buf").unwrap(), (id, false));
+ shm.detach(1, "buf").unwrap();
+ assert!(shm.remove_if_unused(id).is_none());
+ shm.detach(2, "buf").unwrap();
+ assert!(shm.remove_if_unused(id).is_some());
+ }
+}
@@ -0,0 +1,282 @@
+//! Slab allocator: cachés de objetos de tamaño fijo sobre el buddy allocator
+
+use serde::{Deserialize, Serialize};
+use super::zone::{ZoneKind, ZonedHeap};
+
+/// Tamaño mínimo de un slab (se duplica hasta que entren MIN_OBJECTS objetos)
+pub const SLAB_MIN_SIZE: usize = 256;
+const MIN_OBJECTS: usize = 4;
+/// Alineación de los objetos y paso del coloreado
+const ALIGN: usize = 8;
+/// Los slabs pertenecen al kernel, no a un proceso
+pub const KERNEL_PID: u32 = 0;
+
+/// Estado de un slab según sus objetos en uso
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlabState {
+ Full,
+ Partial,
+ Empty,
+}
+
+/// Slab: bloque del buddy dividido en objetos
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Slab {
+ pub address: usize, // Dirección del bloque en el heap buddy
+ pub color: usize, // Desplazamiento del primer objeto
+ in_use: Vec<bool>,
+}
+
+impl Slab {
+ pub fn used(&self) -> usize {
+ self.in_use.iter().filter(|u| **u).count()
+ }
+
+ pub fn state(&self) -> SlabState {
+ match self.used() {
+ 0 => SlabState::Empty,
+ n if n == self.in_use.len() => SlabState::Full,
+ _ => SlabState::Partial,
+ }
+ }
+}
+
+/// Caché de objetos de un tipo (ej: pcb de 96 bytes)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlabCache {
+ pub name: String,
+ pub object_size: usize, // Tamaño pedido
+ pub aligned_size: usize, // Tamaño real de cada objeto
+ pub slab_size: usize,
+ pub objects_per_slab: usize,
+ colors: usize, // Cantidad de colores posibles
+ next_color: usize,
+ slabs: Vec<Slab>,
+
+ // Métricas
+ pub allocations: u64,
+ pub frees: u64,
+}
+
+impl SlabCache {
+ fn new(name: &str, object_size: usize) -> Self {
+ let aligned_size = object_size.div_ceil(ALIGN) * ALIGN;
+ let mut slab_size = SLAB_MIN_SIZE;
+ while slab_size / aligned_size < MIN_OBJECTS {
+ slab_size *= 2;
+ }
+ let objects_per_slab = slab_size / aligned_size;
+ let leftover = slab_size - objects_per_slab * aligned_size;
+
+ Self {
+ name: name.to_string(),
+ object_size,
+ aligned_size,
+ slab_size,
+ objects_per_slab,
+ colors: leftover / ALIGN + 1,
+ next_color: 0,
+ slabs: Vec::new(),
+ allocations: 0,
+ frees: 0,
+ }
+ }
+
+ pub fn slabs(&self) -> &[Slab] {
+ &self.slabs
+ }
+
+ pub fn count(&self, state: SlabState) -> usize {
+ self.slabs.iter().filter(|s| s.state() == state).count()
+ }
+
+ pub fn objects_in_use(&self) -> usize {
+ self.slabs.iter().map(|s| s.used()).sum()
+ }
+
+ pub fn capacity(&self) -> usize {
+ self.slabs.len() * self.objects_per_slab
+ }
+
+ /// Porcentaje de objetos ocupados sobre la capacidad de los slabs
+ pub fn utilization(&self) -> f64 {
+ if self.capacity() == 0 {
+ 0.0
+ } else {
+ self.objects_in_use() as f64 / self.capacity() as f64 * 100.0
+ }
+ }
+
+ /// Bytes desperdiciados: relleno de alineación de los objetos en uso y sobrante de cada slab
+ pub fn internal_waste(&self) -> usize {
+ let padding = self.objects_in_use() * (self.aligned_size - self.object_size);
+ let leftover = self.slabs.len() * (self.slab_size - self.objects_per_slab * self.aligned_size);
+ padding + leftover
+ }
+
+ fn object_address(&self, slab: &Slab, index: usize) -> usize {
+ slab.address + slab.color + index * self.aligned_size
+ }
+}
+
+/// Conjunto de cachés con nombre
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlabAllocator {
+ caches: Vec<SlabCache>,
+}
+
+impl SlabAllocator {
+ /// Crear caché (slab-create pcb 96)
+ pub fn create_cache(&mut self, name: &str, object_size: usize) -> Result<&SlabCache, String> {
+ if object_size == 0 {
+ return Err("El tamaño de objeto debe ser mayor que 0".to_string());
+ }
+ if self.cache(name).is_some() {
+ return Err(format!("La caché '{}' ya existe", name));
+ }
+ self.caches.push(SlabCache::new(name, object_size));
+ Ok(self.caches.last().unwrap())
+ }
+
+ pub fn cache(&self, name: &str) -> Option<&SlabCache> {
+ self.caches.iter().find(|c| c.name == name)
+ }
+
+ /// Caché cuyo slab ocupa el bloque del buddy que empieza en `address`
+ pub fn cache_of_slab(&self, address: usize) -> Option<&str> {
+ self.caches.iter()
+ .find(|c| c.slabs.iter().any(|s| s.address == address))
+ .map(|c| c.name.as_str())
+ }
+
+ fn cache_mut(&mut self, name: &str) -> Result<&mut SlabCache, String> {
+ self.caches.iter_mut()
+ .find(|c| c.name == name)
+ .ok_or_else(|| format!("Caché '{}' no encontrada", name))
+ }
+
+ /// Asignar un objeto: primero de un slab parcial, luego de uno vacío y si no, de un slab nuevo
+ pub fn alloc(&mut self, name: &str, heap: &mut ZonedHeap) -> Result<usize, String> {
+ let cache = self.cache_mut(name)?;
+
+ let slab_idx = match cache.slabs.iter().position(|s| s.state() == SlabState::Partial)
+ .or_else(|| cache.slabs.iter().position(|s| s.state() == SlabState::Empty))
+ {
+ Some(idx) => idx,
+ None => {
+ let (address, _) = heap.alloc(KERNEL_PID, cache.slab_size, ZoneKind::Normal)
+ .map_err(|e| format!("Sin memoria para un slab de '{}': {}", name, e))?;
+ let color = (cache.next_color % cache.colors) * ALIGN;
+ cache.next_color += 1;
+ cache.slabs.push(Slab {
+ address,
+ color,
+ in_use: vec![false; cache.objects_per_slab],
+ });
+ cache.slabs.len() - 1
+ }
+ };
+
+ let index = cache.slabs[slab_idx].in_use.iter().position(|u| !u).unwrap();
+ cache.slabs[slab_idx].in_use[index] = true;
+ cache.allocations += 1;
+ Ok(cache.object_address(&cache.slabs[slab_idx], index))
+ }
+
+ /// Liberar un objeto; el slab queda en la caché aunque se vacíe
+ pub fn free(&mut self, name: &str, address: usize) -> Result<(), String> {
+ let cache = self.cache_mut(name)?;
+ let (slab_idx, index) = cache.slabs.iter()
+ .enumerate()
+ .find_map(|(i, slab)| {
+ let offset = address.checked_sub(slab.address + slab.color)?;
+ let index = offset / cache.aligned_size;
+ (address < slab.address + cache.slab_size && offset.is_multiple_of(cache.aligned_size)
+ && index < cache.objects_per_slab).then_some((i, index))
+ })
+ .ok_or_else(|| format!("La dirección {:#x} no es un objeto de '{}'", address, name))?;
+
+ let slot = &mut cache.slabs[slab_idx].in_use[index];
+ if !*slot {
+ return Err(format!("El objeto {:#x} de '{}' ya estaba libre", address, name));
+ }
+ *slot = false;
+ cache.frees += 1;
+ Ok(())
+ }
+
+ /// Devolver al buddy los slabs vacíos de una caché; devuelve cuántos
+ pub fn shrink(&mut self, name: &str, heap: &mut ZonedHeap) -> Result<usize, String> {
+ let cache = self.cache_mut(name)?;
+ let (empty, kept): (Vec<Slab>, Vec<Slab>) = std::mem::take(&mut cache.slabs)
+ .into_iter()
+ .partition(|s| s.state() == SlabState::Empty);
+ cache.slabs = kept;
+ for slab in &empty {
+ heap.free(slab.address)?;
+ }
+ Ok(empty.len())
+ }
+
+ /// Reporte por caché: slabs por estado, utilización y desperdicio
+ pub fn report(&self) {
+ println!("\n╔════════════╦════════╦═══════╦══════════════════╦═════════════╦═════════╦══════════╗");
+ println!("║ Caché ║ Objeto ║ Slab ║ Full/Part/Empty ║ En uso ║ Uso ║ Desperd. ║");
+ println!("╠════════════╬════════╬═══════╬══════════════════╬═════════════╬═════════╬══════════╣");
+ for cache in &self.caches {
+ let states = format!("{}/{}/{}", cache.count(SlabState::Full), cache.count(SlabState::Partial), cache.count(SlabState::Empty));
+ let objects = format!("{}/{}", cache.objects_in_use(), cache.capacity());
+ println!("║ {:10} ║ {:4} B ║ {:5} ║ {:16} ║ {:11} ║ {:6.1}% ║ {:6} B ║",
+ cache.name, cache.object_size, cache.slab_size, states, objects, cache.utilization(), cache.internal_waste());
+ }
+ println!("╚════════════╩════════╩═══════╩══════════════════╩═════════════╩═════════╩══════════╝");
+
+ for cache in &self.caches {
+ let colors: Vec<String> = cache.slabs.iter().map(|s| format!("{:#x}+{}", s.address, s.color)).collect();
+ if !colors.is_empty() {
+ println!("{}: {} objetos/slab, slabs (dirección+color): {}", cache.name, cache.objects_per_slab, colors.join(", "));
+ }
+ }
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_slab_states_and_coloring() {
+ let mut buddy = ZonedHeap::default();
+ let mut slab = SlabAllocator::default();
+ let cache = slab.create_cache("pcb", 96).unwrap();
+ assert_eq!((cache.slab_size, cache.objects_per_slab), (512, 5));
+
+ let objs: Vec<usize> = (0..6).map(|_| slab.alloc("pcb", &mut buddy).unwrap()).collect();
+ let cache = slab.cache("pcb").unwrap();
+ assert_eq!(cache.count(SlabState::Full), 1);
+ assert_eq!(cache.count(SlabState::Partial), 1);
+ // El segundo slab arranca con otro color
+ assert_eq!(cache.slabs()[0].color, 0);
+ assert_eq!(cache.slabs()[1].color, 8);
+ assert_eq!(objs[5], cache.slabs()[1].address + 8);
+
+ slab.free("pcb", objs[5]).unwrap();
+ assert!(slab.free("pcb", objs[5]).is_err());
+ assert_eq!(slab.cache("pcb").unwrap().count(SlabState::Empty), 1);
+ assert_eq!(slab.shrink("pcb", &mut buddy).unwrap(), 1);
+ assert_eq!(buddy.stats().allocated_memory, 512);
+ }
+
+ #[test]
+ fn test_internal_waste() {
+ let mut buddy = ZonedHeap::default();
+ let mut slab = SlabAllocator::default();
+ slab.create_cache("sem", 20).unwrap();
+ slab.alloc("sem", &mut buddy).unwrap();
+
+ let cache = slab.cache("sem").unwrap();
+ // 20 -> 24 bytes; 256 / 24 = 10 objetos, sobran 16 bytes
+ assert_eq!(cache.objects_per_slab, 10);
+ assert_eq!(cache.internal_waste(), 4 + 16);
+ }
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use crate::modules::disk::scheduler::{DiskRequest, DiskSchedulerKind};
+
+/// Configuración del área de swap sobre el disco simulado
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SwapConfig {
+ pub slots: usize, // 0 = sin swap
+ pub start_cylinder: usize, // Primer cilindro del área de swap
+ pub slots_per_cylinder: usize,
+ pub scheduler: DiskSchedulerKind,
+ pub seek_ns: u64, // Costo por cilindro recorrido
+ pub transfer_ns: u64, // Costo de transferir una página
+}
+
+impl Default for SwapConfig {
+ fn default() -> Self {
+ Self {
+ slots: 0,
+ start_cylinder: 100,
+ slots_per_cylinder: 8,
+ scheduler: DiskSchedulerKind::Fcfs,
+ seek_ns: 100_000, // 0.1 ms por cilindro
+ transfer_ns: 1_000_000, // 1 ms por página
+ }
+ }
+}
+
+/// Sentido de una transferencia entre memoria y swap
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapOp {
+ PageIn,
+ PageOut,
+}
+
+/// Transferencia pendiente de enviar al disco
+#[derive(Debug, Clone)]
+pub struct SwapIo {
+ pub op: SwapOp,
+ pub pid: u32,
+ pub page_num: usize,
+ pub slot: usize,
+}
+
+/// Área de swap: slots de página mapeados a cilindros del disco
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SwapSpace {
+ config: SwapConfig,
+ slots: Vec<Option<(u32, usize)>>, // slot -> (pid, página)
+ head: usize, // Posición del cabezal
+ #[serde(skip)]
+ pending: Vec<SwapIo>,
+
+ // Métricas
+ page_ins: u64,
+ page_outs: u64,
+ total_movement: usize,
+ total_service_ns: u64,
+ last_service_ns: u64,
+ swap_full: u64, // Páginas descartadas por falta de slots
+}
+
+impl SwapSpace {
+ pub fn new(config: SwapConfig) -> Self {
+ Self {
+ config,
+ slots: vec![None; config.slots],
+ head: config.start_cylinder,
+ ..Self::default()
+ }
+ }
+
+ pub fn is_enabled(&self) -> bool {
+ !self.slots.is_empty()
+ }
+
+ /// Cilindro donde vive un slot
+ pub fn slot_cylinder(&self, slot: usize) -> usize {
+ self.config.start_cylinder + slot / self.config.slots_per_cylinder.max(1)
+ }
+
+ /// Encolar la escritura de una página expulsada
+ /// Reutiliza su slot si ya tenía uno; devuelve el slot usado
+ pub fn queue_page_out(&mut self, pid: u32, page_num: usize, slot: Option<usize>) -> Option<usize> {
+ let slot = match slot {
+ Some(slot) => slot,
+ None => match self.slots.iter().position(|s| s.is_none()) {
+ Some(free) => free,
+ None => {
+ self.swap_full += 1;
+ println!("[SWAP] Área de swap llena: página {} del proceso {} descartada", page_num, pid);
+ return None;
+ }
+ },
+ };
+
+ self.slots[slot] = Some((pid, page_num));
+ self.pending.push(SwapIo { op: SwapOp::PageOut, pid, page_num, slot });
+ Some(slot)
+ }
+
+ /// Encolar la lectura de una página desde su slot
+ pub fn queue_page_in(&mut self, pid: u32, page_num: usize, slot: usize) {
+ self.pending.push(SwapIo { op: SwapOp::PageIn, pid, page_num, slot });
+ }
+
+ /// Liberar los slots de un proceso que terminó
+ pub fn free_slots(&mut self, pid: u32) {
+ for slot in self.slots.iter_mut() {
+ if matches!(slot, Some((owner, _)) if *owner == pid) {
+ *slot = None;
+ }
+ }
+ }
+
+ /// Liberar un slot concreto
+ pub fn free_slot(&mut self, slot: usize) {
+ if let Some(entry) = self.slots.get_mut(slot) {
+ *entry = None;
+ }
+ }
+
+ /// Atender las transferencias pendientes con el planificador de disco elegido
+ /// Devuelve el tiempo de servicio en ns (búsqueda + transferencia)
+ pub fn service_pending(&mut self, time: u64) -> u64 {
+ if self.pending.is_empty() {
+ return 0;
+ }
+
+ let pending: Vec<SwapIo> = self.pending.drain(..).collect();
+ let mut scheduler = self.config.scheduler.build();
+ for io in &pending {
+ scheduler.add_request(DiskRequest {
+ pid: io.pid,
+ cylinder: self.slot_cylinder(io.slot),
+ timestamp: time,
+ });
+ }
+
+ let start = self.head;
+ let mut movement = 0;
+ while let Some(request) = scheduler.next_request(self.head) {
+ movement += request.cylinder.abs_diff(self.head);
+ self.head = request.cylinder;
+ }
+
+ for io in &pending {
+ match io.op {
+ SwapOp::PageIn => self.page_ins += 1,
+ SwapOp::PageOut => self.page_outs += 1,
+ }
+ }
+
+ let service = movement as u64 * self.config.seek_ns + pending.len() as u64 * self.config.transfer_ns;
+ self.total_movement += movement;
+ self.total_service_ns += service;
+ self.last_service_ns = service;
+
+ let ops: Vec<String> = pending.iter()
+ .map(|io| format!("{} P{}:{} slot {}",
+ if io.op == SwapOp::PageIn { "in" } else { "out" }, io.pid, io.page_num, io.slot))
+ .collect();
+ println!("[SWAP] {} | {}: cabezal {} -> {} ({} cil), servicio {} ns",
+ ops.join(", "), scheduler.name(), start, self.head, movement, service);
+
+ service
+ }
+
+ pub fn used_slots(&self) -> usize {
+ self.slots.iter().filter(|s| s.is_some()).count()
+ }
+
+ pub fn page_ins(&self) -> u64 {
+ self.page_ins
+ }
+
+ pub fn page_outs(&self) -> u64 {
+ self.page_outs
+ }
+
+ pub fn total_movement(&self) -> usize {
+ self.total_movement
+ }
+
+ pub fn total_service_ns(&self) -> u64 {
+ self.total_service_ns
+ }
+
+ /// Resetear métricas
+ pub fn reset_stats(&mut self) {
+ self.page_ins = 0;
+ self.page_outs = 0;
+ self.total_movement = 0;
+ self.total_service_ns = 0;
+ self.last_service_ns = 0;
+ self.swap_full = 0;
+ }
+
+ /// Visualizar el área de swap
+ pub fn display(&self) {
+ println!("\n╔════════════════════════════════════════════════════╗");
+ println!("║ ÁREA DE SWAP ({:?}) ", self.config.scheduler);
+ println!("╠════════════════════════════════════════════════════╣");
+ println!("║ Slots usados: {}/{} ", self.used_slots(), self.slots.len());
+ println!("║ Cilindros: {}-{} | Cabezal: {} ", self.config.start_cylinder,
+ self.slot_cylinder(self.slots.len().saturating_sub(1)), self.head);
+ println!("║ Page-ins: {} | Page-outs: {} ", self.page_ins, self.page_outs);
+ println!("║ Movimiento total: {} cilindros ", self.total_movement);
+ println!("║ Tiempo de servicio total: {} ns ", self.total_service_ns);
+ println!("║ Páginas descartadas (swap lleno): {} ", self.swap_full);
+ println!("╠════════════════════════════════════════════════════╣");
+
+ for (slot, entry) in self.slots.iter().enumerate() {
+ if let Some((pid, page)) = entry {
+ println!("║ Slot {:3} (cil {:3}): PID {} página {} ", slot, self.slot_cylinder(slot), pid, page);
+ }
+ }
+
+ println!("╚════════════════════════════════════════════════════╝");
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_swap_slots_and_service_time() {
+ let mut swap = SwapSpace::new(SwapConfig {
+ slots: 16,
+ start_cylinder: 100,
+ slots_per_cylinder: 4,
+ scheduler: DiskSchedulerKind::Sstf,
+ seek_ns: 10,
+ transfer_ns: 1000,
+ });
+
+ let slot = swap.queue_page_out(1, 7, None).unwrap();
+ assert_eq!(slot, 0);
+ // 100 -> 100: solo transferencia
+ assert_eq!(swap.service_pending(1), 1000);
+
+ for page in 0..5 {
+ swap.queue_page_out(2, page, None);
+ }
+ swap.service_pending(2);
+ // Slots 1..5 ocupan los cilindros 100 y 101
+ assert_eq!(swap.total_movement(), 1);
+
+ swap.queue_page_in(1, 7, slot);
+ swap.service_pending(3);
+ assert_eq!(swap.page_ins(), 1);
+ assert_eq!(swap.page_outs(), 6);
+
+ swap.free_slots(2);
+ assert_eq!(swap.used_slots(), 1);
+ }
+
+ #[test]
+ fn test_swap_full() {
+ let mut swap = SwapSpace::new(SwapConfig { slots: 1, ..SwapConfig::default() });
+ assert!(swap.queue_page_out(1, 0, None).is_some());
+ assert!(swap.queue_page_out(1, 1, None).is_none());
+ }
+}
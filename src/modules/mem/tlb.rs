@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+
+/// Política de reemplazo de la TLB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TlbPolicy {
+ Lru,
+ Fifo,
+}
+
+/// Entrada de la TLB (etiquetada con el PID, como un ASID)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlbEntry {
+ pub pid: u32,
+ pub page_num: usize,
+ pub frame_num: usize,
+ pub load_time: u64, // Para FIFO
+ pub last_access: u64, // Para LRU
+}
+
+/// Latencias simuladas (en nanosegundos) para el tiempo efectivo de acceso
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AccessLatencies {
+ pub tlb_ns: u64,
+ pub memory_ns: u64,
+ pub page_fault_ns: u64,
+}
+
+impl Default for AccessLatencies {
+ fn default() -> Self {
+ Self {
+ tlb_ns: 20,
+ memory_ns: 100,
+ page_fault_ns: 8_000_000, // 8 ms
+ }
+ }
+}
+
+/// Translation Lookaside Buffer
+/// Caché de traducciones página -> marco consultada antes de la tabla de páginas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tlb {
+ capacity: usize, // 0 = TLB desactivada
+ policy: TlbPolicy,
+ flush_on_switch: bool,
+ entries: Vec<TlbEntry>,
+ current_pid: Option<u32>,
+
+ // Métricas
+ hits: u64,
+ misses: u64,
+ flushes: u64,
+}
+
+impl Default for Tlb {
+ fn default() -> Self {
+ Self::new(0, TlbPolicy::Lru, false)
+ }
+}
+
+impl Tlb {
+ pub fn new(capacity: usize, policy: TlbPolicy, flush_on_switch: bool) -> Self {
+ Self {
+ capacity,
+ policy,
+ flush_on_switch,
+ entries: Vec::with_capacity(capacity),
+ current_pid: None,
+ hits: 0,
+ misses: 0,
+ flushes: 0,
+ }
+ }
+
+ pub fn is_enabled(&self) -> bool {
+ self.capacity > 0
+ }
+
+ /// Buscar traducción (devuelve marco si hay TLB hit)
+ pub fn lookup(&mut self, pid: u32, page_num: usize, time: u64) -> Option<usize> {
+ if !self.is_enabled() {
+ return None;
+ }
+
+ if let Some(entry) = self.entries.iter_mut().find(|e| e.pid == pid && e.page_num == page_num) {
+ entry.last_access = time;
+ self.hits += 1;
+ Some(entry.frame_num)
+ } else {
+ self.misses += 1;
+ None
+ }
+ }
+
+ /// Insertar traducción (reemplaza según la política si está llena)
+ pub fn insert(&mut self, pid: u32, page_num: usize, frame_num: usize, time: u64) {
+ if !self.is_enabled() {
+ return;
+ }
+
+ self.invalidate(pid, page_num);
+
+ if self.entries.len() >= self.capacity {
+ let victim_idx = match self.policy {
+ TlbPolicy::Lru => self.entries.iter().enumerate().min_by_key(|(_, e)| e.last_access),
+ TlbPolicy::Fifo => self.entries.iter().enumerate().min_by_key(|(_, e)| e.load_time),
+ }
+ .map(|(idx, _)| idx)
+ .unwrap();
+ self.entries.remove(victim_idx);
+ }
+
+ self.entries.push(TlbEntry {
+ pid,
+ page_num,
+ frame_num,
+ load_time: time,
+ last_access: time,
+ });
+ }
+
+ /// Invalidar traducción (cuando la página sale de memoria)
+ pub fn invalidate(&mut self, pid: u32, page_num: usize) {
+ self.entries.retain(|e| !(e.pid == pid && e.page_num == page_num));
+ }
+
+ /// Vaciar la TLB completa
+ pub fn flush(&mut self) {
+ self.entries.clear();
+ self.flushes += 1;
+ }
+
+ /// Notificar cambio de contexto (vacía la TLB si está configurado)
+ pub fn context_switch(&mut self, pid: u32) {
+ if self.current_pid == Some(pid) {
+ return;
+ }
+ if self.flush_on_switch && self.current_pid.is_some() && self.is_enabled() {
+ self.flush();
+ }
+ self.current_pid = Some(pid);
+ }
+
+ pub fn capacity(&self) -> usize {
+ self.capacity
+ }
+
+ pub fn policy(&self) -> TlbPolicy {
+ self.policy
+ }
+
+ pub fn flush_on_switch(&self) -> bool {
+ self.flush_on_switch
+ }
+
+ pub fn hits(&self) -> u64 {
+ self.hits
+ }
+
+ pub fn misses(&self) -> u64 {
+ self.misses
+ }
+
+ pub fn flushes(&self) -> u64 {
+ self.flushes
+ }
+
+ /// Tasa de aciertos de la TLB (porcentaje)
+ pub fn hit_ratio(&self) -> f64 {
+ if self.hits + self.misses > 0 {
+ (self.hits as f64 / (self.hits + self.misses) as f64) * 100.0
+ } else {
+ 0.0
+ }
+ }
+
+ /// Resetear métricas
+ pub fn reset_stats(&mut self) {
+ self.hits = 0;
+ self.misses = 0;
+ self.flushes = 0;
+ }
+
+ /// Visualizar entradas de la TLB
+ pub fn display(&self) {
+ println!("\n╔════════════════════════════════════════════╗");
+ println!("║ TLB ({} entradas, {:?}) ", self.capacity, self.policy);
+ println!("╠═══════╦══════╦═══════╦═══════╦═════════════╣");
+ println!("║ Slot ║ PID ║ Pág # ║ Marco ║ Último uso ║");
+ println!("╠═══════╬══════╬═══════╬═══════╬═════════════╣");
+
+ for (slot, entry) in self.entries.iter().enumerate() {
+ println!("║ {:3} ║ {:4} ║ {:5} ║ {:5} ║ {:11} ║",
+ slot, entry.pid, entry.page_num, entry.frame_num, entry.last_access);
+ }
+
+ println!("╚═══════╩══════╩═══════╩═══════╩═════════════╝");
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_tlb_lru_eviction() {
+ let mut tlb = Tlb::new(2, TlbPolicy::Lru, false);
+ tlb.insert(1, 10, 0, 1);
+ tlb.insert(1, 11, 1, 2);
+
+ // Usar página 10 para que 11 sea la menos reciente
+ assert_eq!(tlb.lookup(1, 10, 3), Some(0));
+ tlb.insert(1, 12, 2, 4);
+
+ assert_eq!(tlb.lookup(1, 11, 5), None);
+ assert_eq!(tlb.lookup(1, 10, 6), Some(0));
+ assert_eq!(tlb.hits(), 2);
+ assert_eq!(tlb.misses(), 1);
+ }
+
+ #[test]
+ fn test_tlb_fifo_eviction() {
+ let mut tlb = Tlb::new(2, TlbPolicy::Fifo, false);
+ tlb.insert(1, 10, 0, 1);
+ tlb.insert(1, 11, 1, 2);
+ let _ = tlb.lookup(1, 10, 3);
+ tlb.insert(1, 12, 2, 4);
+
+ // FIFO expulsa la más antigua aunque se haya usado
+ assert_eq!(tlb.lookup(1, 10, 5), None);
+ assert_eq!(tlb.lookup(1, 11, 6), Some(1));
+ }
+
+ #[test]
+ fn test_tlb_flush_on_context_switch() {
+ let mut tlb = Tlb::new(4, TlbPolicy::Lru, true);
+ tlb.context_switch(1);
+ tlb.insert(1, 10, 0, 1);
+ tlb.context_switch(2);
+ tlb.context_switch(1);
+
+ assert_eq!(tlb.lookup(1, 10, 2), None);
+ assert_eq!(tlb.flushes(), 2);
+ }
+}
@@ -0,0 +1,126 @@
+//! Trazas de referencias a memoria leídas desde archivo
+//! Formatos: `pid op dirección` (ej: `1 W 0x1a2b3`) y la salida de Valgrind lackey (` L 04016f50,8`)
+//! El archivo se lee línea a línea, así una traza de millones de accesos no se carga entera
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+
+/// Tipo de referencia
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceOp {
+ Read,
+ Write,
+ Fetch, // Lectura de instrucción
+ Modify, // Lectura seguida de escritura
+}
+
+impl TraceOp {
+ pub fn is_write(self) -> bool {
+ matches!(self, TraceOp::Write | TraceOp::Modify)
+ }
+
+ fn parse(token: &str) -> Option<Self> {
+ match token.to_ascii_uppercase().as_str() {
+ "R" | "L" | "READ" => Some(TraceOp::Read),
+ "W" | "S" | "WRITE" => Some(TraceOp::Write),
+ "I" | "X" | "FETCH" => Some(TraceOp::Fetch),
+ "M" | "MODIFY" => Some(TraceOp::Modify),
+ _ => None,
+ }
+ }
+}
+
+/// Una referencia de la traza; lackey no trae pid
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceRecord {
+ pub pid: Option<u32>,
+ pub op: TraceOp,
+ pub address: u64,
+}
+
+/// Interpretar una línea; None si es comentario, vacía o cabecera de Valgrind
+pub fn parse_line(line: &str) -> Option<Result<TraceRecord, String>> {
+ let line = line.trim();
+ if line.is_empty() || line.starts_with('#') || line.starts_with("==") {
+ return None;
+ }
+
+ let tokens: Vec<&str> = line.split_whitespace().collect();
+ Some(match tokens.as_slice() {
+ // lackey: "I  0400d7d4,8" (dirección hexadecimal sin prefijo, tamaño tras la coma)
+ [op, address] => {
+ let hex = address.split(',').next().unwrap_or_default();
+ match (TraceOp::parse(op), u64::from_str_radix(hex, 16)) {
+ (Some(op), Ok(address)) => Ok(TraceRecord { pid: None, op, address }),
+ _ => Err(format!("Referencia lackey inválida: '{}'", line)),
+ }
+ }
+ [pid, op, address] => {
+ let parsed = match address.strip_prefix("0x").or_else(|| address.strip_prefix("0X")) {
+ Some(hex) => u64::from_str_radix(hex, 16),
+ None => address.parse::<u64>(),
+ };
+ match (pid.parse::<u32>(), TraceOp::parse(op), parsed) {
+ (Ok(pid), Some(op), Ok(address)) => Ok(TraceRecord { pid: Some(pid), op, address }),
+ _ => Err(format!("Referencia inválida (se espera 'pid op dirección'): '{}'", line)),
+ }
+ }
+ _ => Err(format!("Formato desconocido: '{}'", line)),
+ })
+}
+
+/// Iterador perezoso sobre las referencias de una traza
+pub struct TraceReader<R: BufRead> {
+ lines: Lines<R>,
+ line_no: usize,
+}
+
+impl TraceReader<BufReader<File>> {
+ pub fn open(path: &str) -> Result<Self, String> {
+ let file = File::open(path).map_err(|e| format!("No se pudo abrir la traza {}: {}", path, e))?;
+ Ok(Self::new(BufReader::new(file)))
+ }
+}
+
+impl<R: BufRead> TraceReader<R> {
+ pub fn new(reader: R) -> Self {
+ Self { lines: reader.lines(), line_no: 0 }
+ }
+}
+
+impl<R: BufRead> Iterator for TraceReader<R> {
+ type Item = Result<TraceRecord, String>;
+
+ fn next(&mut self) -> Option<Self::Item> {
+ loop {
+ let line = self.lines.next()?;
+ self.line_no += 1;
+ let line = match line {
+ Ok(line) => line,
+ Err(e) => return Some(Err(format!("Línea {}: {}", self.line_no, e))),
+ };
+ if let Some(record) = parse_line(&line) {
+ let line_no = self.line_no;
+ return Some(record.map_err(|e| format!("Línea {}: {}", line_no, e)));
+ }
+ }
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_reads_both_formats() {
+ let trace = "# traza de prueba\n1 R 0x1000\n2 w 8192\n==123== Lackey\nI  0400d7d4,8\n S 7ff000398,8\n1 Q 0x0\n";
+ let records: Vec<_> = TraceReader::new(trace.as_bytes()).collect();
+
+ assert_eq!(records.len(), 5);
+ assert_eq!(records[0], Ok(TraceRecord { pid: Some(1), op: TraceOp::Read, address: 0x1000 }));
+ assert_eq!(records[1], Ok(TraceRecord { pid: Some(2), op: TraceOp::Write, address: 8192 }));
+ assert_eq!(records[2], Ok(TraceRecord { pid: None, op: TraceOp::Fetch, address: 0x0400d7d4 }));
+ assert!(records[3].as_ref().unwrap().op.is_write());
+ assert!(records[4].as_ref().unwrap_err().starts_with("Línea 7"));
+ }
+}
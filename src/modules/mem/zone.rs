@@ -0,0 +1,196 @@
+//! Heap dividido en zonas (DMA, normal), cada una con su buddy allocator
+
+use serde::{Deserialize, Serialize};
+use super::buddy::{Block, BuddyAllocator, BuddyStats};
+
+/// Zonas de memoria física
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ZoneKind {
+ Dma, // Memoria baja, accesible por dispositivos
+ Normal,
+}
+
+impl ZoneKind {
+ /// Zonas a probar en orden: una petición normal puede caer en DMA, no al revés
+ pub fn fallback_order(self) -> &'static [ZoneKind] {
+ match self {
+ ZoneKind::Normal => &[ZoneKind::Normal, ZoneKind::Dma],
+ ZoneKind::Dma => &[ZoneKind::Dma],
+ }
+ }
+}
+
+/// Zona: rango de direcciones con su propio buddy allocator
+#[derive(Serialize, Deserialize)]
+pub struct MemoryZone {
+ pub kind: ZoneKind,
+ pub base: usize,
+ pub allocator: BuddyAllocator,
+}
+
+impl MemoryZone {
+ fn contains(&self, address: usize) -> bool {
+ address >= self.base && address < self.base + self.allocator.total_size()
+ }
+}
+
+/// Heap con zonas contiguas: DMA (si existe) en las direcciones bajas y luego la normal
+#[derive(Serialize, Deserialize)]
+pub struct ZonedHeap {
+ zones: Vec<MemoryZone>,
+}
+
+impl Default for ZonedHeap {
+ fn default() -> Self {
+ Self::new(4096, 64, 0).unwrap() // 4KB total, bloques mínimos de 64 bytes
+ }
+}
+
+impl ZonedHeap {
+ /// Crear heap; dma_size 0 deja solo la zona normal
+ pub fn new(normal_size: usize, min_block_size: usize, dma_size: usize) -> Result<Self, String> {
+ let mut zones = Vec::new();
+ if dma_size > 0 {
+ zones.push(MemoryZone {
+ kind: ZoneKind::Dma,
+ base: 0,
+ allocator: BuddyAllocator::new(dma_size, min_block_size).map_err(|e| format!("Zona DMA: {}", e))?,
+ });
+ }
+ zones.push(MemoryZone {
+ kind: ZoneKind::Normal,
+ base: dma_size,
+ allocator: BuddyAllocator::new(normal_size, min_block_size)?,
+ });
+ Ok(Self { zones })
+ }
+
+ pub fn zones(&self) -> &[MemoryZone] {
+ &self.zones
+ }
+
+ pub fn total_size(&self) -> usize {
+ self.zones.iter().map(|z| z.allocator.total_size()).sum()
+ }
+
+ pub fn set_clock(&mut self, now: u64) {
+ for zone in &mut self.zones {
+ zone.allocator.set_clock(now);
+ }
+ }
+
+ /// Bloques de un proceso en todas las zonas, con direcciones globales
+ pub fn blocks_of(&self, pid: u32) -> Vec<Block> {
+ self.zones.iter()
+ .flat_map(|z| z.allocator.blocks_of(pid).into_iter().map(move |mut b| {
+ b.address += z.base;
+ b
+ }))
+ .collect()
+ }
+
+ /// Liberar todo lo que posee un proceso; devuelve los bloques liberados
+ pub fn free_pid(&mut self, pid: u32) -> Vec<Block> {
+ self.zones.iter_mut()
+ .flat_map(|z| {
+ let base = z.base;
+ z.allocator.free_pid(pid).into_iter().map(move |mut b| {
+ b.address += base;
+ b
+ })
+ })
+ .collect()
+ }
+
+ /// Asignar en la zona pedida o en la siguiente de su orden de respaldo
+ pub fn alloc(&mut self, pid: u32, size: usize, kind: ZoneKind) -> Result<(usize, ZoneKind), String> {
+ let mut last_error = format!("No existe la zona {:?}", kind);
+ for zone_kind in kind.fallback_order() {
+ let Some(zone) = self.zones.iter_mut().find(|z| z.kind == *zone_kind) else {
+ continue;
+ };
+ match zone.allocator.alloc(pid, size) {
+ Ok(local) => {
+ if *zone_kind != kind {
+ println!("[Zonas] Zona {:?} sin espacio: asignado en {:?}", kind, zone_kind);
+ }
+ return Ok((zone.base + local, *zone_kind));
+ }
+ Err(e) => last_error = e,
+ }
+ }
+ Err(last_error)
+ }
+
+ fn zone_of(&mut self, address: usize) -> Result<&mut MemoryZone, String> {
+ self.zones.iter_mut()
+ .find(|z| z.contains(address))
+ .ok_or_else(|| format!("Dirección {} fuera del heap", address))
+ }
+
+ pub fn free(&mut self, address: usize) -> Result<(), String> {
+ let zone = self.zone_of(address)?;
+ zone.allocator.free(address - zone.base)
+ }
+
+ /// Redimensionar un bloque dentro de su zona
+ pub fn realloc(&mut self, address: usize, new_size: usize) -> Result<usize, String> {
+ let zone = self.zone_of(address)?;
+ let local = zone.allocator.realloc(address - zone.base, new_size)?;
+ Ok(zone.base + local)
+ }
+
+ /// Métricas agregadas de todas las zonas
+ pub fn stats(&self) -> BuddyStats {
+ let all: Vec<BuddyStats> = self.zones.iter().map(|z| z.allocator.stats()).collect();
+ let free_memory: usize = all.iter().map(|s| s.free_memory).sum();
+ let largest = self.zones.iter().map(|z| z.allocator.largest_free_block()).max().unwrap_or(0);
+
+ BuddyStats {
+ total_size: self.total_size(),
+ free_memory,
+ allocated_memory: all.iter().map(|s| s.allocated_memory).sum(),
+ free_blocks: all.iter().map(|s| s.free_blocks).sum(),
+ allocated_blocks: all.iter().map(|s| s.allocated_blocks).sum(),
+ total_allocations: all.iter().map(|s| s.total_allocations).sum(),
+ total_deallocations: all.iter().map(|s| s.total_deallocations).sum(),
+ internal_fragmentation: all.iter().map(|s| s.internal_fragmentation).sum(),
+ external_fragmentation: if free_memory == 0 {
+ 0.0
+ } else {
+ (1.0 - largest as f64 / free_memory as f64) * 100.0
+ },
+ }
+ }
+
+ pub fn display(&self) {
+ for zone in &self.zones {
+ let title = format!("ZONA {} - BASE {:#06x}", format!("{:?}", zone.kind).to_uppercase(), zone.base);
+ zone.allocator.display_zone(&title, zone.base);
+ }
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_zone_fallback() {
+ let mut heap = ZonedHeap::new(256, 64, 256).unwrap();
+
+ let (addr, zone) = heap.alloc(1, 200, ZoneKind::Normal).unwrap();
+ assert_eq!((addr, zone), (256, ZoneKind::Normal));
+
+ // Zona normal llena: la petición cae en DMA
+ let (addr, zone) = heap.alloc(1, 100, ZoneKind::Normal).unwrap();
+ assert_eq!((addr, zone), (0, ZoneKind::Dma));
+
+ // DMA no tiene respaldo
+ assert!(heap.alloc(2, 200, ZoneKind::Dma).is_err());
+
+ heap.free(256).unwrap();
+ assert_eq!(heap.stats().allocated_memory, 128);
+ assert!(ZonedHeap::new(256, 64, 100).is_err());
+ }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::rng::XorShift;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProcessState {
@@ -20,6 +21,81 @@ pub struct Process {
  pub start_time: Option<u64>, // Tiempo de primera ejecución
  pub finish_time: Option<u64>, // Tiempo de terminación
  pub io_operations: Vec<u64>, // Lista de operaciones I/O pendientes
+ #[serde(default)]
+ pub memory_pattern: Option<MemoryPattern>, // Referencias a memoria que genera al ejecutar
+ #[serde(default)]
+ pub page_faults: u64, // Fallos de página provocados en ejecución
+ #[serde(default)]
+ pub retry_page: Option<usize>, // Referencia que falló y se repite al reanudar
+ #[serde(default)]
+ pub priority: u8, // Prioridad efectiva (mayor número = más prioridad)
+ #[serde(default)]
+ pub base_priority: u8, // Prioridad asignada, sin herencia ni techo
+}
+
+/// Patrón de referencias a páginas que un proceso emite en cada tick
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MemoryPattern {
+ /// Cadena de referencias fija, recorrida cíclicamente
+ ReferenceString { pages: Vec<usize>, position: usize },
+ /// Modelo de localidad: accesos aleatorios dentro de una ventana que cambia de lugar cada fase
+ Locality {
+ locality_size: usize,
+ total_pages: usize,
+ phase_length: u64, // Referencias por fase
+ base: usize, // Primera página de la localidad actual
+ issued: u64,
+ rng: XorShift,
+ },
+}
+
+impl MemoryPattern {
+ pub fn reference_string(pages: Vec<usize>) -> Self {
+ MemoryPattern::ReferenceString { pages, position: 0 }
+ }
+
+ pub fn locality(locality_size: usize, total_pages: usize, phase_length: u64, seed: u64) -> Self {
+ let locality_size = locality_size.clamp(1, total_pages.max(1));
+ MemoryPattern::Locality {
+ locality_size,
+ total_pages: total_pages.max(locality_size),
+ phase_length: phase_length.max(1),
+ base: 0,
+ issued: 0,
+ rng: XorShift::new(seed),
+ }
+ }
+
+ /// Siguiente página referenciada
+ pub fn next_page(&mut self) -> Option<usize> {
+ match self {
+ MemoryPattern::ReferenceString { pages, position } => {
+ if pages.is_empty() {
+ return None;
+ }
+ let page = pages[*position % pages.len()];
+ *position = (*position + 1) % pages.len();
+ Some(page)
+ }
+ MemoryPattern::Locality { locality_size, total_pages, phase_length, base, issued, rng } => {
+ // Cambio de fase: la localidad se mueve a otra región
+ if *issued > 0 && issued.is_multiple_of(*phase_length) {
+ *base = rng.next_range(*total_pages - *locality_size + 1);
+ }
+ *issued += 1;
+ Some(*base + rng.next_range(*locality_size))
+ }
+ }
+ }
+
+ /// Descripción corta para listados
+ pub fn describe(&self) -> String {
+ match self {
+ MemoryPattern::ReferenceString { pages, .. } => format!("cadena de {} refs", pages.len()),
+ MemoryPattern::Locality { locality_size, total_pages, phase_length, .. } =>
+ format!("localidad {}/{} págs, fase {}", locality_size, total_pages, phase_length),
+ }
+ }
 }
 
 impl Process {
@@ -35,6 +111,11 @@ impl Process {
  start_time: None,
  finish_time: None,
  io_operations: Vec::new(),
+ memory_pattern: None,
+ page_faults: 0,
+ retry_page: None,
+ priority: 0,
+ base_priority: 0,
  }
  }
 
@@ -50,7 +131,18 @@ impl Process {
  start_time: None,
  finish_time: None,
  io_operations: Vec::new(),
+ memory_pattern: None,
+ page_faults: 0,
+ retry_page: None,
+ priority: 0,
+ base_priority: 0,
+ }
  }
+
+ /// Asignar el patrón de accesos a memoria
+ pub fn with_memory_pattern(mut self, pattern: Option<MemoryPattern>) -> Self {
+ self.memory_pattern = pattern;
+ self
  }
 
  /// Calcular tiempo de espera
@@ -93,4 +185,25 @@ impl Process {
  pub fn is_finished(&self) -> bool {
  self.remaining_burst == 0 || self.state == ProcessState::Terminated
  }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_reference_string_cycles() {
+ let mut pattern = MemoryPattern::reference_string(vec![1, 2, 3]);
+ let pages: Vec<_> = (0..5).filter_map(|_| pattern.next_page()).collect();
+ assert_eq!(pages, vec![1, 2, 3, 1, 2]);
+ }
+
+ #[test]
+ fn test_locality_stays_in_window() {
+ let mut pattern = MemoryPattern::locality(3, 20, 5, 7);
+ let pages: Vec<_> = (0..5).filter_map(|_| pattern.next_page()).collect();
+ // La primera fase empieza en la página 0
+ assert!(pages.iter().all(|p| *p < 3));
+ assert!((0..100).filter_map(|_| pattern.next_page()).all(|p| p < 20));
+ }
+}
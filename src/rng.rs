@@ -0,0 +1,48 @@
+//! Generador pseudoaleatorio determinista (xorshift64*) para simulaciones reproducibles
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XorShift {
+ state: u64,
+}
+
+impl XorShift {
+ pub fn new(seed: u64) -> Self {
+ // El estado 0 es un punto fijo de xorshift
+ Self { state: if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed } }
+ }
+
+ pub fn next_u64(&mut self) -> u64 {
+ let mut x = self.state;
+ x ^= x >> 12;
+ x ^= x << 25;
+ x ^= x >> 27;
+ self.state = x;
+ x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+ }
+
+ /// Entero en [0, n)
+ pub fn next_range(&mut self, n: usize) -> usize {
+ if n == 0 {
+ 0
+ } else {
+ (self.next_u64() % n as u64) as usize
+ }
+ }
+}
+
+#[cfg(test)]
+mod tests {
+ use super::*;
+
+ #[test]
+ fn test_same_seed_same_sequence() {
+ let mut a = XorShift::new(42);
+ let mut b = XorShift::new(42);
+ for _ in 0..100 {
+ assert_eq!(a.next_u64(), b.next_u64());
+ }
+ assert!((0..100).all(|_| a.next_range(7) < 7));
+ }
+}
@@ -5,12 +5,15 @@ use std::collections::VecDeque;
 pub trait Scheduler {
  fn push(&mut self, p: Process);
  fn next(&mut self) -> Option<Process>;
+ /// Retirar un proceso de la cola (suspensión o terminación)
+ fn remove(&mut self, pid: u32) -> Option<Process>;
  fn is_empty(&self) -> bool;
  fn len(&self) -> usize;
  fn name(&self) -> &str;
 }
 
 /// Implementación FIFO simple
+#[derive(Default)]
 pub struct FifoScheduler {
  queue: VecDeque<Process>,
 }
@@ -30,6 +33,11 @@ impl Scheduler for FifoScheduler {
  self.queue.pop_front()
  }
 
+ fn remove(&mut self, pid: u32) -> Option<Process> {
+ let idx = self.queue.iter().position(|p| p.pid == pid)?;
+ self.queue.remove(idx)
+ }
+
  fn is_empty(&self) -> bool {
  self.queue.is_empty()
  }
@@ -71,6 +79,11 @@ impl Scheduler for RoundRobinScheduler {
  self.queue.pop_front()
  }
 
+ fn remove(&mut self, pid: u32) -> Option<Process> {
+ let idx = self.queue.iter().position(|p| p.pid == pid)?;
+ self.queue.remove(idx)
+ }
+
  fn is_empty(&self) -> bool {
  self.queue.is_empty()
  }
@@ -85,6 +98,7 @@ impl Scheduler for RoundRobinScheduler {
 }
 
 /// Implementación SJF (Shortest Job First) no expropiativo
+#[derive(Default)]
 pub struct SjfScheduler {
  processes: Vec<Process>,
 }
@@ -110,6 +124,11 @@ impl Scheduler for SjfScheduler {
  }
  }
 
+ fn remove(&mut self, pid: u32) -> Option<Process> {
+ let idx = self.processes.iter().position(|p| p.pid == pid)?;
+ Some(self.processes.remove(idx))
+ }
+
  fn is_empty(&self) -> bool {
  self.processes.is_empty()
  }
@@ -123,6 +142,48 @@ impl Scheduler for SjfScheduler {
  }
 }
 
+/// Planificación por prioridad (mayor número primero, FIFO entre iguales)
+#[derive(Default)]
+pub struct PriorityScheduler {
+ processes: Vec<Process>,
+}
+
+impl PriorityScheduler {
+ pub fn new() -> Self {
+ Self { processes: Vec::new() }
+ }
+}
+
+impl Scheduler for PriorityScheduler {
+ fn push(&mut self, p: Process) {
+ self.processes.push(p);
+ }
+
+ fn next(&mut self) -> Option<Process> {
+ // El primero de los de mayor prioridad mantiene el orden de llegada
+ let best = self.processes.iter().map(|p| p.priority).max()?;
+ let idx = self.processes.iter().position(|p| p.priority == best)?;
+ Some(self.processes.remove(idx))
+ }
+
+ fn remove(&mut self, pid: u32) -> Option<Process> {
+ let idx = self.processes.iter().position(|p| p.pid == pid)?;
+ Some(self.processes.remove(idx))
+ }
+
+ fn is_empty(&self) -> bool {
+ self.processes.is_empty()
+ }
+
+ fn len(&self) -> usize {
+ self.processes.len()
+ }
+
+ fn name(&self) -> &str {
+ "Prioridad"
+ }
+}
+
 #[cfg(test)]
 mod tests {
  use super::*;
@@ -191,4 +252,29 @@ mod tests {
  assert_eq!(sjf.next().unwrap().pid, 3);
  assert_eq!(sjf.next().unwrap().pid, 1);
  }
+
+ #[test]
+ fn priority_highest_first() {
+ let mut s = PriorityScheduler::new();
+ for (pid, priority) in [(1, 1), (2, 5), (3, 5), (4, 2)] {
+ let mut p = Process::new(pid, 3, 4);
+ p.priority = priority;
+ s.push(p);
+ }
+
+ let order: Vec<u32> = std::iter::from_fn(|| s.next()).map(|p| p.pid).collect();
+ assert_eq!(order, vec![2, 3, 4, 1]);
+ }
+
+ #[test]
+ fn remove_from_queue() {
+ let mut rr = RoundRobinScheduler::new(2);
+ rr.push(Process::new(1, 5, 10));
+ rr.push(Process::new(2, 3, 8));
+
+ assert_eq!(rr.remove(1).unwrap().pid, 1);
+ assert!(rr.remove(1).is_none());
+ assert_eq!(rr.len(), 1);
+ assert_eq!(rr.next().unwrap().pid, 2);
+ }
 }
\ No newline at end of file